@@ -0,0 +1,53 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use plonky2_backend::noir_and_plonky2_serialization::{
+    deserialize_program_within_file_path, deserialize_witnesses_within_file_path,
+};
+use plonky2_backend::Plonky2Backend;
+
+/// Compares the proving time and the proof size of a precompiled Noir program with and without
+/// zero knowledge. The program must be compiled first (like for the precompiled tests).
+const PROGRAM_NAME: &str = "sha256_4";
+
+fn _program_path(file_name: &str) -> String {
+    format!(
+        "src/circuit_translation/tests/factories/noir_circuits_for_testing/{}/target/{}",
+        PROGRAM_NAME, file_name
+    )
+}
+
+fn bench_zero_knowledge_proving(c: &mut Criterion) {
+    let acir_program_json_path = _program_path("circuit.json");
+    let witness_stack_zip_path = _program_path("witness");
+    let acir_program = deserialize_program_within_file_path(&acir_program_json_path);
+
+    let mut group = c.benchmark_group("prove");
+    group.sample_size(10);
+    for zero_knowledge in [false, true] {
+        let backend = Plonky2Backend {
+            zero_knowledge,
+            ..Plonky2Backend::default()
+        };
+        let compiled_circuit = backend.compile(&acir_program);
+
+        let witness_stack = deserialize_witnesses_within_file_path(witness_stack_zip_path.clone());
+        let proof = backend.prove(&compiled_circuit, witness_stack).unwrap();
+        println!(
+            "{} (zk: {}): compressed proof of {} bytes",
+            PROGRAM_NAME,
+            zero_knowledge,
+            proof.to_bytes().len()
+        );
+
+        group.bench_function(format!("{} (zk: {})", PROGRAM_NAME, zero_knowledge), |b| {
+            b.iter_batched(
+                || deserialize_witnesses_within_file_path(witness_stack_zip_path.clone()),
+                |witness_stack| backend.prove(&compiled_circuit, witness_stack).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_zero_knowledge_proving);
+criterion_main!(benches);
@@ -16,7 +16,7 @@ def main(argc, argv):
         return
 
     try:
-        command = f"{custom_backend_path} prove -c ./target/{example_name}.json -w ./target/witness -o ./proof"
+        command = f"{custom_backend_path} prove -b ./target/{example_name}.json -w ./target/witness -o ./proof"
         result = subprocess.check_output(command, shell=True, text=True)
         print("Proof generated successfully")
     except Exception as e:
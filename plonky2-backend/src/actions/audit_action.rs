@@ -0,0 +1,156 @@
+use std::collections::{BTreeSet, HashMap};
+
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_data::CircuitData;
+use serde::Serialize;
+
+use super::*;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_debug_info::ProgramDebugInfo;
+
+/// The Audit Action looks for ACIR witnesses that end up unconstrained in the Plonky2 circuit: a
+/// witness whose target isn't a wire of any gate nor connected to one through copy constraints
+/// can take any value without affecting the proof. The typical case is the output of a
+/// BrilligCall that no later opcode uses, since Brillig calls only compute values.
+///
+/// The report is printed as JSON, with the unconstrained witnesses of each function along with
+/// the opcode that first uses them and its location in the Noir source code.
+pub struct AuditAction {
+    pub acir_program_json_path: String,
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AuditReport {
+    pub functions: Vec<FunctionAudit>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FunctionAudit {
+    pub name: String,
+    pub unconstrained_witnesses: Vec<UnconstrainedWitness>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct UnconstrainedWitness {
+    pub witness: u32,
+    /// First opcode using the witness, which is None for parameters no opcode uses.
+    pub opcode_index: Option<usize>,
+    pub opcode_kind: Option<String>,
+    pub source_locations: Vec<String>,
+}
+
+impl AuditAction {
+    pub fn run(&self) {
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
+        let report = self.generate_report(&program_json);
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("There was a problem serializing the report")
+        );
+    }
+
+    pub fn generate_report(&self, program_json: &serde_json::Value) -> AuditReport {
+        let program = deserialize_program(program_json);
+        let debug_info = ProgramDebugInfo::from_program_json(program_json);
+        let functions = (0..program.functions.len())
+            .map(|function_index| {
+                let name = function_name(program_json, function_index);
+                self.function_audit(name, &program, function_index, &debug_info)
+            })
+            .collect();
+        AuditReport { functions }
+    }
+
+    /// Each function is translated on its own with the options of the action, like prove does,
+    /// and with the Brillig functions of the program so the outputs of the Brillig calls get
+    /// their targets.
+    pub fn function_audit(
+        &self,
+        name: String,
+        program: &Program,
+        function_index: usize,
+        debug_info: &ProgramDebugInfo,
+    ) -> FunctionAudit {
+        let circuit = &program.functions[function_index];
+        let mut translator = Plonky2Backend {
+            field_conversion_mode: self.field_conversion_mode,
+            zero_knowledge: self.zero_knowledge,
+            sha256_implementation: self.sha256_implementation,
+        }
+        .translator();
+        translator.brillig_functions = program.unconstrained_functions.clone();
+        translator.translate_circuit(circuit);
+        let (circuit_data, witness_target_map) = translator.unpack();
+
+        let unconstrained_witnesses =
+            Self::unconstrained_witnesses(circuit, &circuit_data, &witness_target_map)
+                .into_iter()
+                .map(|witness| {
+                    let opcode_index = circuit
+                        .opcodes
+                        .iter()
+                        .position(|opcode| opcode_witnesses(opcode).contains(&witness));
+                    UnconstrainedWitness {
+                        witness: witness.0,
+                        opcode_index,
+                        opcode_kind: opcode_index
+                            .map(|opcode_index| opcode_kind_name(&circuit.opcodes[opcode_index])),
+                        source_locations: opcode_index
+                            .map(|opcode_index| {
+                                debug_info
+                                    .opcode_call_stack(function_index, opcode_index)
+                                    .iter()
+                                    .map(|location| format!("{}: {}", location, location.snippet))
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                    }
+                })
+                .collect();
+        FunctionAudit {
+            name,
+            unconstrained_witnesses,
+        }
+    }
+
+    /// Plonky2 groups the targets connected by copy constraints into partitions, and the prover
+    /// data keeps a representative for each target. A witness is constrained when its target
+    /// shares the partition with a wire. Witnesses of the circuit without a target at all are
+    /// unconstrained too.
+    pub fn unconstrained_witnesses(
+        circuit: &Circuit,
+        circuit_data: &CircuitData<F, C, D>,
+        witness_target_map: &HashMap<Witness, Target>,
+    ) -> Vec<Witness> {
+        let num_wires = circuit_data.common.config.num_wires;
+        let degree = circuit_data.common.degree();
+        let num_wire_targets = num_wires * degree;
+        let representative_map = &circuit_data.prover_only.representative_map;
+        let partitions_with_wires: BTreeSet<usize> = (0..num_wire_targets)
+            .map(|target_index| representative_map[target_index])
+            .collect();
+
+        Self::_circuit_witnesses(circuit)
+            .into_iter()
+            .filter(|witness| match witness_target_map.get(witness) {
+                Some(target) => {
+                    let target_index = target.index(num_wires, degree);
+                    !partitions_with_wires.contains(&representative_map[target_index])
+                }
+                None => true,
+            })
+            .collect()
+    }
+
+    fn _circuit_witnesses(circuit: &Circuit) -> BTreeSet<Witness> {
+        let mut witnesses: BTreeSet<Witness> = circuit.circuit_arguments();
+        witnesses.extend(circuit.return_values.0.iter().cloned());
+        for opcode in &circuit.opcodes {
+            witnesses.extend(opcode_witnesses(opcode));
+        }
+        witnesses
+    }
+}
@@ -0,0 +1,112 @@
+use acir::brillig::ForeignCallResult;
+use acir::native_types::WitnessMap;
+use acvm::pwg::{ACVMStatus, ACVM};
+use acvm_blackbox_solver::StubbedBlackBoxSolver;
+use noirc_abi::input_parser::Format;
+use noirc_abi::{Abi, MAIN_RETURN_NAME};
+
+use super::*;
+use crate::field_conversion::FieldConversionMode;
+
+/// The Execute Action replaces the `nargo execute` step: it reads the inputs of the program from
+/// a Prover.toml file through the ABI of the program, solves the rest of the witnesses with the
+/// ACVM (running the Brillig calls) and writes the resulting witness stack, which can then be
+/// used by the prove command.
+///
+/// The ACVM solves over Noir's FieldElement, so every solved witness is converted to Goldilocks
+/// with the same field conversion mode the prove command will use. This way a value that the
+/// strict mode rejects is reported here, naming the witness, instead of while proving.
+pub struct ExecuteAction {
+    pub acir_program_json_path: String,
+    pub prover_toml_path: String,
+    pub resulting_witness_file_path: String,
+    pub field_conversion_mode: FieldConversionMode,
+}
+
+impl ExecuteAction {
+    pub fn run(&self) {
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
+        let prover_toml = String::from_utf8(read_file_to_bytes(&self.prover_toml_path))
+            .expect("The inputs file is not valid UTF-8");
+
+        let witness_stack = self.solve_witness_stack(&program_json, &prover_toml);
+
+        let witness_stack_bytes: Vec<u8> = witness_stack
+            .try_into()
+            .expect("There was a problem serializing the witness stack");
+        write_bytes_to_file_path(witness_stack_bytes, &self.resulting_witness_file_path)
+    }
+
+    pub fn solve_witness_stack(
+        &self,
+        program_json: &serde_json::Value,
+        prover_toml: &str,
+    ) -> WitnessStack {
+        let program = deserialize_program(program_json);
+        let initial_witness = self.initial_witness_from_inputs(program_json, prover_toml);
+        let witness_map = self.solve_main_function(&program, initial_witness);
+        self._check_witnesses_are_goldilocks_elements(&witness_map);
+        WitnessStack::from(witness_map)
+    }
+
+    /// The inputs are encoded into the witnesses of the parameters following the ABI. If the
+    /// file has a return value, it's also encoded, so the ACVM checks it's the one returned.
+    pub fn initial_witness_from_inputs(
+        &self,
+        program_json: &serde_json::Value,
+        prover_toml: &str,
+    ) -> WitnessMap<FieldElement> {
+        let abi: Abi = serde_json::from_value(program_json["abi"].clone())
+            .expect("There was a problem parsing the ABI of the program");
+        let mut input_map = Format::Toml
+            .parse(prover_toml, &abi)
+            .unwrap_or_else(|error| panic!("Invalid inputs: {}", error));
+        let return_value = input_map.remove(MAIN_RETURN_NAME);
+        abi.encode(&input_map, return_value)
+            .unwrap_or_else(|error| panic!("The inputs don't match the ABI: {}", error))
+    }
+
+    /// Only the main function is solved, since it's the only one the backend translates. Printing
+    /// is the only foreign call supported, and it has no effect on the witnesses.
+    pub fn solve_main_function(
+        &self,
+        program: &Program,
+        initial_witness: WitnessMap<FieldElement>,
+    ) -> WitnessMap<FieldElement> {
+        let circuit = &program.functions[0];
+        let black_box_solver = StubbedBlackBoxSolver;
+        let mut acvm = ACVM::new(
+            &black_box_solver,
+            &circuit.opcodes,
+            initial_witness,
+            &program.unconstrained_functions,
+            &circuit.assert_messages,
+        );
+        loop {
+            match acvm.solve() {
+                ACVMStatus::Solved => break,
+                ACVMStatus::InProgress => {}
+                ACVMStatus::Failure(error) => panic!("Failed to solve the witnesses: {}", error),
+                ACVMStatus::RequiresForeignCall(foreign_call)
+                    if foreign_call.function == "print" =>
+                {
+                    acvm.resolve_pending_foreign_call(ForeignCallResult { values: vec![] });
+                }
+                ACVMStatus::RequiresForeignCall(foreign_call) => {
+                    panic!("Foreign call {} is not supported", foreign_call.function)
+                }
+                ACVMStatus::RequiresAcirCall(_) => {
+                    panic!("Calls to other ACIR functions are not supported yet")
+                }
+            }
+        }
+        acvm.finalize()
+    }
+
+    fn _check_witnesses_are_goldilocks_elements(&self, witness_map: &WitnessMap<FieldElement>) {
+        for (witness, value) in witness_map.clone().into_iter() {
+            self.field_conversion_mode
+                .field_element_to_goldilocks_field(&value, || format!("Witness {}", witness.0));
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use plonky2::field::types::PrimeField64;
+use plonky2::iop::target::Target;
+use plonky2::plonk::circuit_data::CircuitData;
+use serde::Serialize;
+
+use super::*;
+use crate::field_conversion::FieldConversionMode;
+
+/// Selector value used by Plonky2 for the rows that don't belong to a selector group. It mirrors
+/// the (crate private) UNUSED_SELECTOR constant of Plonky2.
+const UNUSED_SELECTOR: u64 = u32::MAX as u64;
+
+/// The Info Action translates the ACIR circuit into Plonky2 and reports the size of the resulting
+/// circuit. The report is printed as JSON following the structure of nargo's `info --json`
+/// output, extended with some Plonky2 specific information, so it can be tracked by the CI.
+pub struct InfoAction {
+    pub acir_program_json_path: String,
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
+}
+
+#[derive(Serialize)]
+pub struct ProgramsInfoReport {
+    pub programs: Vec<ProgramInfo>,
+}
+
+/// The report of Barretenberg's `gates` command, which is what Nargo expects from a backend: the
+/// functions of the program, without the package around them.
+#[derive(Serialize)]
+pub struct GatesReport {
+    pub functions: Vec<FunctionInfo>,
+}
+
+#[derive(Serialize)]
+pub struct ProgramInfo {
+    pub package_name: String,
+    pub functions: Vec<FunctionInfo>,
+}
+
+#[derive(Serialize)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub acir_opcodes: usize,
+    pub circuit_size: usize,
+    pub gates_per_opcode: Vec<usize>,
+    /// Rows of the circuit not attributed to any opcode: the memory checking arguments, the rows
+    /// laid out when the circuit is built (lookups, lookup tables, constants and public inputs
+    /// hashing) and the padding up to a power of two. The gate histogram of plonky2 breaks them
+    /// down by gate.
+    pub unattributed_gates: usize,
+    pub opcode_kinds: BTreeMap<String, OpcodeKindCost>,
+    pub plonky2: Plonky2CircuitInfo,
+}
+
+/// Amount of opcodes of a given kind and the gates they added to the circuit, including the gates
+/// of the range checks they requested. The rest of the rows are reported as unattributed_gates.
+#[derive(Serialize, Default)]
+pub struct OpcodeKindCost {
+    pub opcodes: usize,
+    pub gates: usize,
+}
+
+#[derive(Serialize)]
+pub struct Plonky2CircuitInfo {
+    pub degree: usize,
+    pub degree_bits: usize,
+    pub gates: BTreeMap<String, usize>,
+    pub num_public_inputs: usize,
+    /// ACIR witnesses whose target is a virtual target instead of a wire.
+    pub num_virtual_targets: usize,
+    pub num_lookup_tables: usize,
+}
+
+impl InfoAction {
+    pub fn run(&self) {
+        let report = self.generate_report();
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("There was a problem serializing the report")
+        );
+    }
+
+    pub fn run_gates(&self) {
+        let report = self.generate_gates_report();
+        println!(
+            "{}",
+            serde_json::to_string(&report).expect("There was a problem serializing the report")
+        );
+    }
+
+    pub fn generate_gates_report(&self) -> GatesReport {
+        let program_info = self.generate_report().programs.remove(0);
+        GatesReport {
+            functions: program_info.functions,
+        }
+    }
+
+    pub fn generate_report(&self) -> ProgramsInfoReport {
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
+        self.report_for_program_json(&program_json)
+    }
+
+    /// Every ACIR function of the program is reported, each one translated on its own with the
+    /// options of the action, which must be the ones of prove for the report to describe the
+    /// circuit being proven.
+    pub fn report_for_program_json(&self, program_json: &serde_json::Value) -> ProgramsInfoReport {
+        let acir_program: Program = deserialize_program(program_json);
+        let functions = acir_program
+            .functions
+            .iter()
+            .enumerate()
+            .map(|(index, circuit)| {
+                self.function_info(&function_name(program_json, index), circuit)
+            })
+            .collect();
+        ProgramsInfoReport {
+            programs: vec![ProgramInfo {
+                package_name: self._package_name(),
+                functions,
+            }],
+        }
+    }
+
+    pub fn function_info(&self, name: &str, circuit: &Circuit) -> FunctionInfo {
+        let mut translator = Plonky2Backend {
+            field_conversion_mode: self.field_conversion_mode,
+            zero_knowledge: self.zero_knowledge,
+            sha256_implementation: self.sha256_implementation,
+        }
+        .translator();
+        translator.translate_circuit(circuit);
+        let CircuitBuilderFromAcirToPlonky2 {
+            builder,
+            witness_target_map,
+            memory_blocks: _,
+            memory_traces: _,
+            memory_checking_threshold: _,
+            field_conversion_mode: _,
+            solved_witnesses: _,
+            brillig_functions: _,
+            acir_expression_gate: _,
+            translation_cache: _,
+            sha256_implementation: _,
+            bitwise_lookup_threshold: _,
+            bitwise_lookup_tables: _,
+            blocks_written_at_non_constant_indices: _,
+            opcode_gate_counts,
+        } = translator;
+        let circuit_data = builder.build::<C>();
+
+        let num_virtual_targets = witness_target_map
+            .values()
+            .filter(|target| matches!(target, Target::VirtualTarget { .. }))
+            .count();
+        let attributed_gates: usize = opcode_gate_counts.iter().sum();
+
+        FunctionInfo {
+            name: String::from(name),
+            acir_opcodes: circuit.opcodes.len(),
+            circuit_size: circuit_data.common.degree(),
+            unattributed_gates: circuit_data.common.degree() - attributed_gates,
+            opcode_kinds: Self::_opcode_kinds_cost(circuit, &opcode_gate_counts),
+            gates_per_opcode: opcode_gate_counts,
+            plonky2: Plonky2CircuitInfo {
+                degree: circuit_data.common.degree(),
+                degree_bits: circuit_data.common.degree_bits(),
+                gates: Self::gate_histogram(&circuit_data),
+                num_public_inputs: circuit_data.common.num_public_inputs,
+                num_virtual_targets,
+                num_lookup_tables: circuit_data.common.luts.len(),
+            },
+        }
+    }
+
+    fn _opcode_kinds_cost(
+        circuit: &Circuit,
+        opcode_gate_counts: &Vec<usize>,
+    ) -> BTreeMap<String, OpcodeKindCost> {
+        let mut opcode_kinds: BTreeMap<String, OpcodeKindCost> = BTreeMap::new();
+        for (opcode, gates) in circuit.opcodes.iter().zip(opcode_gate_counts) {
+            let cost = opcode_kinds.entry(opcode_kind_name(opcode)).or_default();
+            cost.opcodes += 1;
+            cost.gates += gates;
+        }
+        opcode_kinds
+    }
+
+    /// The CircuitBuilder doesn't expose its gate instances, but the built circuit does: every row
+    /// has a selector polynomial that evaluates to the index of its gate (within the common
+    /// data gates) in the row, and to UNUSED_SELECTOR in the rest of the selector polynomials.
+    pub fn gate_histogram(circuit_data: &CircuitData<F, C, D>) -> BTreeMap<String, usize> {
+        let common = &circuit_data.common;
+        let num_selectors = common.selectors_info.num_selectors();
+        let selector_values: Vec<Vec<F>> = circuit_data
+            .prover_only
+            .constants_sigmas_commitment
+            .polynomials[..num_selectors]
+            .iter()
+            .map(|selector_polynomial| selector_polynomial.clone().fft().values)
+            .collect();
+
+        let mut histogram: BTreeMap<String, usize> = BTreeMap::new();
+        for row in 0..common.degree() {
+            let gate_index = selector_values
+                .iter()
+                .map(|values| values[row].to_canonical_u64())
+                .find(|selector_value| *selector_value != UNUSED_SELECTOR)
+                .expect("Every row of the circuit should have a gate") as usize;
+            let gate_name = common.gates[gate_index].0.id();
+            *histogram.entry(gate_name).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Nargo names the compiled program after the package, so the file name is used as the
+    /// package name.
+    fn _package_name(&self) -> String {
+        Path::new(&self.acir_program_json_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(String::from)
+            .unwrap_or_default()
+    }
+}
@@ -1,5 +1,20 @@
 use super::*;
 
+pub mod audit_action;
+pub mod execute_action;
+pub mod info_action;
+pub mod profile_action;
 pub mod prove_action;
+pub mod prove_and_verify_action;
 pub mod verify_action;
 pub mod write_vk_action;
+
+/// Nargo lists the names of the ACIR functions in the program json (main is always the first
+/// one). Programs without them get generic names.
+pub fn function_name(program_json: &serde_json::Value, function_index: usize) -> String {
+    match program_json["names"][function_index].as_str() {
+        Some(name) => String::from(name),
+        None if function_index == 0 => String::from("main"),
+        None => format!("function_{}", function_index),
+    }
+}
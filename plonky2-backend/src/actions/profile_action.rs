@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_debug_info::ProgramDebugInfo;
+
+use super::*;
+
+/// The Profile Action translates every ACIR function of the program while recording how many
+/// gates each opcode added to the Plonky2 circuit, and attributes those gates to the Noir source
+/// code through the debug symbols of the program. The result is written in the folded stacks
+/// format, one line per call stack ("function;frame;...;opcode_kind gates"), which tools like
+/// inferno or flamegraph.pl turn into a flamegraph. The rows of the built circuit that no opcode
+/// added (see unattributed_gates in the info report) get a stack of their own,
+/// "function;unattributed". The circuits are translated with the same options as prove, so the
+/// gates are the ones of the circuit being proven.
+pub struct ProfileAction {
+    pub acir_program_json_path: String,
+    pub resulting_folded_stacks_file_path: String,
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
+}
+
+impl ProfileAction {
+    pub fn run(&self) {
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
+        let folded_stacks = self.folded_stacks_of_program_json(&program_json);
+        write_bytes_to_file_path(
+            folded_stacks.into_bytes(),
+            &self.resulting_folded_stacks_file_path,
+        );
+    }
+
+    pub fn folded_stacks_of_program_json(&self, program_json: &serde_json::Value) -> String {
+        let acir_program = deserialize_program(program_json);
+        let debug_info = ProgramDebugInfo::from_program_json(program_json);
+        let mut folded_stacks = String::new();
+        for (function_index, circuit) in acir_program.functions.iter().enumerate() {
+            let mut translator = Plonky2Backend {
+                field_conversion_mode: self.field_conversion_mode,
+                zero_knowledge: self.zero_knowledge,
+                sha256_implementation: self.sha256_implementation,
+            }
+            .translator();
+            translator.translate_circuit(circuit);
+            let CircuitBuilderFromAcirToPlonky2 {
+                builder,
+                opcode_gate_counts,
+                ..
+            } = translator;
+            let circuit_size = builder.build::<C>().common.degree();
+            let unattributed_gates = circuit_size - opcode_gate_counts.iter().sum::<usize>();
+
+            folded_stacks.push_str(&Self::folded_stacks(
+                &function_name(program_json, function_index),
+                circuit,
+                &opcode_gate_counts,
+                unattributed_gates,
+                &debug_info,
+                function_index,
+            ));
+        }
+        folded_stacks
+    }
+
+    /// Identical call stacks are merged, and opcodes that didn't add any gates are left out since
+    /// they don't show up in a flamegraph anyway.
+    pub fn folded_stacks(
+        function_name: &str,
+        circuit: &Circuit,
+        opcode_gate_counts: &Vec<usize>,
+        unattributed_gates: usize,
+        debug_info: &ProgramDebugInfo,
+        function_index: usize,
+    ) -> String {
+        let mut gates_per_stack: BTreeMap<String, usize> = BTreeMap::new();
+        for (opcode_index, (opcode, gates)) in
+            circuit.opcodes.iter().zip(opcode_gate_counts).enumerate()
+        {
+            if *gates == 0 {
+                continue;
+            }
+            let mut frames = vec![String::from(function_name)];
+            frames.extend(
+                debug_info
+                    .opcode_call_stack(function_index, opcode_index)
+                    .iter()
+                    .map(|location| Self::_frame_name(&location.to_string(), &location.snippet)),
+            );
+            frames.push(opcode_kind_name(opcode));
+            *gates_per_stack.entry(frames.join(";")).or_insert(0) += gates;
+        }
+        if unattributed_gates > 0 {
+            let stack = format!("{};unattributed", function_name);
+            gates_per_stack.insert(stack, unattributed_gates);
+        }
+
+        gates_per_stack
+            .iter()
+            .map(|(stack, gates)| format!("{} {}\n", stack, gates))
+            .collect()
+    }
+
+    /// Semicolons separate the frames in the folded format, so they can't be part of a frame.
+    fn _frame_name(location: &str, snippet: &str) -> String {
+        format!("{}::{}", location, snippet).replace(';', ",")
+    }
+}
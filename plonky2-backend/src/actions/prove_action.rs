@@ -1,90 +1,137 @@
 use std::collections::HashMap;
 
-use num_bigint::BigUint;
-
 use plonky2::field::goldilocks_field::GoldilocksField;
-use plonky2::field::types::Field;
 use plonky2::iop::target::Target;
-use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::iop::witness::PartialWitness;
 use plonky2::plonk::circuit_data::CircuitData;
 use plonky2::plonk::proof::ProofWithPublicInputs;
 
 use super::*;
+use crate::constraint_debugger::ConstraintDebugger;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_debug_info::ProgramDebugInfo;
 
 /// The Prove Action will read the ACIR circuit and the witnesses generated by Nargo, translate the
 /// circuit to Plonky2 and then use the provided witnesses to generate the proof. Lastly, te proof
 /// will be saved.
 
+/// In debug mode, the witnesses are checked against the ACIR opcodes and the targets of the
+/// circuit before proving (see the constraint_debugger module), so a witness that doesn't satisfy
+/// the circuit is reported in terms of the Noir program instead of a Plonky2 error.
+
 /// This action could be split into 1) the translation of the circuit and 2) the generation of the
 /// proof since the latter can be performed multiple times with different inputs for the same circuit
 pub struct ProveAction {
     pub acir_program_json_path: String,
     pub witness_stack_zip_path: String,
     pub resulting_proof_file_path: String,
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
+    pub debug: bool,
 }
 
 impl ProveAction {
     pub fn run(&self) {
-        let acir_program: Program =
-            deserialize_program_within_file_path(&self.acir_program_json_path);
+        let backend = self.backend();
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
         let witness_stack: WitnessStack =
             deserialize_witnesses_within_file_path(self.witness_stack_zip_path.clone());
 
-        let circuit = &acir_program.functions[0];
-        let (circuit_data, witness_target_map) =
-            self.generate_plonky2_circuit_from_acir_circuit(circuit);
-        let proof = self.generate_serialized_plonky2_proof(
-            witness_stack,
-            &witness_target_map,
-            &circuit_data,
-        );
+        let compiled_circuit = backend.compile_program_json(&program_json);
+        if self.debug {
+            let acir_program: Program = deserialize_program(&program_json);
+            let debug_info = ProgramDebugInfo::from_program_json(&program_json);
+            self.check_witness_stack(
+                &acir_program.functions[0],
+                &witness_stack,
+                &debug_info,
+                &compiled_circuit.witness_target_map,
+                &compiled_circuit.circuit_data,
+            )
+            .unwrap_or_else(|report| panic!("{}", report));
+        }
+        let proof = backend
+            .prove(&compiled_circuit, witness_stack)
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        self._write_proof_into_file(proof.to_bytes(), &self.resulting_proof_file_path);
+    }
 
-        self._write_proof_into_file(proof, &self.resulting_proof_file_path);
+    /// The library API with the options of the command.
+    pub fn backend(&self) -> Plonky2Backend {
+        Plonky2Backend {
+            field_conversion_mode: self.field_conversion_mode,
+            zero_knowledge: self.zero_knowledge,
+            sha256_implementation: self.sha256_implementation,
+        }
     }
 
     fn _write_proof_into_file(&self, proof: Vec<u8>, proof_path: &String) {
         write_bytes_to_file_path(proof, proof_path)
     }
 
+    /// Unlike generate_plonky2_circuit_from_acir_circuit, the Brillig functions of the program
+    /// are known, so the circuit can generate the outputs of the Brillig calls.
+    pub fn generate_plonky2_circuit_from_acir_program(
+        &self,
+        program: &Program,
+    ) -> (CircuitData<F, C, 2>, HashMap<Witness, Target>) {
+        let mut translator = self.backend().translator();
+        translator.translate_program(program);
+        translator.unpack()
+    }
+
     pub fn generate_plonky2_circuit_from_acir_circuit(
         &self,
         circuit: &Circuit,
     ) -> (CircuitData<F, C, 2>, HashMap<Witness, Target>) {
-        let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+        let mut translator = self.backend().translator();
         translator.translate_circuit(circuit);
         translator.unpack()
     }
 
-    /// This is just a transformation from the Noir's FieldElement to the Plonky2 GoldilocksField
-    fn _field_element_to_goldilocks_field(&self, fe: &FieldElement) -> F {
-        let fe_as_big_uint = BigUint::from_bytes_be(&fe.to_be_bytes() as &[u8]);
-        F::from_noncanonical_biguint(fe_as_big_uint)
-    }
-
-    fn generate_serialized_plonky2_proof(
+    /// Checks the opcodes first, since a violated opcode also makes the circuit generate different
+    /// values, and its report is more precise.
+    pub fn check_witness_stack(
         &self,
-        mut witness_stack: WitnessStack,
+        circuit: &Circuit,
+        witness_stack: &WitnessStack,
+        debug_info: &ProgramDebugInfo,
         witness_target_map: &HashMap<Witness, Target>,
         circuit_data: &CircuitData<F, C, 2>,
-    ) -> Vec<u8> {
-        let proof = self.generate_plonky2_proof_from_witness_stack(
-            &mut witness_stack,
-            witness_target_map,
-            circuit_data,
-        );
-        let verifier_data_digest = &circuit_data.verifier_only.circuit_digest;
-        let common = &circuit_data.common;
-        let compressed_proof = proof.compress(verifier_data_digest, common).unwrap();
-        compressed_proof.to_bytes()
+    ) -> Result<(), String> {
+        let witness_map = &witness_stack.peek().unwrap().witness;
+        let mut debugger =
+            ConstraintDebugger::new_for(circuit, witness_map, self.field_conversion_mode);
+        if let Some(violation) = debugger.first_violated_opcode() {
+            return Err(violation.report(debug_info, 0));
+        }
+        let target_mismatch = debugger
+            .first_mismatched_target(circuit_data, witness_target_map)
+            .map_err(|error| {
+                format!("The Plonky2 circuit failed generating its targets: {}", error)
+            })?;
+        match target_mismatch {
+            Some(mismatch) => {
+                let opcode_index = debugger.first_opcode_using(mismatch.witness);
+                Err(mismatch.report(debug_info, 0, opcode_index))
+            }
+            None => Ok(()),
+        }
     }
 
     pub fn generate_plonky2_proof_from_witness_stack(
         &self,
-        mut witness_stack: &mut WitnessStack,
+        witness_stack: &mut WitnessStack,
         witness_target_map: &HashMap<Witness, Target>,
         circuit_data: &CircuitData<GoldilocksField, C, 2>,
     ) -> ProofWithPublicInputs<GoldilocksField, C, 2> {
-        let witnesses = self._extract_witnesses(&mut witness_stack, witness_target_map);
+        let main_witnesses = WitnessStack::from(witness_stack.pop().unwrap().witness);
+        let witnesses = self
+            .backend()
+            .partial_witness(main_witnesses, witness_target_map)
+            .unwrap_or_else(|error| panic!("{}", error));
         self.generate_plonky2_proof_from_partial_witnesses(circuit_data, witnesses)
     }
 
@@ -95,24 +142,4 @@ impl ProveAction {
     ) -> ProofWithPublicInputs<GoldilocksField, C, 2> {
         circuit_data.prove(witnesses).unwrap()
     }
-
-    /// All the witnesses used in the ACIR code have an "equivalent" Target in the Plonky2
-    /// circuit. During the translation process we maintain an injective mapping between those, in
-    /// part for assigning the values to the targets when generating the proof.
-    fn _extract_witnesses(
-        &self,
-        witness_stack: &mut WitnessStack,
-        witness_target_map: &HashMap<Witness, Target>,
-    ) -> PartialWitness<GoldilocksField> {
-        let mut witnesses = PartialWitness::<F>::new();
-        let witness_map = witness_stack.pop().unwrap().witness;
-        for (witness, value) in witness_map.into_iter() {
-            let plonky2_target = witness_target_map.get(&witness).unwrap();
-            witnesses.set_target(
-                *plonky2_target,
-                self._field_element_to_goldilocks_field(&value),
-            );
-        }
-        witnesses
-    }
 }
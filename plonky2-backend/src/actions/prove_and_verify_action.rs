@@ -0,0 +1,39 @@
+use super::*;
+use crate::field_conversion::FieldConversionMode;
+
+/// The Prove and Verify Action generates a proof like the Prove Action and verifies it right away
+/// with the verifying key of the same circuit, without writing either of them. It fails if the
+/// proof can't be generated or doesn't verify, so it's a quick check that the witnesses satisfy
+/// the program.
+pub struct ProveAndVerifyAction {
+    pub acir_program_json_path: String,
+    pub witness_stack_zip_path: String,
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
+}
+
+impl ProveAndVerifyAction {
+    pub fn run(&self) {
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
+        let witness_stack: WitnessStack =
+            deserialize_witnesses_within_file_path(self.witness_stack_zip_path.clone());
+        self.prove_and_verify(&program_json, witness_stack)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    pub fn prove_and_verify(
+        &self,
+        program_json: &serde_json::Value,
+        witness_stack: WitnessStack,
+    ) -> Result<PublicInputs, String> {
+        let backend = Plonky2Backend {
+            field_conversion_mode: self.field_conversion_mode,
+            zero_knowledge: self.zero_knowledge,
+            sha256_implementation: self.sha256_implementation,
+        };
+        let compiled_circuit = backend.compile_program_json(program_json);
+        let proof = backend.prove(&compiled_circuit, witness_stack)?;
+        backend.verify(&backend.verification_key(&compiled_circuit), &proof)
+    }
+}
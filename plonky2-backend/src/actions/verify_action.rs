@@ -1,18 +1,73 @@
 use super::*;
+use crate::field_conversion::FieldConversionMode;
+use crate::file_format::to_hex;
+use plonky2::plonk::config::GenericHashOut;
 
 /// The verify action will receive a proof path and a verification key path, and verify the program
-/// execution using those values.
+/// execution using those values. If the path of the ACIR program is also given, it first checks
+/// that the verification key was generated from that program, translating it with the given
+/// field conversion mode and SHA256 implementation.
 pub struct VerifyAction {
     pub proof_path: String,
     pub vk_path: String,
+    pub acir_program_json_path: Option<String>,
+    pub field_conversion_mode: FieldConversionMode,
+    pub sha256_implementation: Sha256Implementation,
 }
 
 impl VerifyAction {
     pub fn run(&self) {
-        let verifier_data = deserialize_verifying_key_within_file_path(&self.vk_path);
-        let compressed_proof = deserialize_proof_within_file_path(&self.proof_path, &verifier_data);
-        verifier_data
-            .verify_compressed(compressed_proof)
-            .expect("Verification failed");
+        let verifying_key = VerifyingKey::from_bytes(&read_file_to_bytes(&self.vk_path))
+            .unwrap_or_else(|error| panic!("Invalid verifying key {}: {}", self.vk_path, error));
+        if let Some(acir_program_json_path) = &self.acir_program_json_path {
+            let program_json = read_program_json_within_file_path(acir_program_json_path);
+            self.check_verifying_key_matches_program(&verifying_key, &program_json)
+                .unwrap_or_else(|error| panic!("{}", error));
+        }
+        let proof = Proof::from_bytes(&read_file_to_bytes(&self.proof_path), &verifying_key)
+            .unwrap_or_else(|error| panic!("Invalid proof {}: {}", self.proof_path, error));
+        Plonky2Backend::default()
+            .verify(&verifying_key, &proof)
+            .unwrap_or_else(|error| panic!("{}", error));
+    }
+
+    /// The program hash recorded in the key must be the one of the program, and translating the
+    /// program again (with the same zero knowledge setting, and the field conversion mode and
+    /// SHA256 implementation of the action) must give the same circuit digest.
+    pub fn check_verifying_key_matches_program(
+        &self,
+        verifying_key: &VerifyingKey,
+        program_json: &serde_json::Value,
+    ) -> Result<(), String> {
+        let expected_program_hash = program_hash(program_json);
+        if verifying_key.program_hash != expected_program_hash {
+            return Err(format!(
+                "The verification key was generated from the ACIR program with hash 0x{} but the \
+                 given program has hash 0x{}",
+                to_hex(&verifying_key.program_hash),
+                to_hex(&expected_program_hash)
+            ));
+        }
+
+        let backend = Plonky2Backend {
+            field_conversion_mode: self.field_conversion_mode,
+            zero_knowledge: verifying_key.verifier_data.common.config.zero_knowledge,
+            sha256_implementation: self.sha256_implementation,
+        };
+        let expected_circuit_digest = backend
+            .compile_program_json(program_json)
+            .circuit_data
+            .verifier_only
+            .circuit_digest;
+        let circuit_digest = verifying_key.verifier_data.verifier_only.circuit_digest;
+        if circuit_digest != expected_circuit_digest {
+            return Err(format!(
+                "The verification key has circuit digest 0x{} but the given program translates to \
+                 a circuit with digest 0x{}",
+                to_hex(&circuit_digest.to_bytes()),
+                to_hex(&expected_circuit_digest.to_bytes())
+            ));
+        }
+        Ok(())
     }
 }
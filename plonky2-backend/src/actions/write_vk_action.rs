@@ -23,13 +23,18 @@ use crate::plonky2_ecdsa::biguint::gates::arithmetic_u32::U32ArithmeticGate;
 use crate::plonky2_ecdsa::biguint::gates::comparison::ComparisonGate;
 use crate::plonky2_ecdsa::biguint::gates::range_check_u32::U32RangeCheckGate;
 use crate::plonky2_ecdsa::biguint::gates::subtraction_u32::U32SubtractionGate;
+use crate::circuit_translation::acir_expression_gate::AcirExpressionGate;
 use super::*;
+use crate::field_conversion::FieldConversionMode;
 
 /// The Write Verification Key Action will translate the ACIR circuit into the Plonky2 circuit
 /// (again) and write the necessary data for the Verifier to verify the computation.
 pub struct WriteVKAction {
     pub acir_program_json_path: String,
     pub vk_path_output: String,
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
 }
 
 pub struct BackendGateSerializer;
@@ -57,26 +62,26 @@ impl<F: RichField + Extendable<D>, const D: usize> GateSerializer<F, D> for Back
             U32AddManyGate<F,D>,
             U32ArithmeticGate<F,D>,
             U32RangeCheckGate<F,D>,
-            U32SubtractionGate<F,D>
+            U32SubtractionGate<F,D>,
+            AcirExpressionGate
         }
 }
 
 impl WriteVKAction {
     pub fn run(&self) {
-        let acir_program: Program =
-            deserialize_program_within_file_path(&self.acir_program_json_path);
-        let acir_circuit = &acir_program.functions[0];
-        let mut translator = CircuitBuilderFromAcirToPlonky2::new();
-        translator.translate_circuit(acir_circuit);
-        let CircuitBuilderFromAcirToPlonky2 {
-            builder,
-            witness_target_map: _,
-            memory_blocks: _,
-        } = translator;
-        let plonky2_circuit = builder.build::<C>();
-        let verifier_data = plonky2_circuit.verifier_data();
-        let gate_serializer = BackendGateSerializer;
-        let serialized_verifier_data = verifier_data.to_bytes(&gate_serializer).unwrap();
-        write_bytes_to_file_path(serialized_verifier_data, &self.vk_path_output);
+        let program_json = read_program_json_within_file_path(&self.acir_program_json_path);
+        let verifying_key = self.generate_verifying_key(&program_json);
+        write_bytes_to_file_path(verifying_key.to_bytes(), &self.vk_path_output);
+    }
+
+    /// The verifying key records the hash of the program, so it can be told apart from the key of
+    /// another program (see VerifyAction).
+    pub fn generate_verifying_key(&self, program_json: &serde_json::Value) -> VerifyingKey {
+        let backend = Plonky2Backend {
+            field_conversion_mode: self.field_conversion_mode,
+            zero_knowledge: self.zero_knowledge,
+            sha256_implementation: self.sha256_implementation,
+        };
+        backend.verification_key(&backend.compile_program_json(program_json))
     }
 }
@@ -1,56 +1,225 @@
 use crate::actions;
+use crate::circuit_translation::Sha256Implementation;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_and_plonky2_serialization::STANDARD_STREAM_PATH;
 use clap::{value_parser, Arg, ArgMatches, Command};
 use std::path::PathBuf;
+use std::time::Instant;
 
-/// Commands: prove, write_vk, verify.
-///     prove -b circuit/path -w witness/path.gz -o output/proof/path
-///     write_vk -b circuit/path -o output/verification/key/path
-///     verify -k verification/key/path -p proof/path
+/// The default paths of Barretenberg, relative to the directory of the Noir project.
+const DEFAULT_CIRCUIT_PATH: &str = "./target/program.json";
+const DEFAULT_WITNESS_PATH: &str = "./target/witness.gz";
+const DEFAULT_PROOF_PATH: &str = "./proofs/proof";
+const DEFAULT_VK_PATH: &str = "./target/vk";
+
+/// Commands: execute, prove, write_vk, verify, prove_and_verify, gates, info, profile, audit.
+///     execute -b circuit/path -i inputs/Prover.toml -o output/witness/path.gz [--strict-field]
+///     prove [-b circuit/path] [-w witness/path.gz] [-o output/proof/path] [--strict-field]
+///           [--zk <bool>] [--bitwise-sha256] [--debug]
+///     write_vk [-b circuit/path] [-o output/verification/key/path] [--strict-field] [--zk <bool>]
+///              [--bitwise-sha256]
+///     verify [-k verification/key/path] [-p proof/path] [--program circuit/path] [--strict-field]
+///            [--bitwise-sha256]
+///     prove_and_verify [-b circuit/path] [-w witness/path.gz] [--strict-field] [--zk <bool>]
+///                      [--bitwise-sha256]
+///     gates [-b circuit/path] [--strict-field] [--zk <bool>] [--bitwise-sha256]
+///     info -b circuit/path [--strict-field] [--zk <bool>] [--bitwise-sha256]
+///     profile -b circuit/path -o output/folded/stacks/path [--strict-field] [--zk <bool>]
+///             [--bitwise-sha256]
+///     audit -b circuit/path [--strict-field] [--zk <bool>] [--bitwise-sha256]
+///
+/// The commands Nargo uses from a backend (prove, write_vk, verify, prove_and_verify and gates)
+/// follow Barretenberg's command line, so this binary can be used in its place: their paths
+/// default to the ones of Barretenberg, "-" reads a path from the standard input or writes it to
+/// the standard output, and every command accepts (and ignores) the -c path of the CRS, since
+/// Plonky2 doesn't need one. With -v the commands report how long they took on stderr.
 
 pub fn parse_and_execute_commands() {
+    let execute_command = _create_execute_command();
     let prove_command = _create_prove_command();
     let write_vk_command = _create_write_vk_command();
     let verify_command = _create_verify_command();
+    let prove_and_verify_command = _create_prove_and_verify_command();
+    let gates_command = _create_gates_command();
+    let info_command = _create_info_command();
+    let profile_command = _create_profile_command();
+    let audit_command = _create_audit_command();
 
     let main_command = Command::new("plonky2_backend")
         .subcommand_required(true)
+        .arg(_argument_crs_path())
+        .arg(_argument_verbose())
+        .subcommand(execute_command.clone())
         .subcommand(prove_command.clone())
         .subcommand(write_vk_command.clone())
-        .subcommand(verify_command.clone());
+        .subcommand(verify_command.clone())
+        .subcommand(prove_and_verify_command.clone())
+        .subcommand(gates_command.clone())
+        .subcommand(info_command.clone())
+        .subcommand(profile_command.clone())
+        .subcommand(audit_command.clone());
 
     _match_command_values(
+        execute_command,
         prove_command,
         write_vk_command,
         verify_command,
+        prove_and_verify_command,
+        gates_command,
+        info_command,
+        profile_command,
+        audit_command,
         main_command,
     );
 }
 
 fn _match_command_values(
+    execute_command: Command,
     prove_command: Command,
     write_vk_command: Command,
     verify_command: Command,
+    prove_and_verify_command: Command,
+    gates_command: Command,
+    info_command: Command,
+    profile_command: Command,
+    audit_command: Command,
     main_command: Command,
 ) {
     let matches = main_command.get_matches();
-    if let Some(subcommand_matches) = matches.subcommand_matches(prove_command.get_name()) {
+    let Some((command_name, command_matches)) = matches.subcommand() else {
+        return;
+    };
+    let verbose = _get_flag_value(command_matches, _argument_verbose());
+    let start = Instant::now();
+
+    if let Some(subcommand_matches) = matches.subcommand_matches(execute_command.get_name()) {
+        let circuit_path =
+            _get_argument_value(subcommand_matches, _execute_argument_circuit_path());
+        let inputs_path = _get_argument_value(subcommand_matches, _execute_argument_inputs_path());
+        let output_path = _get_argument_value(subcommand_matches, _execute_argument_output_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+
+        _execute_execute_command(circuit_path, inputs_path, output_path, strict_field);
+    } else if let Some(subcommand_matches) = matches.subcommand_matches(prove_command.get_name()) {
         let circuit_path = _get_argument_value(subcommand_matches, _prove_argument_circuit_path());
         let witness_path = _get_argument_value(subcommand_matches, _prove_argument_witness_path());
         let output_path = _get_argument_value(subcommand_matches, _prove_argument_output_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
+        let debug = _get_flag_value(subcommand_matches, _prove_argument_debug());
+        _check_at_most_one_standard_input(&[circuit_path, witness_path]);
 
-        _execute_prove_command(circuit_path, witness_path, output_path);
+        _execute_prove_command(
+            circuit_path,
+            witness_path,
+            output_path,
+            strict_field,
+            zero_knowledge,
+            bitwise_sha256,
+            debug,
+        );
     } else if let Some(subcommand_matches) = matches.subcommand_matches(write_vk_command.get_name())
     {
         let circuit_path =
             _get_argument_value(subcommand_matches, _write_vk_argument_circuit_path());
         let output_path = _get_argument_value(subcommand_matches, _write_vk_argument_output_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
 
-        _execute_write_vk_command(circuit_path, output_path);
+        _execute_write_vk_command(
+            circuit_path,
+            output_path,
+            strict_field,
+            zero_knowledge,
+            bitwise_sha256,
+        );
     } else if let Some(subcommand_matches) = matches.subcommand_matches(verify_command.get_name()) {
         let vk_path = _get_argument_value(subcommand_matches, _verify_argument_vk_path());
         let proof_path = _get_argument_value(subcommand_matches, _verify_argument_proof());
+        let program_path =
+            _get_optional_argument_value(subcommand_matches, _verify_argument_program_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
+        _check_at_most_one_standard_input(
+            &[vk_path, proof_path]
+                .into_iter()
+                .chain(program_path)
+                .collect::<Vec<_>>(),
+        );
+
+        _execute_verify_command(
+            vk_path,
+            proof_path,
+            program_path,
+            strict_field,
+            bitwise_sha256,
+        );
+    } else if let Some(subcommand_matches) =
+        matches.subcommand_matches(prove_and_verify_command.get_name())
+    {
+        let circuit_path = _get_argument_value(
+            subcommand_matches,
+            _prove_and_verify_argument_circuit_path(),
+        );
+        let witness_path = _get_argument_value(
+            subcommand_matches,
+            _prove_and_verify_argument_witness_path(),
+        );
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
+        _check_at_most_one_standard_input(&[circuit_path, witness_path]);
+
+        _execute_prove_and_verify_command(
+            circuit_path,
+            witness_path,
+            strict_field,
+            zero_knowledge,
+            bitwise_sha256,
+        );
+    } else if let Some(subcommand_matches) = matches.subcommand_matches(gates_command.get_name()) {
+        let circuit_path = _get_argument_value(subcommand_matches, _gates_argument_circuit_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
+
+        _execute_gates_command(circuit_path, strict_field, zero_knowledge, bitwise_sha256);
+    } else if let Some(subcommand_matches) = matches.subcommand_matches(info_command.get_name()) {
+        let circuit_path = _get_argument_value(subcommand_matches, _info_argument_circuit_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
+
+        _execute_info_command(circuit_path, strict_field, zero_knowledge, bitwise_sha256);
+    } else if let Some(subcommand_matches) = matches.subcommand_matches(profile_command.get_name())
+    {
+        let circuit_path =
+            _get_argument_value(subcommand_matches, _profile_argument_circuit_path());
+        let output_path = _get_argument_value(subcommand_matches, _profile_argument_output_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
+
+        _execute_profile_command(
+            circuit_path,
+            output_path,
+            strict_field,
+            zero_knowledge,
+            bitwise_sha256,
+        );
+    } else if let Some(subcommand_matches) = matches.subcommand_matches(audit_command.get_name()) {
+        let circuit_path = _get_argument_value(subcommand_matches, _audit_argument_circuit_path());
+        let strict_field = _get_flag_value(subcommand_matches, _argument_strict_field());
+        let zero_knowledge = _get_boolean_option_value(subcommand_matches, _argument_zk());
+        let bitwise_sha256 = _get_flag_value(subcommand_matches, _argument_bitwise_sha256());
 
-        _execute_verify_command(vk_path, proof_path);
+        _execute_audit_command(circuit_path, strict_field, zero_knowledge, bitwise_sha256);
+    }
+
+    if verbose {
+        eprintln!("{} finished in {:?}", command_name, start.elapsed());
     }
 }
 
@@ -71,6 +240,75 @@ fn create_command_argument(
         .value_parser(value_parser!(PathBuf))
 }
 
+/// Argument that can be omitted, taking the given default value, like the paths of Barretenberg's
+/// commands.
+fn create_command_argument_with_default(
+    argument_id: &'static str,
+    short_identifier: char,
+    long_identifier: &'static str,
+    short_help: &'static str,
+    long_help: &'static str,
+    default_value: &'static str,
+) -> Arg {
+    Arg::new(argument_id)
+        .help(short_help)
+        .long_help(long_help)
+        .short(short_identifier)
+        .long(long_identifier)
+        .required(false)
+        .default_value(default_value)
+        .action(clap::ArgAction::Set)
+        .value_parser(value_parser!(PathBuf))
+}
+
+fn create_command_optional_argument(
+    argument_id: &'static str,
+    long_identifier: &'static str,
+    short_help: &'static str,
+    long_help: &'static str,
+) -> Arg {
+    Arg::new(argument_id)
+        .help(short_help)
+        .long_help(long_help)
+        .long(long_identifier)
+        .required(false)
+        .action(clap::ArgAction::Set)
+        .value_parser(value_parser!(PathBuf))
+}
+
+fn create_command_flag(
+    argument_id: &'static str,
+    long_identifier: &'static str,
+    short_help: &'static str,
+    long_help: &'static str,
+) -> Arg {
+    Arg::new(argument_id)
+        .help(short_help)
+        .long_help(long_help)
+        .long(long_identifier)
+        .action(clap::ArgAction::SetTrue)
+}
+
+/// Boolean option that can be used as a flag (--option means true) or with an explicit value
+/// (--option false).
+fn create_command_boolean_option(
+    argument_id: &'static str,
+    long_identifier: &'static str,
+    short_help: &'static str,
+    long_help: &'static str,
+    default_value: &'static str,
+) -> Arg {
+    Arg::new(argument_id)
+        .help(short_help)
+        .long_help(long_help)
+        .long(long_identifier)
+        .action(clap::ArgAction::Set)
+        .num_args(0..=1)
+        .default_value(default_value)
+        .default_missing_value("true")
+        .value_parser(value_parser!(bool))
+}
+
 fn create_command_from_arguments(command_name: &'static str, args: Vec<Arg>) -> Command {
     args.iter()
         .fold(Command::new(command_name), |acc_command, arg| {
@@ -84,6 +322,48 @@ fn _get_argument_value(subcommand_matches: &ArgMatches, argument: Arg) -> &PathB
         .expect("Value for command not found")
 }
 
+fn _get_optional_argument_value(
+    subcommand_matches: &ArgMatches,
+    argument: Arg,
+) -> Option<&PathBuf> {
+    subcommand_matches.get_one::<PathBuf>(argument.get_id().to_string().as_str())
+}
+
+fn _get_flag_value(subcommand_matches: &ArgMatches, argument: Arg) -> bool {
+    subcommand_matches.get_flag(argument.get_id().to_string().as_str())
+}
+
+/// The standard input can only be read once, so at most one of the input paths can be "-".
+fn _check_at_most_one_standard_input(input_paths: &[&PathBuf]) {
+    let standard_inputs = input_paths
+        .iter()
+        .filter(|path| path.as_os_str() == STANDARD_STREAM_PATH)
+        .count();
+    if standard_inputs > 1 {
+        panic!("Only one of the inputs can be read from the standard input");
+    }
+}
+
+fn _get_boolean_option_value(subcommand_matches: &ArgMatches, argument: Arg) -> bool {
+    *subcommand_matches
+        .get_one::<bool>(argument.get_id().to_string().as_str())
+        .expect("Value for command not found")
+}
+
+fn _create_execute_command() -> Command {
+    let execute_command_name = "execute";
+    let execute_command = create_command_from_arguments(
+        execute_command_name,
+        vec![
+            _execute_argument_circuit_path(),
+            _execute_argument_inputs_path(),
+            _execute_argument_output_path(),
+            _argument_strict_field(),
+        ],
+    );
+    execute_command
+}
+
 fn _create_prove_command() -> Command {
     let prove_command_name = "prove";
     let prove_command = create_command_from_arguments(
@@ -92,6 +372,10 @@ fn _create_prove_command() -> Command {
             _prove_argument_circuit_path(),
             _prove_argument_witness_path(),
             _prove_argument_output_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
+            _prove_argument_debug(),
         ],
     );
     prove_command
@@ -104,6 +388,9 @@ fn _create_write_vk_command() -> Command {
         vec![
             _write_vk_argument_circuit_path(),
             _write_vk_argument_output_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
         ],
     );
     prove_command
@@ -113,12 +400,90 @@ fn _create_verify_command() -> Command {
     let verify_command_name = "verify";
     let prove_command = create_command_from_arguments(
         verify_command_name,
-        vec![_verify_argument_vk_path(), _verify_argument_proof()],
+        vec![
+            _verify_argument_vk_path(),
+            _verify_argument_proof(),
+            _verify_argument_program_path(),
+            _argument_strict_field(),
+            _argument_bitwise_sha256(),
+        ],
     );
     prove_command
 }
 
-fn _prove_argument_circuit_path() -> Arg {
+fn _create_prove_and_verify_command() -> Command {
+    let prove_and_verify_command_name = "prove_and_verify";
+    let prove_and_verify_command = create_command_from_arguments(
+        prove_and_verify_command_name,
+        vec![
+            _prove_and_verify_argument_circuit_path(),
+            _prove_and_verify_argument_witness_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
+        ],
+    );
+    prove_and_verify_command
+}
+
+fn _create_gates_command() -> Command {
+    let gates_command_name = "gates";
+    let gates_command = create_command_from_arguments(
+        gates_command_name,
+        vec![
+            _gates_argument_circuit_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
+        ],
+    );
+    gates_command
+}
+
+fn _create_info_command() -> Command {
+    let info_command_name = "info";
+    let info_command = create_command_from_arguments(
+        info_command_name,
+        vec![
+            _info_argument_circuit_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
+        ],
+    );
+    info_command
+}
+
+fn _create_profile_command() -> Command {
+    let profile_command_name = "profile";
+    let profile_command = create_command_from_arguments(
+        profile_command_name,
+        vec![
+            _profile_argument_circuit_path(),
+            _profile_argument_output_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
+        ],
+    );
+    profile_command
+}
+
+fn _create_audit_command() -> Command {
+    let audit_command_name = "audit";
+    let audit_command = create_command_from_arguments(
+        audit_command_name,
+        vec![
+            _audit_argument_circuit_path(),
+            _argument_strict_field(),
+            _argument_zk(),
+            _argument_bitwise_sha256(),
+        ],
+    );
+    audit_command
+}
+
+fn _execute_argument_circuit_path() -> Arg {
     let argument_id = "circuit_path";
     let short_command_identifier = 'b';
     let long_command_identifier = "circuit-path";
@@ -133,18 +498,66 @@ fn _prove_argument_circuit_path() -> Arg {
     )
 }
 
+fn _execute_argument_inputs_path() -> Arg {
+    let argument_id = "inputs_path";
+    let short_command_identifier = 'i';
+    let long_command_identifier = "inputs-path";
+    let short_help = "Path to the Prover.toml file with the inputs of the program";
+    let long_help = "The inputs are read following the ABI of the program, as nargo does. If the \
+        file has a return value, the execution checks the program returns it.";
+    create_command_argument(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _execute_argument_output_path() -> Arg {
+    let argument_id = "output_path";
+    let short_command_identifier = 'o';
+    let long_command_identifier = "output-path";
+    let short_help = "Path where the solved witness stack is to be stored";
+    let long_help = "";
+    create_command_argument(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _prove_argument_circuit_path() -> Arg {
+    let argument_id = "circuit_path";
+    let short_command_identifier = 'b';
+    let long_command_identifier = "circuit-path";
+    let short_help = "Path to the generated ACIR circuit";
+    let long_help = "";
+    create_command_argument_with_default(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+        DEFAULT_CIRCUIT_PATH,
+    )
+}
+
 fn _prove_argument_witness_path() -> Arg {
     let argument_id = "witness_path";
     let short_command_identifier = 'w';
     let long_command_identifier = "witness-path";
     let short_help = "Path to the generated witness values";
     let long_help = "";
-    create_command_argument(
+    create_command_argument_with_default(
         argument_id,
         short_command_identifier,
         long_command_identifier,
         short_help,
         long_help,
+        DEFAULT_WITNESS_PATH,
     )
 }
 
@@ -154,12 +567,29 @@ fn _prove_argument_output_path() -> Arg {
     let long_command_identifier = "output-path";
     let short_help = "Path where the generated proof is to be stored";
     let long_help = "";
-    create_command_argument(
+    create_command_argument_with_default(
         argument_id,
         short_command_identifier,
         long_command_identifier,
         short_help,
         long_help,
+        DEFAULT_PROOF_PATH,
+    )
+}
+
+fn _prove_argument_debug() -> Arg {
+    let argument_id = "debug";
+    let long_command_identifier = "debug";
+    let short_help = "Check the witnesses against every ACIR opcode before proving";
+    let long_help = "Reports the first ACIR opcode the witnesses don't satisfy, with the values \
+        involved and its location in the Noir source code (if the program has debug symbols). If \
+        every opcode holds, it also checks that the Plonky2 circuit generates the same values for \
+        the witnesses.";
+    create_command_flag(
+        argument_id,
+        long_command_identifier,
+        short_help,
+        long_help,
     )
 }
 
@@ -169,12 +599,13 @@ fn _write_vk_argument_circuit_path() -> Arg {
     let long_command_identifier = "circuit-path";
     let short_help = "Path to the generated ACIR circuit";
     let long_help = "";
-    create_command_argument(
+    create_command_argument_with_default(
         argument_id,
         short_command_identifier,
         long_command_identifier,
         short_help,
         long_help,
+        DEFAULT_CIRCUIT_PATH,
     )
 }
 
@@ -184,12 +615,13 @@ fn _write_vk_argument_output_path() -> Arg {
     let long_command_identifier = "output-path";
     let short_help = "Path to the generated verification key";
     let long_help = "";
-    create_command_argument(
+    create_command_argument_with_default(
         argument_id,
         short_command_identifier,
         long_command_identifier,
         short_help,
         long_help,
+        DEFAULT_VK_PATH,
     )
 }
 
@@ -199,12 +631,13 @@ fn _verify_argument_vk_path() -> Arg {
     let long_command_identifier = "vk-path";
     let short_help = "Path to the verification key";
     let long_help = "";
-    create_command_argument(
+    create_command_argument_with_default(
         argument_id,
         short_command_identifier,
         long_command_identifier,
         short_help,
         long_help,
+        DEFAULT_VK_PATH,
     )
 }
 
@@ -214,6 +647,129 @@ fn _verify_argument_proof() -> Arg {
     let long_command_identifier = "proof-path";
     let short_help = "Path to the proof";
     let long_help = "";
+    create_command_argument_with_default(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+        DEFAULT_PROOF_PATH,
+    )
+}
+
+fn _verify_argument_program_path() -> Arg {
+    let argument_id = "program_path";
+    let long_command_identifier = "program";
+    let short_help = "Path to the ACIR program the verification key should correspond to";
+    let long_help = "Checks that the verification key was generated from this program (same \
+        bytecode and ABI, and same circuit once translated) before verifying the proof.";
+    create_command_optional_argument(
+        argument_id,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _prove_and_verify_argument_circuit_path() -> Arg {
+    let argument_id = "circuit_path";
+    let short_command_identifier = 'b';
+    let long_command_identifier = "circuit-path";
+    let short_help = "Path to the generated ACIR circuit";
+    let long_help = "";
+    create_command_argument_with_default(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+        DEFAULT_CIRCUIT_PATH,
+    )
+}
+
+fn _prove_and_verify_argument_witness_path() -> Arg {
+    let argument_id = "witness_path";
+    let short_command_identifier = 'w';
+    let long_command_identifier = "witness-path";
+    let short_help = "Path to the generated witness values";
+    let long_help = "";
+    create_command_argument_with_default(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+        DEFAULT_WITNESS_PATH,
+    )
+}
+
+fn _gates_argument_circuit_path() -> Arg {
+    let argument_id = "circuit_path";
+    let short_command_identifier = 'b';
+    let long_command_identifier = "circuit-path";
+    let short_help = "Path to the generated ACIR circuit";
+    let long_help = "";
+    create_command_argument_with_default(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+        DEFAULT_CIRCUIT_PATH,
+    )
+}
+
+fn _info_argument_circuit_path() -> Arg {
+    let argument_id = "circuit_path";
+    let short_command_identifier = 'b';
+    let long_command_identifier = "circuit-path";
+    let short_help = "Path to the generated ACIR circuit";
+    let long_help = "";
+    create_command_argument(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _profile_argument_circuit_path() -> Arg {
+    let argument_id = "circuit_path";
+    let short_command_identifier = 'b';
+    let long_command_identifier = "circuit-path";
+    let short_help = "Path to the generated ACIR circuit";
+    let long_help = "";
+    create_command_argument(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _profile_argument_output_path() -> Arg {
+    let argument_id = "output_path";
+    let short_command_identifier = 'o';
+    let long_command_identifier = "output-path";
+    let short_help = "Path where the folded stacks of the gates per opcode are to be stored";
+    let long_help = "The output follows the folded stacks format used to generate flamegraphs";
+    create_command_argument(
+        argument_id,
+        short_command_identifier,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _audit_argument_circuit_path() -> Arg {
+    let argument_id = "circuit_path";
+    let short_command_identifier = 'b';
+    let long_command_identifier = "circuit-path";
+    let short_help = "Path to the generated ACIR circuit";
+    let long_help = "";
     create_command_argument(
         argument_id,
         short_command_identifier,
@@ -223,27 +779,220 @@ fn _verify_argument_proof() -> Arg {
     )
 }
 
-fn _execute_prove_command(circuit_path: &PathBuf, witness_path: &PathBuf, output_path: &PathBuf) {
+fn _argument_strict_field() -> Arg {
+    let argument_id = "strict_field";
+    let long_command_identifier = "strict-field";
+    let short_help = "Reject values that are not Goldilocks field elements instead of reducing";
+    let long_help = "Every ACIR constant and witness value must be a canonical Goldilocks field \
+        element, or close enough to the Noir field modulus to be interpreted as a negative number. \
+        The same option must be used for prove and write_vk.";
+    create_command_flag(
+        argument_id,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _argument_zk() -> Arg {
+    let argument_id = "zk";
+    let long_command_identifier = "zk";
+    let short_help = "Generate zero knowledge proofs, hiding the private inputs (default: true)";
+    let long_help = "Builds the circuit with blinding enabled, so the proof reveals nothing about \
+        the private witness. Use --zk false for smaller and faster proofs that are not zero \
+        knowledge. The same value must be used for prove and write_vk.";
+    create_command_boolean_option(
+        argument_id,
+        long_command_identifier,
+        short_help,
+        long_help,
+        "true",
+    )
+}
+
+fn _argument_bitwise_sha256() -> Arg {
+    let argument_id = "bitwise_sha256";
+    let long_command_identifier = "bitwise-sha256";
+    let short_help = "Translate the SHA256 compressions bit by bit instead of with u32 lookups";
+    let long_help = "The bitwise translation needs no lookup tables but far more gates. The same \
+        option must be used for prove and write_vk.";
+    create_command_flag(
+        argument_id,
+        long_command_identifier,
+        short_help,
+        long_help,
+    )
+}
+
+fn _argument_crs_path() -> Arg {
+    let argument_id = "crs_path";
+    let short_command_identifier = 'c';
+    let long_command_identifier = "crs-path";
+    let short_help = "Path to the CRS, accepted for compatibility with Barretenberg and ignored";
+    let long_help = "Plonky2 proofs don't need a common reference string, so the path is never \
+        read. It's accepted since Nargo passes it to every command of a backend.";
+    create_command_optional_argument(argument_id, long_command_identifier, short_help, long_help)
+        .short(short_command_identifier)
+        .global(true)
+}
+
+fn _argument_verbose() -> Arg {
+    let argument_id = "verbose";
+    let short_command_identifier = 'v';
+    let long_command_identifier = "verbose";
+    let short_help = "Report how long the command took on stderr";
+    let long_help = "";
+    create_command_flag(argument_id, long_command_identifier, short_help, long_help)
+        .short(short_command_identifier)
+        .global(true)
+}
+
+fn _execute_execute_command(
+    circuit_path: &PathBuf,
+    inputs_path: &PathBuf,
+    output_path: &PathBuf,
+    strict_field: bool,
+) {
+    actions::execute_action::ExecuteAction {
+        acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
+        prover_toml_path: String::from(inputs_path.to_str().unwrap()),
+        resulting_witness_file_path: String::from(output_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+    }
+    .run();
+}
+
+fn _execute_prove_command(
+    circuit_path: &PathBuf,
+    witness_path: &PathBuf,
+    output_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+    debug: bool,
+) {
     actions::prove_action::ProveAction {
         acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
         witness_stack_zip_path: String::from(witness_path.to_str().unwrap()),
         resulting_proof_file_path: String::from(output_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
+        debug,
     }
     .run();
 }
 
-fn _execute_write_vk_command(circuit_path: &PathBuf, output_path: &PathBuf) {
+fn _execute_write_vk_command(
+    circuit_path: &PathBuf,
+    output_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+) {
     actions::write_vk_action::WriteVKAction {
         acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
         vk_path_output: String::from(output_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
     }
     .run()
 }
 
-fn _execute_verify_command(vk_path: &PathBuf, proof_path: &PathBuf) {
+fn _execute_verify_command(
+    vk_path: &PathBuf,
+    proof_path: &PathBuf,
+    program_path: Option<&PathBuf>,
+    strict_field: bool,
+    bitwise_sha256: bool,
+) {
     actions::verify_action::VerifyAction {
         proof_path: String::from(proof_path.to_str().unwrap()),
         vk_path: String::from(vk_path.to_str().unwrap()),
+        acir_program_json_path: program_path
+            .map(|program_path| String::from(program_path.to_str().unwrap())),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
+    }
+    .run()
+}
+
+fn _execute_prove_and_verify_command(
+    circuit_path: &PathBuf,
+    witness_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+) {
+    actions::prove_and_verify_action::ProveAndVerifyAction {
+        acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
+        witness_stack_zip_path: String::from(witness_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
+    }
+    .run()
+}
+
+fn _execute_gates_command(
+    circuit_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+) {
+    actions::info_action::InfoAction {
+        acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
+    }
+    .run_gates()
+}
+
+fn _execute_info_command(
+    circuit_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+) {
+    actions::info_action::InfoAction {
+        acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
+    }
+    .run()
+}
+
+fn _execute_profile_command(
+    circuit_path: &PathBuf,
+    output_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+) {
+    actions::profile_action::ProfileAction {
+        acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
+        resulting_folded_stacks_file_path: String::from(output_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
+    }
+    .run()
+}
+
+fn _execute_audit_command(
+    circuit_path: &PathBuf,
+    strict_field: bool,
+    zero_knowledge: bool,
+    bitwise_sha256: bool,
+) {
+    actions::audit_action::AuditAction {
+        acir_program_json_path: String::from(circuit_path.to_str().unwrap()),
+        field_conversion_mode: FieldConversionMode::from_strict_flag(strict_field),
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(bitwise_sha256),
     }
     .run()
 }
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_data::CircuitData;
+
+use super::*;
+use crate::field_conversion::FieldConversionMode;
+use crate::file_format::{FileHeader, FileKind};
+
+/// The backend as a Rust library. It does what the prove, write_vk and verify commands do, but
+/// every artifact is a value in memory instead of a file: the proof and the verifying key can be
+/// turned into bytes (in the same format the commands write) and back, so a service embedding
+/// the backend never needs temporary files. The commands are built on top of it.
+///
+/// The options change the resulting circuit, so the same ones must be used to compile the
+/// circuit that is proven and the one the verifying key is taken from.
+///
+/// Invalid inputs are reported as errors, except for the failures of the translation and of
+/// Plonky2 itself, which panic: compile panics on programs it can't translate (an unsupported
+/// opcode, a constant memory access out of bounds or, in strict mode, a constant that isn't a
+/// Goldilocks element), and prove panics when the witness generation of Plonky2 finds values
+/// that contradict each other or a Brillig call that fails.
+#[derive(Clone, Copy, Debug)]
+pub struct Plonky2Backend {
+    pub field_conversion_mode: FieldConversionMode,
+    pub zero_knowledge: bool,
+    pub sha256_implementation: Sha256Implementation,
+}
+
+impl Default for Plonky2Backend {
+    /// The same defaults as the commands: lenient field conversion, zero knowledge proofs and the
+    /// SHA256 compression built on u32 lookups.
+    fn default() -> Self {
+        Self {
+            field_conversion_mode: FieldConversionMode::default(),
+            zero_knowledge: true,
+            sha256_implementation: Sha256Implementation::default(),
+        }
+    }
+}
+
+/// The Plonky2 circuit of an ACIR program, along with the targets of its witnesses, which are
+/// needed to prove it. The program hash ends up in the proofs and the verifying key.
+pub struct CompiledCircuit {
+    pub program_hash: Vec<u8>,
+    pub circuit_data: CircuitData<F, C, D>,
+    pub witness_target_map: HashMap<Witness, Target>,
+}
+
+/// A compressed proof along with the header describing the circuit it was generated for (see
+/// the file_format module).
+pub struct Proof {
+    pub header: FileHeader,
+    pub compressed_proof: CompressedProofWithPublicInputs<F, C, D>,
+}
+
+/// The public inputs of a proof, in the order of public_input_witnesses. They're Goldilocks field
+/// elements read back as FieldElements by the field conversion mode of the backend: in lenient
+/// mode a negative value is the Goldilocks prime minus its absolute value, while in strict mode
+/// it's negative in the FieldElement field too.
+pub type PublicInputs = Vec<FieldElement>;
+
+impl Proof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.header.prepend_to(self.compressed_proof.to_bytes())
+    }
+
+    /// The proof is checked to belong to the circuit and program of the verifying key.
+    pub fn from_bytes(bytes: &[u8], verifying_key: &VerifyingKey) -> Result<Self, String> {
+        let compressed_proof = deserialize_proof(bytes, verifying_key)?;
+        let (header, _) = FileHeader::split_from(bytes)?;
+        Ok(Self {
+            header,
+            compressed_proof,
+        })
+    }
+}
+
+impl Plonky2Backend {
+    /// A translator with the options of the backend, for translating circuits by hand.
+    pub fn translator(&self) -> CircuitBuilderFromAcirToPlonky2 {
+        let mut translator = CircuitBuilderFromAcirToPlonky2::new_with_options(
+            self.field_conversion_mode,
+            self.zero_knowledge,
+        );
+        translator.sha256_implementation = self.sha256_implementation;
+        translator
+    }
+
+    /// Since the ABI of the program is unknown, the program hash only covers its bytecode, so it
+    /// differs from the one of compile_program_json: the proofs and verifying keys of both don't
+    /// match each other, even for the same program.
+    pub fn compile(&self, program: &Program) -> CompiledCircuit {
+        let bytecode = Program::serialize_program(program);
+        self._compile(
+            program,
+            program_hash_of(&bytecode, &serde_json::Value::Null),
+        )
+    }
+
+    /// Compiles the program json written by Nargo, like the commands do. Its program hash covers
+    /// the bytecode and the ABI, so its verifying keys are the ones of the write_vk command, but
+    /// not the ones of compile.
+    pub fn compile_program_json(&self, program_json: &serde_json::Value) -> CompiledCircuit {
+        let program = deserialize_program(program_json);
+        self._compile(&program, program_hash(program_json))
+    }
+
+    fn _compile(&self, program: &Program, program_hash: Vec<u8>) -> CompiledCircuit {
+        let mut translator = self.translator();
+        translator.translate_program(program);
+        let (circuit_data, witness_target_map) = translator.unpack();
+        CompiledCircuit {
+            program_hash,
+            circuit_data,
+            witness_target_map,
+        }
+    }
+
+    /// Only the witnesses of the main function are used, and the input witnesses of the program
+    /// are enough since the circuit generates the rest.
+    pub fn prove(
+        &self,
+        compiled_circuit: &CompiledCircuit,
+        witness_stack: WitnessStack,
+    ) -> Result<Proof, String> {
+        let circuit_data = &compiled_circuit.circuit_data;
+        let partial_witness =
+            self.partial_witness(witness_stack, &compiled_circuit.witness_target_map)?;
+        let proof = circuit_data
+            .prove(partial_witness)
+            .map_err(|error| format!("The proof could not be generated: {}", error))?;
+        let compressed_proof = proof
+            .compress(
+                &circuit_data.verifier_only.circuit_digest,
+                &circuit_data.common,
+            )
+            .map_err(|error| format!("The proof could not be compressed: {}", error))?;
+        let header = FileHeader::new_for(
+            FileKind::Proof,
+            &compiled_circuit.program_hash,
+            &circuit_data.verifier_only,
+            &circuit_data.common,
+        );
+        Ok(Proof {
+            header,
+            compressed_proof,
+        })
+    }
+
+    /// All the witnesses used in the ACIR code have an "equivalent" Target in the Plonky2
+    /// circuit. Witnesses that don't appear in the circuit (like the ones only used by Brillig
+    /// calls) are skipped. In strict mode, a value that isn't a Goldilocks element is an error.
+    pub fn partial_witness(
+        &self,
+        mut witness_stack: WitnessStack,
+        witness_target_map: &HashMap<Witness, Target>,
+    ) -> Result<PartialWitness<F>, String> {
+        let witness_map = witness_stack
+            .pop()
+            .ok_or_else(|| "The witness stack is empty".to_string())?
+            .witness;
+        let mut partial_witness = PartialWitness::<F>::new();
+        for (witness, value) in witness_map.into_iter() {
+            let Some(plonky2_target) = witness_target_map.get(&witness) else {
+                continue;
+            };
+            let goldilocks_value = self
+                .field_conversion_mode
+                .try_field_element_to_goldilocks_field(&value, || {
+                    format!("Witness {}", witness.0)
+                })?;
+            partial_witness.set_target(*plonky2_target, goldilocks_value);
+        }
+        Ok(partial_witness)
+    }
+
+    pub fn verification_key(&self, compiled_circuit: &CompiledCircuit) -> VerifyingKey {
+        VerifyingKey {
+            program_hash: compiled_circuit.program_hash.clone(),
+            verifier_data: compiled_circuit.circuit_data.verifier_data(),
+        }
+    }
+
+    /// The header of the proof must describe the circuit and program of the verifying key.
+    pub fn verify(
+        &self,
+        verifying_key: &VerifyingKey,
+        proof: &Proof,
+    ) -> Result<PublicInputs, String> {
+        let verifier_data = &verifying_key.verifier_data;
+        let expected_header = FileHeader::new_for(
+            FileKind::Proof,
+            &verifying_key.program_hash,
+            &verifier_data.verifier_only,
+            &verifier_data.common,
+        );
+        proof.header.check_matches(&expected_header)?;
+        verifier_data
+            .verify_compressed(proof.compressed_proof.clone())
+            .map_err(|error| format!("Verification failed: {}", error))?;
+        Ok(proof
+            .compressed_proof
+            .public_inputs
+            .iter()
+            .map(|value| {
+                self.field_conversion_mode
+                    .goldilocks_field_to_field_element(*value)
+            })
+            .collect())
+    }
+}
@@ -1,65 +1,143 @@
-use plonky2::field::types::Field;
-use plonky2::iop::target::BoolTarget;
+use std::cell::OnceCell;
+use std::rc::Rc;
+
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 
 use crate::circuit_translation::CB;
-use crate::F;
 
 /// This module provides a BinaryDigitsTarget object. It's main goal is to represent numbers as
 /// its bit decomposition, so we can perform bitwise operations.
-/// TODO: Some operations might be optimized (https://github.com/JumpCrypto/plonky2-crypto/tree/main/src/u32/gates)
+///
+/// A BinaryDigitsTarget is a view over a source number: each of its digits (most significant
+/// first) is either a bit of the source or a zero. Rotations and shifts only rearrange the view,
+/// so they don't add any gate. The source can be kept packed in a single target (a u32 limb, for
+/// instance) whose bits are only decomposed the first time an operation needs them; every view
+/// of the same source shares that decomposition.
 #[derive(Clone, Debug)]
 pub struct BinaryDigitsTarget {
-    pub bits: Vec<BoolTarget>,
+    source: Rc<PackedSource>,
+    view: Vec<DigitSource>,
+}
+
+#[derive(Debug)]
+struct PackedSource {
+    limb: Option<Target>,
+    number_of_digits: usize,
+    bits: OnceCell<Vec<BoolTarget>>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum DigitSource {
+    Bit(usize),
+    Zero,
 }
 
 impl BinaryDigitsTarget {
-    pub(crate) fn number_of_digits(&self) -> usize {
-        self.bits.len()
+    /// The bits must be constrained to be boolean already.
+    pub fn from_bits(bits: Vec<BoolTarget>) -> Self {
+        Self::_new(None, bits.len(), OnceCell::from(bits))
     }
 
-    pub fn rotate_right(
-        binary_target: &BinaryDigitsTarget,
-        times: usize,
-        builder: &mut CB,
-    ) -> BinaryDigitsTarget {
-        let mut new_bits = Vec::new();
+    /// A number packed in a single target, whose bits are decomposed on demand. The decomposition
+    /// also checks that the number fits in the given digits, so a number whose bits are never
+    /// needed must be range checked elsewhere.
+    pub fn from_target(limb: Target, number_of_digits: usize) -> Self {
+        Self::_new(Some(limb), number_of_digits, OnceCell::new())
+    }
 
-        for i in (binary_target.number_of_digits() - times)..binary_target.number_of_digits() {
-            let new_bool_target = builder.add_virtual_bool_target_safe();
-            builder.connect(binary_target.bits[i].target, new_bool_target.target);
-            new_bits.push(new_bool_target);
-        }
+    /// A number together with its bits, both already constrained to match.
+    pub fn from_target_and_bits(limb: Target, bits: Vec<BoolTarget>) -> Self {
+        Self::_new(Some(limb), bits.len(), OnceCell::from(bits))
+    }
 
-        for i in 0..(binary_target.number_of_digits() - times) {
-            let new_bool_target = builder.add_virtual_bool_target_safe();
-            builder.connect(binary_target.bits[i].target, new_bool_target.target);
-            new_bits.push(new_bool_target);
+    fn _new(
+        limb: Option<Target>,
+        number_of_digits: usize,
+        bits: OnceCell<Vec<BoolTarget>>,
+    ) -> Self {
+        Self {
+            source: Rc::new(PackedSource {
+                limb,
+                number_of_digits,
+                bits,
+            }),
+            view: (0..number_of_digits).map(DigitSource::Bit).collect(),
         }
+    }
 
-        BinaryDigitsTarget { bits: new_bits }
+    pub(crate) fn number_of_digits(&self) -> usize {
+        self.view.len()
     }
 
-    pub fn shift_right(
-        target: &BinaryDigitsTarget,
-        times: usize,
-        builder: &mut CB,
-    ) -> BinaryDigitsTarget {
-        let mut new_bits = Vec::new();
+    /// The digits, most significant first. The bits of the source are decomposed the first time.
+    pub fn bits<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Vec<BoolTarget> {
+        let source_bits = self.source.bits.get_or_init(|| {
+            let limb = self
+                .source
+                .limb
+                .expect("A source without bits must have a packed target");
+            builder
+                .split_le(limb, self.source.number_of_digits)
+                .into_iter()
+                .rev()
+                .collect()
+        });
+        let source_bits = source_bits.clone();
+        self.view
+            .iter()
+            .map(|digit| match digit {
+                DigitSource::Bit(index) => source_bits[*index],
+                DigitSource::Zero => builder._false(),
+            })
+            .collect()
+    }
 
-        for _ in 0..times {
-            new_bits.push(BoolTarget::new_unsafe(
-                builder.constant(F::from_canonical_u8(0)),
-            ));
+    /// The number the digits represent, which is free when the view is the packed source itself.
+    pub fn to_target<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Target {
+        if let Some(limb) = self.source.limb {
+            if self._is_the_whole_source() {
+                return limb;
+            }
         }
-        for i in 0..target.number_of_digits() - times {
-            let new_bool_target = builder.add_virtual_bool_target_safe();
-            builder.connect(target.bits[i].target, new_bool_target.target);
-            new_bits.push(new_bool_target);
-        }
-        // Fill zero bits
+        let bits = self.bits(builder);
+        builder.le_sum(bits.into_iter().rev())
+    }
 
+    fn _is_the_whole_source(&self) -> bool {
+        self.view.len() == self.source.number_of_digits
+            && self
+                .view
+                .iter()
+                .enumerate()
+                .all(|(position, digit)| *digit == DigitSource::Bit(position))
+    }
+
+    pub fn rotate_right(&self, times: usize) -> BinaryDigitsTarget {
+        let split_position = self.number_of_digits() - times;
+        let view = [&self.view[split_position..], &self.view[..split_position]].concat();
+        self._with_view(view)
+    }
 
-        BinaryDigitsTarget { bits: new_bits }
+    pub fn shift_right(&self, times: usize) -> BinaryDigitsTarget {
+        let kept_digits = &self.view[..self.number_of_digits() - times];
+        let view = [vec![DigitSource::Zero; times].as_slice(), kept_digits].concat();
+        self._with_view(view)
+    }
+
+    fn _with_view(&self, view: Vec<DigitSource>) -> BinaryDigitsTarget {
+        BinaryDigitsTarget {
+            source: self.source.clone(),
+            view,
+        }
     }
 
     pub fn choose(
@@ -68,16 +146,18 @@ impl BinaryDigitsTarget {
         on_false: &BinaryDigitsTarget,
         builder: &mut CB,
     ) -> BinaryDigitsTarget {
-        let bit_pairs_iter = on_true.bits.iter().zip(on_false.bits.iter());
+        let on_true_bits = on_true.bits(builder);
+        let on_false_bits = on_false.bits(builder);
+        let bit_pairs_iter = on_true_bits.iter().zip(on_false_bits.iter());
 
         let chosen_bits = chooser
-            .bits
+            .bits(builder)
             .iter()
             .zip(bit_pairs_iter)
             .map(|(c, (t, f))| BinaryDigitsTarget::select_bool_target(c, t, f, builder))
             .collect();
 
-        BinaryDigitsTarget { bits: chosen_bits }
+        BinaryDigitsTarget::from_bits(chosen_bits)
     }
 
     pub fn majority(
@@ -86,10 +166,12 @@ impl BinaryDigitsTarget {
         c: &BinaryDigitsTarget,
         builder: &mut CB,
     ) -> BinaryDigitsTarget {
-        let bit_pairs_iter = a.bits.iter().zip(b.bits.iter());
+        let a_bits = a.bits(builder);
+        let b_bits = b.bits(builder);
+        let bit_pairs_iter = a_bits.iter().zip(b_bits.iter());
 
         let majority_bits = c
-            .bits
+            .bits(builder)
             .iter()
             .zip(bit_pairs_iter)
             .map(|(b0, (b1, b2))| {
@@ -98,9 +180,7 @@ impl BinaryDigitsTarget {
                 BinaryDigitsTarget::select_bool_target(b0, &on_true, &on_false, builder)
             })
             .collect();
-        BinaryDigitsTarget {
-            bits: majority_bits,
-        }
+        BinaryDigitsTarget::from_bits(majority_bits)
     }
 
     pub fn select_bool_target(
@@ -145,9 +225,9 @@ impl BinaryDigitsTarget {
         builder: &mut CB,
         op: fn(BoolTarget, BoolTarget, &mut CB) -> BoolTarget,
     ) -> BinaryDigitsTarget {
-        BinaryDigitsTarget {
-            bits: BinaryDigitsTarget::apply_bitwise_and_output_bool_targets(&b1, &b2, builder, op),
-        }
+        BinaryDigitsTarget::from_bits(BinaryDigitsTarget::apply_bitwise_and_output_bool_targets(
+            &b1, &b2, builder, op,
+        ))
     }
 
     pub fn apply_bitwise_and_output_bool_targets(
@@ -156,10 +236,12 @@ impl BinaryDigitsTarget {
         builder: &mut CB,
         op: fn(BoolTarget, BoolTarget, &mut CB) -> BoolTarget,
     ) -> Vec<BoolTarget> {
-        b1.bits
-            .iter()
-            .zip(b2.bits.iter())
-            .map(|(bit1, bit2)| op(*bit1, *bit2, builder))
+        let b1_bits = b1.bits(builder);
+        let b2_bits = b2.bits(builder);
+        b1_bits
+            .into_iter()
+            .zip(b2_bits)
+            .map(|(bit1, bit2)| op(bit1, bit2, builder))
             .collect()
     }
 
@@ -210,6 +292,6 @@ impl BinaryDigitsTarget {
             sum.push(sum_with_carry_in);
         }
         sum.reverse();
-        BinaryDigitsTarget { bits: sum }
+        BinaryDigitsTarget::from_bits(sum)
     }
 }
@@ -0,0 +1,422 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::packed::PackedField;
+use plonky2::gates::gate::Gate;
+use plonky2::gates::packed_util::PackedEvaluableBase;
+use plonky2::gates::util::StridedConstraintConsumer;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator, WitnessGeneratorRef};
+use plonky2::iop::target::Target;
+use plonky2::iop::wire::Wire;
+use plonky2::iop::witness::{PartitionWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CommonCircuitData};
+use plonky2::plonk::vars::{
+    EvaluationTargets, EvaluationVars, EvaluationVarsBase, EvaluationVarsBaseBatch,
+    EvaluationVarsBasePacked,
+};
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+
+/// A gate that evaluates (part of) an ACIR expression in each of its operations:
+///     output = input + q_c + Σ q_i·w_i + Σ q_jk·w_j·w_k
+/// for a fixed number of linear and quadratic terms. The input and output wires let an expression
+/// with more terms than a single operation be split across several of them, chaining the output
+/// of one into the input of the next: a whole AssertZero opcode is checked by starting with a zero
+/// input and asserting the last output is zero.
+///
+/// The coefficients are routed wires of the operation (connected to constant targets) instead of
+/// constants of the row, so the operations of different expressions can share a row: the gate has
+/// as many operations as fit in the routed wires of the circuit (see new_from_config), and the
+/// translator places them with find_slot. Like the U32 gates of the ECDSA gadgets, the unused
+/// operations of the last row keep zero wires, which satisfy their constraint.
+///
+/// Unused terms have a zero coefficient, so the value of their wires doesn't matter.
+#[derive(Copy, Clone, Debug)]
+pub struct AcirExpressionGate {
+    pub num_linear_terms: usize,
+    pub num_mul_terms: usize,
+    pub num_ops: usize,
+}
+
+/// By default an operation has room for the terms of a width-4 ACIR expression with one product.
+pub const DEFAULT_NUM_LINEAR_TERMS: usize = 4;
+pub const DEFAULT_NUM_MUL_TERMS: usize = 1;
+
+impl AcirExpressionGate {
+    /// Both kinds of terms are needed so any expression can be split across operations.
+    pub fn new(num_linear_terms: usize, num_mul_terms: usize, num_ops: usize) -> Self {
+        assert!(
+            num_linear_terms > 0 && num_mul_terms > 0,
+            "The AcirExpressionGate needs room for at least one term of each kind"
+        );
+        assert!(num_ops > 0, "The AcirExpressionGate needs at least one operation");
+        Self {
+            num_linear_terms,
+            num_mul_terms,
+            num_ops,
+        }
+    }
+
+    /// The gate with the default terms and as many operations as fit in the routed wires.
+    pub fn new_from_config(config: &CircuitConfig) -> Self {
+        Self::new_with_terms_from_config(config, DEFAULT_NUM_LINEAR_TERMS, DEFAULT_NUM_MUL_TERMS)
+    }
+
+    pub fn new_with_terms_from_config(
+        config: &CircuitConfig,
+        num_linear_terms: usize,
+        num_mul_terms: usize,
+    ) -> Self {
+        let num_ops = config.num_routed_wires / Self::wires_per_op(num_linear_terms, num_mul_terms);
+        Self::new(num_linear_terms, num_mul_terms, num_ops)
+    }
+
+    /// Input, output, the constant term, a coefficient and a wire per linear term, and a
+    /// coefficient and two factors per product.
+    fn wires_per_op(num_linear_terms: usize, num_mul_terms: usize) -> usize {
+        3 + 2 * num_linear_terms + 3 * num_mul_terms
+    }
+
+    fn _op_start(&self, i: usize) -> usize {
+        debug_assert!(i < self.num_ops);
+        i * Self::wires_per_op(self.num_linear_terms, self.num_mul_terms)
+    }
+
+    pub fn wire_ith_input(&self, i: usize) -> usize {
+        self._op_start(i)
+    }
+
+    pub fn wire_ith_output(&self, i: usize) -> usize {
+        self._op_start(i) + 1
+    }
+
+    pub fn wire_ith_constant_term(&self, i: usize) -> usize {
+        self._op_start(i) + 2
+    }
+
+    pub fn wire_ith_linear_coefficient(&self, i: usize, j: usize) -> usize {
+        debug_assert!(j < self.num_linear_terms);
+        self._op_start(i) + 3 + j
+    }
+
+    pub fn wire_ith_mul_coefficient(&self, i: usize, j: usize) -> usize {
+        debug_assert!(j < self.num_mul_terms);
+        self._op_start(i) + 3 + self.num_linear_terms + j
+    }
+
+    pub fn wire_ith_linear_term(&self, i: usize, j: usize) -> usize {
+        debug_assert!(j < self.num_linear_terms);
+        self._op_start(i) + 3 + self.num_linear_terms + self.num_mul_terms + j
+    }
+
+    pub fn wire_ith_mul_term_first_factor(&self, i: usize, j: usize) -> usize {
+        debug_assert!(j < self.num_mul_terms);
+        self._op_start(i) + 3 + 2 * self.num_linear_terms + self.num_mul_terms + 2 * j
+    }
+
+    pub fn wire_ith_mul_term_second_factor(&self, i: usize, j: usize) -> usize {
+        self.wire_ith_mul_term_first_factor(i, j) + 1
+    }
+
+    pub fn wires_count(&self) -> usize {
+        self.num_ops * Self::wires_per_op(self.num_linear_terms, self.num_mul_terms)
+    }
+
+    /// Every wire of the operation but the output is an input of the expression.
+    fn input_wires(&self, i: usize) -> Vec<usize> {
+        let start = self._op_start(i);
+        let end = start + Self::wires_per_op(self.num_linear_terms, self.num_mul_terms);
+        (start..end)
+            .filter(|wire| *wire != self.wire_ith_output(i))
+            .collect()
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Gate<F, D> for AcirExpressionGate {
+    fn id(&self) -> String {
+        format!("{self:?}")
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.num_linear_terms)?;
+        dst.write_usize(self.num_mul_terms)?;
+        dst.write_usize(self.num_ops)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let num_linear_terms = src.read_usize()?;
+        let num_mul_terms = src.read_usize()?;
+        let num_ops = src.read_usize()?;
+        Ok(Self::new(num_linear_terms, num_mul_terms, num_ops))
+    }
+
+    fn eval_unfiltered(&self, vars: EvaluationVars<F, D>) -> Vec<F::Extension> {
+        let mut constraints = Vec::with_capacity(self.num_ops);
+        for i in 0..self.num_ops {
+            let mut computed_output = vars.local_wires[self.wire_ith_input(i)]
+                + vars.local_wires[self.wire_ith_constant_term(i)];
+            for j in 0..self.num_linear_terms {
+                let coefficient = vars.local_wires[self.wire_ith_linear_coefficient(i, j)];
+                computed_output += coefficient * vars.local_wires[self.wire_ith_linear_term(i, j)];
+            }
+            for j in 0..self.num_mul_terms {
+                let coefficient = vars.local_wires[self.wire_ith_mul_coefficient(i, j)];
+                let first_factor = vars.local_wires[self.wire_ith_mul_term_first_factor(i, j)];
+                let second_factor = vars.local_wires[self.wire_ith_mul_term_second_factor(i, j)];
+                computed_output += coefficient * first_factor * second_factor;
+            }
+            constraints.push(vars.local_wires[self.wire_ith_output(i)] - computed_output);
+        }
+        constraints
+    }
+
+    fn eval_unfiltered_base_one(
+        &self,
+        _vars: EvaluationVarsBase<F>,
+        _yield_constr: StridedConstraintConsumer<F>,
+    ) {
+        panic!("use eval_unfiltered_base_packed instead");
+    }
+
+    fn eval_unfiltered_base_batch(&self, vars_base: EvaluationVarsBaseBatch<F>) -> Vec<F> {
+        self.eval_unfiltered_base_batch_packed(vars_base)
+    }
+
+    fn eval_unfiltered_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: EvaluationTargets<D>,
+    ) -> Vec<ExtensionTarget<D>> {
+        let mut constraints = Vec::with_capacity(self.num_ops);
+        for i in 0..self.num_ops {
+            let mut computed_output = builder.add_extension(
+                vars.local_wires[self.wire_ith_input(i)],
+                vars.local_wires[self.wire_ith_constant_term(i)],
+            );
+            for j in 0..self.num_linear_terms {
+                computed_output = builder.mul_add_extension(
+                    vars.local_wires[self.wire_ith_linear_coefficient(i, j)],
+                    vars.local_wires[self.wire_ith_linear_term(i, j)],
+                    computed_output,
+                );
+            }
+            for j in 0..self.num_mul_terms {
+                let product = builder.mul_extension(
+                    vars.local_wires[self.wire_ith_mul_term_first_factor(i, j)],
+                    vars.local_wires[self.wire_ith_mul_term_second_factor(i, j)],
+                );
+                computed_output = builder.mul_add_extension(
+                    vars.local_wires[self.wire_ith_mul_coefficient(i, j)],
+                    product,
+                    computed_output,
+                );
+            }
+            constraints.push(
+                builder.sub_extension(vars.local_wires[self.wire_ith_output(i)], computed_output),
+            );
+        }
+        constraints
+    }
+
+    fn generators(&self, row: usize, _local_constants: &[F]) -> Vec<WitnessGeneratorRef<F, D>> {
+        (0..self.num_ops)
+            .map(|i| {
+                WitnessGeneratorRef::new(AcirExpressionGenerator { gate: *self, row, i }.adapter())
+            })
+            .collect()
+    }
+
+    fn num_wires(&self) -> usize {
+        self.wires_count()
+    }
+
+    fn num_constants(&self) -> usize {
+        0
+    }
+
+    fn degree(&self) -> usize {
+        3
+    }
+
+    fn num_constraints(&self) -> usize {
+        self.num_ops
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> PackedEvaluableBase<F, D>
+    for AcirExpressionGate
+{
+    fn eval_unfiltered_base_packed<P: PackedField<Scalar = F>>(
+        &self,
+        vars: EvaluationVarsBasePacked<P>,
+        mut yield_constr: StridedConstraintConsumer<P>,
+    ) {
+        for i in 0..self.num_ops {
+            let mut computed_output = vars.local_wires[self.wire_ith_input(i)]
+                + vars.local_wires[self.wire_ith_constant_term(i)];
+            for j in 0..self.num_linear_terms {
+                let coefficient = vars.local_wires[self.wire_ith_linear_coefficient(i, j)];
+                computed_output += coefficient * vars.local_wires[self.wire_ith_linear_term(i, j)];
+            }
+            for j in 0..self.num_mul_terms {
+                let coefficient = vars.local_wires[self.wire_ith_mul_coefficient(i, j)];
+                let first_factor = vars.local_wires[self.wire_ith_mul_term_first_factor(i, j)];
+                let second_factor = vars.local_wires[self.wire_ith_mul_term_second_factor(i, j)];
+                computed_output += coefficient * first_factor * second_factor;
+            }
+            yield_constr.one(vars.local_wires[self.wire_ith_output(i)] - computed_output);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct AcirExpressionGenerator {
+    gate: AcirExpressionGate,
+    row: usize,
+    i: usize,
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> SimpleGenerator<F, D>
+    for AcirExpressionGenerator
+{
+    fn id(&self) -> String {
+        "AcirExpressionGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.gate
+            .input_wires(self.i)
+            .into_iter()
+            .map(|column| Target::wire(self.row, column))
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let get_local_wire = |column| {
+            witness.get_wire(Wire {
+                row: self.row,
+                column,
+            })
+        };
+        let gate = &self.gate;
+        let i = self.i;
+
+        let mut output =
+            get_local_wire(gate.wire_ith_input(i)) + get_local_wire(gate.wire_ith_constant_term(i));
+        for j in 0..gate.num_linear_terms {
+            output += get_local_wire(gate.wire_ith_linear_coefficient(i, j))
+                * get_local_wire(gate.wire_ith_linear_term(i, j));
+        }
+        for j in 0..gate.num_mul_terms {
+            output += get_local_wire(gate.wire_ith_mul_coefficient(i, j))
+                * get_local_wire(gate.wire_ith_mul_term_first_factor(i, j))
+                * get_local_wire(gate.wire_ith_mul_term_second_factor(i, j));
+        }
+
+        let output_wire = Wire {
+            row: self.row,
+            column: gate.wire_ith_output(i),
+        };
+        out_buffer.set_wire(output_wire, output);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        self.gate.serialize(dst, common_data)?;
+        dst.write_usize(self.row)?;
+        dst.write_usize(self.i)
+    }
+
+    fn deserialize(src: &mut Buffer, common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let gate = AcirExpressionGate::deserialize(src, common_data)?;
+        let row = src.read_usize()?;
+        let i = src.read_usize()?;
+        Ok(Self { gate, row, i })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plonky2_ecdsa::biguint::gates::gate_testing::{test_eval_fns, test_low_degree};
+    use anyhow::Result;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use super::*;
+
+    #[test]
+    fn low_degree() {
+        test_low_degree::<GoldilocksField, _, 4>(AcirExpressionGate::new(4, 2, 3))
+    }
+
+    #[test]
+    fn eval_fns() -> Result<()> {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_eval_fns::<F, C, _, D>(AcirExpressionGate::new(4, 2, 3))
+    }
+
+    #[test]
+    fn test_gate_fills_the_routed_wires_with_operations() {
+        let config = CircuitConfig::wide_ecc_config();
+        let gate = AcirExpressionGate::new_from_config(&config);
+
+        assert!(gate.num_ops > 1);
+        assert!(gate.wires_count() <= config.num_routed_wires);
+        assert!(
+            AcirExpressionGate::new(4, 1, gate.num_ops + 1).wires_count()
+                > config.num_routed_wires
+        );
+    }
+
+    #[test]
+    fn test_gate_constraint() {
+        type F = GoldilocksField;
+        const D: usize = 2;
+        let gate = AcirExpressionGate::new(2, 1, 2);
+
+        // The first operation has input = 5, q_c = 1, 2·3 + 4·7 and 10·2·6, so its output is
+        // 5 + 1 + 6 + 28 + 120. The second one is unused, so its wires are all zero.
+        let wires = |output: u64| -> Vec<<F as Extendable<D>>::Extension> {
+            let mut wires = vec![F::ZERO; gate.wires_count()];
+            let mut set = |wire: usize, value: u64| wires[wire] = F::from_canonical_u64(value);
+            set(gate.wire_ith_input(0), 5);
+            set(gate.wire_ith_output(0), output);
+            set(gate.wire_ith_constant_term(0), 1);
+            set(gate.wire_ith_linear_coefficient(0, 0), 2);
+            set(gate.wire_ith_linear_term(0, 0), 3);
+            set(gate.wire_ith_linear_coefficient(0, 1), 4);
+            set(gate.wire_ith_linear_term(0, 1), 7);
+            set(gate.wire_ith_mul_coefficient(0, 0), 10);
+            set(gate.wire_ith_mul_term_first_factor(0, 0), 2);
+            set(gate.wire_ith_mul_term_second_factor(0, 0), 6);
+            wires.into_iter().map(|w| w.into()).collect()
+        };
+        let public_inputs_hash = &HashOut::rand();
+
+        let satisfied_wires = wires(160);
+        let vars = EvaluationVars {
+            local_constants: &[],
+            local_wires: &satisfied_wires,
+            public_inputs_hash,
+        };
+        assert!(
+            Gate::<F, D>::eval_unfiltered(&gate, vars)
+                .iter()
+                .all(|x| x.is_zero()),
+            "Gate constraints are not satisfied."
+        );
+
+        let unsatisfied_wires = wires(161);
+        let vars = EvaluationVars {
+            local_constants: &[],
+            local_wires: &unsatisfied_wires,
+            public_inputs_hash,
+        };
+        assert!(!Gate::<F, D>::eval_unfiltered(&gate, vars)
+            .iter()
+            .all(|x| x.is_zero()));
+    }
+}
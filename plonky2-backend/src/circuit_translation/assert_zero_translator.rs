@@ -1,12 +1,18 @@
 use super::*;
+use crate::field_conversion::FieldConversionMode;
+use witness_generators::{AssertZeroSolverGenerator, TargetExpression};
 
-/// Module in charge of translating each AssertZero operation. Currently, the Plonky2 feature used
-/// to deal with systems of equations is the ArithmeticGate, which the CircuitBuilder uses
-/// extensively (through methods like add, sub, mul, mul_const, etc.)
+/// Module in charge of translating each AssertZero operation. Each expression is checked with the
+/// custom AcirExpressionGate, which evaluates a fixed number of its terms in each of its
+/// operations, instead of one ArithmeticGate operation per term.
 pub struct AssertZeroTranslator<'a> {
     builder: &'a mut CircuitBuilder<F, D>,
     witness_target_map: &'a mut HashMap<Witness, Target>,
     expression: &'a Expression,
+    field_conversion_mode: FieldConversionMode,
+    solved_witnesses: &'a mut HashSet<Witness>,
+    acir_expression_gate: AcirExpressionGate,
+    translation_cache: &'a mut TranslationCache,
 }
 
 impl<'a> AssertZeroTranslator<'a> {
@@ -14,26 +20,139 @@ impl<'a> AssertZeroTranslator<'a> {
         builder: &'a mut CircuitBuilder<F, D>,
         witness_target_map: &'a mut HashMap<Witness, Target>,
         expression: &'a Expression,
+        field_conversion_mode: FieldConversionMode,
+        solved_witnesses: &'a mut HashSet<Witness>,
+        acir_expression_gate: AcirExpressionGate,
+        translation_cache: &'a mut TranslationCache,
     ) -> AssertZeroTranslator<'a> {
         Self {
             builder,
             witness_target_map,
             expression,
+            field_conversion_mode,
+            solved_witnesses,
+            acir_expression_gate,
+            translation_cache,
         }
     }
 
     pub fn translate(&mut self) {
         self._register_intermediate_witnesses_for_assert_zero();
         self._translate_assert_zero();
+        self._add_generator_for_unknown_witness();
     }
 
+    /// Like the ACVM, an AssertZero opcode with a single unsolved witness is used to compute it,
+    /// as long as the witness isn't multiplied by itself.
+    fn _add_generator_for_unknown_witness(&mut self) {
+        let mut unknown_witnesses: Vec<Witness> = self
+            .expression
+            .linear_combinations
+            .iter()
+            .map(|(_, witness)| *witness)
+            .chain(
+                self.expression
+                    .mul_terms
+                    .iter()
+                    .flat_map(|(_, witness_1, witness_2)| [*witness_1, *witness_2]),
+            )
+            .filter(|witness| !self.solved_witnesses.contains(witness))
+            .collect();
+        unknown_witnesses.sort();
+        unknown_witnesses.dedup();
+        let [unknown_witness] = unknown_witnesses[..] else {
+            return;
+        };
+        let is_squared = self
+            .expression
+            .mul_terms
+            .iter()
+            .any(|(_, witness_1, witness_2)| {
+                *witness_1 == unknown_witness && *witness_2 == unknown_witness
+            });
+        if is_squared {
+            return;
+        }
+
+        let expression = TargetExpression::new_for(
+            self.expression,
+            self.witness_target_map,
+            self.field_conversion_mode,
+        );
+        self.builder.add_simple_generator(AssertZeroSolverGenerator {
+            expression,
+            unknown: self.witness_target_map[&unknown_witness],
+        });
+        self.solved_witnesses.insert(unknown_witness);
+    }
+
+    /// The expression is checked with as many AcirExpressionGate operations as its terms need,
+    /// each one adding its share of the terms to the output of the previous operation. The
+    /// constant term goes in the first operation, and the output of the last one must be zero.
+    /// The operations are placed with find_slot, so they share rows with the ones of other
+    /// expressions.
+    ///
+    /// Products already computed by other opcodes, or shared with previous expressions (see the
+    /// translation cache), are added as linear terms, since an operation has room for fewer
+    /// products than linear terms.
     fn _translate_assert_zero(self: &mut Self) {
-        let g_constant = self._field_element_to_goldilocks_field(&self.expression.q_c);
+        let g_constant = self._field_element_to_goldilocks_field(&self.expression.q_c, || {
+            String::from("The constant term")
+        });
+        let mut linear_terms = self._linear_terms();
+        let mut mul_terms = Vec::new();
+        for (coefficient, target_1, target_2) in self._mul_terms() {
+            match self
+                .translation_cache
+                .expression_product(self.builder, target_1, target_2)
+            {
+                Some(product) => linear_terms.push((coefficient, product)),
+                None => mul_terms.push((coefficient, target_1, target_2)),
+            }
+        }
+
+        let gate = self.acir_expression_gate;
+        let ops_for_linear_terms = linear_terms.len().div_ceil(gate.num_linear_terms);
+        let ops_for_mul_terms = mul_terms.len().div_ceil(gate.num_mul_terms);
+        let num_ops = ops_for_linear_terms.max(ops_for_mul_terms).max(1);
+
+        let zero = self.builder.zero();
+        let mut current_acc_target = zero;
+        for op_index in 0..num_ops {
+            let op_constant = if op_index == 0 { g_constant } else { F::ZERO };
+            let op_linear_terms = Self::_chunk(&linear_terms, op_index, gate.num_linear_terms);
+            let op_mul_terms = Self::_chunk(&mul_terms, op_index, gate.num_mul_terms);
+            let (row, i) = self.builder.find_slot(gate, &[], &[]);
 
-        let constant_target = self.builder.constant(g_constant);
-        let mut current_acc_target = constant_target;
-        current_acc_target = self._add_linear_combinations(current_acc_target);
-        current_acc_target = self._add_cuadratic_combinations(current_acc_target);
+            let connect_constant = |builder: &mut CircuitBuilder<F, D>, c: F, wire: usize| {
+                let constant = builder.constant(c);
+                builder.connect(constant, Target::wire(row, wire));
+            };
+            connect_constant(self.builder, op_constant, gate.wire_ith_constant_term(i));
+            for j in 0..gate.num_linear_terms {
+                let (coefficient, target) =
+                    op_linear_terms.get(j).copied().unwrap_or((F::ZERO, zero));
+                connect_constant(self.builder, coefficient, gate.wire_ith_linear_coefficient(i, j));
+                self.builder
+                    .connect(target, Target::wire(row, gate.wire_ith_linear_term(i, j)));
+            }
+            for j in 0..gate.num_mul_terms {
+                let (coefficient, first_factor, second_factor) =
+                    op_mul_terms.get(j).copied().unwrap_or((F::ZERO, zero, zero));
+                connect_constant(self.builder, coefficient, gate.wire_ith_mul_coefficient(i, j));
+                self.builder.connect(
+                    first_factor,
+                    Target::wire(row, gate.wire_ith_mul_term_first_factor(i, j)),
+                );
+                self.builder.connect(
+                    second_factor,
+                    Target::wire(row, gate.wire_ith_mul_term_second_factor(i, j)),
+                );
+            }
+            self.builder
+                .connect(current_acc_target, Target::wire(row, gate.wire_ith_input(i)));
+            current_acc_target = Target::wire(row, gate.wire_ith_output(i));
+        }
         self.builder.assert_zero(current_acc_target);
     }
 
@@ -59,64 +178,58 @@ impl<'a> AssertZeroTranslator<'a> {
         }
     }
 
-    fn _add_cuadratic_combinations(self: &mut Self, mut current_acc_target: Target) -> Target {
-        let mul_terms = &self.expression.mul_terms;
-        for mul_term in mul_terms {
-            let (f_cuadratic_factor, public_input_witness_1, public_input_witness_2) = mul_term;
-            let cuadratic_target = self._compute_cuadratic_term_target(
-                f_cuadratic_factor,
-                public_input_witness_1,
-                public_input_witness_2,
-            );
-            let new_target = self.builder.add(cuadratic_target, current_acc_target);
-            current_acc_target = new_target;
-        }
-        current_acc_target
+    fn _linear_terms(self: &mut Self) -> Vec<(F, Target)> {
+        let expression = self.expression;
+        expression
+            .linear_combinations
+            .iter()
+            .map(|(f_multiply_factor, public_input_witness)| {
+                let g_multiply_factor =
+                    self._field_element_to_goldilocks_field(f_multiply_factor, || {
+                        format!("The coefficient of witness {}", public_input_witness.0)
+                    });
+                (g_multiply_factor, self.witness_target_map[public_input_witness])
+            })
+            .collect()
     }
 
-    fn _add_linear_combinations(self: &mut Self, mut current_acc_target: Target) -> Target {
-        let linear_combinations = &self.expression.linear_combinations;
-        for (f_multiply_factor, public_input_witness) in linear_combinations {
-            let linear_combination_target =
-                self._compute_linear_combination_target(f_multiply_factor, public_input_witness);
-            let new_target = self
-                .builder
-                .add(linear_combination_target, current_acc_target);
-            current_acc_target = new_target;
-        }
-        current_acc_target
+    fn _mul_terms(self: &mut Self) -> Vec<(F, Target, Target)> {
+        let expression = self.expression;
+        expression
+            .mul_terms
+            .iter()
+            .map(|(f_cuadratic_factor, public_input_witness_1, public_input_witness_2)| {
+                let g_cuadratic_factor =
+                    self._field_element_to_goldilocks_field(f_cuadratic_factor, || {
+                        format!(
+                            "The coefficient of the product of witnesses {} and {}",
+                            public_input_witness_1.0, public_input_witness_2.0
+                        )
+                    });
+                (
+                    g_cuadratic_factor,
+                    self.witness_target_map[public_input_witness_1],
+                    self.witness_target_map[public_input_witness_2],
+                )
+            })
+            .collect()
     }
 
-    fn _compute_linear_combination_target(
-        self: &mut Self,
-        f_multiply_constant_factor: &FieldElement,
-        public_input_witness: &Witness,
-    ) -> Target {
-        let factor_target = *self.witness_target_map.get(public_input_witness).unwrap();
-        let g_first_pi_factor = self._field_element_to_goldilocks_field(f_multiply_constant_factor);
-        self.builder.mul_const(g_first_pi_factor, factor_target)
+    /// The terms that go in the given operation of the gate, which might be none.
+    fn _chunk<T>(terms: &[T], op_index: usize, terms_per_op: usize) -> &[T] {
+        let start = (op_index * terms_per_op).min(terms.len());
+        let end = (start + terms_per_op).min(terms.len());
+        &terms[start..end]
     }
 
-    fn _compute_cuadratic_term_target(
+    fn _field_element_to_goldilocks_field(
         self: &mut Self,
-        f_cuadratic_factor: &FieldElement,
-        public_input_witness_1: &Witness,
-        public_input_witness_2: &Witness,
-    ) -> Target {
-        let g_cuadratic_factor = self._field_element_to_goldilocks_field(f_cuadratic_factor);
-        let first_public_input_target =
-            *self.witness_target_map.get(public_input_witness_1).unwrap();
-        let second_public_input_target =
-            *self.witness_target_map.get(public_input_witness_2).unwrap();
-
-        let cuadratic_target = self
-            .builder
-            .mul(first_public_input_target, second_public_input_target);
-        self.builder.mul_const(g_cuadratic_factor, cuadratic_target)
-    }
-
-    fn _field_element_to_goldilocks_field(self: &mut Self, fe: &FieldElement) -> F {
-        let fe_as_big_uint = BigUint::from_bytes_be(&fe.to_be_bytes() as &[u8]);
-        F::from_noncanonical_biguint(fe_as_big_uint)
+        fe: &FieldElement,
+        term_description: impl Fn() -> String,
+    ) -> F {
+        let expression = self.expression;
+        self.field_conversion_mode.field_element_to_goldilocks_field(fe, || {
+            format!("{} of the AssertZero opcode {}", term_description(), expression)
+        })
     }
 }
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use plonky2::gates::lookup_table::LookupTable;
+
+/// The And and Xor tables have 65,536 entries, which take about 2.5k rows of LookupTableGates
+/// however many operations use them, while a byte looked up instead of going through a bit
+/// decomposition saves around a row. So the operations of a circuit only use the table when they
+/// look up at least this many bytes in it, and are decomposed into bits otherwise.
+pub const BITWISE_LOOKUP_THRESHOLD: usize = 2048;
+
+/// Plonky2 lookup tables map a 16 bit input to a 16 bit output, so a table can hold a binary
+/// operation over bytes: the input is lhs * 256 + rhs and the output the result for that pair of
+/// bytes. A 32 bit AND or XOR is then 4 lookups instead of a few gates per bit.
+///
+/// The Range table maps every byte to itself: looking up a target in it checks that the target is
+/// a byte, which the binary tables need from their operands since lhs * 256 + rhs could also be
+/// written with a rhs bigger than a byte.
+///
+/// The nibble tables hold the bitwise functions of three operands used by SHA256: the input is
+/// x * 256 + y * 16 + z for three nibbles and the output the nibble of the result. Their operands
+/// are always built from bits, so they don't need a range check.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ByteLookupTable {
+    Range,
+    And,
+    Xor,
+    /// x ^ y ^ z
+    Xor3Nibbles,
+    /// (x & y) ^ (!x & z)
+    ChooseNibbles,
+    /// (x & y) ^ (x & z) ^ (y & z)
+    MajorityNibbles,
+}
+
+impl ByteLookupTable {
+    pub fn entries(self) -> LookupTable {
+        let entries = match self {
+            ByteLookupTable::Range => (0..=u8::MAX as u16).map(|byte| (byte, byte)).collect(),
+            ByteLookupTable::And => Self::_binary_operation_entries(|lhs, rhs| lhs & rhs),
+            ByteLookupTable::Xor => Self::_binary_operation_entries(|lhs, rhs| lhs ^ rhs),
+            ByteLookupTable::Xor3Nibbles => Self::_ternary_nibble_entries(|x, y, z| x ^ y ^ z),
+            ByteLookupTable::ChooseNibbles => {
+                Self::_ternary_nibble_entries(|x, y, z| (x & y) ^ (!x & z))
+            }
+            ByteLookupTable::MajorityNibbles => {
+                Self::_ternary_nibble_entries(|x, y, z| (x & y) ^ (x & z) ^ (y & z))
+            }
+        };
+        Arc::new(entries)
+    }
+
+    fn _binary_operation_entries(operation: fn(u8, u8) -> u8) -> Vec<(u16, u16)> {
+        (0..=u16::MAX)
+            .map(|input| {
+                let [lhs, rhs] = input.to_be_bytes();
+                (input, operation(lhs, rhs) as u16)
+            })
+            .collect()
+    }
+
+    fn _ternary_nibble_entries(operation: fn(u16, u16, u16) -> u16) -> Vec<(u16, u16)> {
+        (0..1 << 12)
+            .map(|input| {
+                let (x, y, z) = (input >> 8, (input >> 4) & 0xf, input & 0xf);
+                (input, operation(x, y, z) & 0xf)
+            })
+            .collect()
+    }
+}
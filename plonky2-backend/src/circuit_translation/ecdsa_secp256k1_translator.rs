@@ -48,14 +48,64 @@ impl<'a> EcdsaSecp256k1Translator<'a> {
             self.hashed_msg.to_vec()
         );
 
-        let r_point = self._calculate_r(&public_key, &r, &s, &h);
+        // A signature with r = 0 or s = 0 is invalid, but s can't be inverted and a zero scalar
+        // has no point to multiply to, so the circuit couldn't be satisfied. Both are replaced by
+        // one to compute R, and the output is forced to false.
+        let r_is_zero = self._is_zero_mod_n(&r);
+        let s_is_zero = self._is_zero_mod_n(&s);
+        let is_degenerate = self.circuit_builder.builder.or(r_is_zero, s_is_zero);
+        let r_or_one = self._one_if(is_degenerate, &r);
+        let s_or_one = self._one_if(is_degenerate, &s);
+
+        let r_point = self._calculate_r(&public_key, &r_or_one, &s_or_one, &h);
+
+        // The signature is valid when r == R.x mod n. R.x is an element of the base field, so it
+        // has to be reduced modulo the order of the curve (the scalar field) before comparing.
+        let r_point_x_mod_n = self
+            .circuit_builder
+            .builder
+            .reduce::<Secp256K1Scalar>(&r_point.x.value);
+        let is_r_equal = self._is_equal_biguint(&r.value, &r_point_x_mod_n.value);
+        let builder = &mut self.circuit_builder.builder;
+        let is_not_degenerate = builder.not(is_degenerate);
+        let does_signature_verify = builder.and(is_r_equal, is_not_degenerate);
+
+        self.circuit_builder
+            .set_target_for_witness(self.output, does_signature_verify.target);
+    }
 
-        let does_signature_verify = self.circuit_builder.builder.cmp_biguint(
-            &r.value, &r_point.x.value,
-        );
+    fn _is_zero_mod_n(&mut self, x: &NonNativeTarget<Secp256K1Scalar>) -> BoolTarget {
+        let builder = &mut self.circuit_builder.builder;
+        let reduced = builder.reduce_nonnative(x);
+        let zero = builder.zero();
+        let mut is_zero = builder._true();
+        for limb in &reduced.value.limbs {
+            let is_limb_zero = builder.is_equal(limb.0, zero);
+            is_zero = builder.and(is_zero, is_limb_zero);
+        }
+        is_zero
+    }
+
+    /// The scalar itself, or one when the condition holds.
+    fn _one_if(
+        &mut self,
+        condition: BoolTarget,
+        x: &NonNativeTarget<Secp256K1Scalar>,
+    ) -> NonNativeTarget<Secp256K1Scalar> {
+        let builder = &mut self.circuit_builder.builder;
+        let one = builder.constant_nonnative(Secp256K1Scalar::ONE);
+        builder.if_nonnative(condition, &one, x)
+    }
 
-        let output_target = self.circuit_builder.target_for_witness(self.output);
-        self.circuit_builder.builder.connect(does_signature_verify.target, output_target);
+    fn _is_equal_biguint(&mut self, a: &BigUintTarget, b: &BigUintTarget) -> BoolTarget {
+        let builder = &mut self.circuit_builder.builder;
+        let (a, b) = builder.pad_biguints(a, b);
+        let mut are_equal = builder._true();
+        for (a_limb, b_limb) in a.limbs.iter().zip(b.limbs.iter()) {
+            let are_limbs_equal = builder.is_equal(a_limb.0, b_limb.0);
+            are_equal = builder.and(are_equal, are_limbs_equal);
+        }
+        are_equal
     }
 
     fn _calculate_r(
@@ -99,21 +149,20 @@ impl<'a> EcdsaSecp256k1Translator<'a> {
             self.circuit_builder._get_or_create_target_for_witness(i.witness)
         }).collect();
 
+        // The bytes are big endian, while the limbs of a BigUintTarget are little endian
         let mut u32_limbs: Vec<U32Target> = Vec::new();
         for u32_target_index in 0..8 {
-            let target_0 = byte_targets[4*u32_target_index];
-            let target_1 = byte_targets[4*u32_target_index+1];
-            let target_1_papota = self.circuit_builder.builder.mul_const(F::from_canonical_u64(1<<8), target_1);
-            let target_2 = byte_targets[4*u32_target_index+2];
-            let target_2_papota = self.circuit_builder.builder.mul_const(F::from_canonical_u64(1<<16), target_2);
-            let target_3 = byte_targets[4*u32_target_index+3];
-            let target_3_papota = self.circuit_builder.builder.mul_const(F::from_canonical_u64(1<<24), target_3);
-
-            let target = self.circuit_builder.builder.add_many([
-               target_0, target_1_papota, target_2_papota, target_3_papota
-            ]);
-            let u32_target = U32Target(target);
-            u32_limbs.push(u32_target);
+            let first_byte_index = 28 - 4 * u32_target_index;
+            let limb_bytes = &byte_targets[first_byte_index..first_byte_index + 4];
+            let mut target = limb_bytes[0];
+            for byte_target in &limb_bytes[1..] {
+                target = self.circuit_builder.builder.mul_const_add(
+                    F::from_canonical_u64(1 << 8),
+                    target,
+                    *byte_target,
+                );
+            }
+            u32_limbs.push(U32Target(target));
         }
         let bui_target = BigUintTarget{ limbs: u32_limbs };
         let nonnative_target = self.circuit_builder.builder.biguint_to_nonnative(&bui_target);
@@ -122,11 +171,12 @@ impl<'a> EcdsaSecp256k1Translator<'a> {
 
     fn _connect_32_bytes_with_256_bits(&mut self, bytes_targets: Vec<BinaryDigitsTarget>, bits_targets: Vec<BoolTarget>) {
         for index_byte in 0..32 {
+            let byte_bits = bytes_targets[index_byte].bits(&mut self.circuit_builder.builder);
             for index_bit in 0..8 {
                 let index_bit_in_biguint = 8 * index_byte + index_bit;
                 self.circuit_builder.builder.connect(
                     bits_targets[index_bit_in_biguint].target,
-                    bytes_targets[index_byte].bits[index_bit].target,
+                    byte_bits[index_bit].target,
                 );
             }
         }
@@ -0,0 +1,474 @@
+use plonky2::field::types::PrimeField64;
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::generator::{
+    GeneratedValues, SimpleGenerator, WitnessGenerator, WitnessGeneratorRef,
+};
+use plonky2::iop::witness::{PartitionWitness, Witness as _, WitnessWrite};
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, IoResult, Read, Write};
+
+use super::*;
+
+/// Blocks with more positions than this that are written at a non constant index are translated
+/// with the memory checking argument of this module. Other blocks keep the vector of targets
+/// representation, where a write costs an is_equal and an _if per position: the argument costs a
+/// few gates per access, and since the initialization of every position counts as an access, it
+/// only pays off for longer blocks. Blocks that are only read (or written at constant indices)
+/// are cheaper as vectors whatever their length, since a read costs a random access per
+/// sub-block of max_random_access_length positions.
+pub const MEMORY_CHECKING_THRESHOLD: usize = 64;
+
+/// Memory checking for a block of memory: instead of keeping a target per position, every access
+/// (including the initialization of each position) is recorded as an (index, timestamp, value,
+/// is_write) tuple, so reads and writes cost the same no matter the length of the block.
+///
+/// When the whole circuit has been translated, the trace is sorted by (index, timestamp) out of
+/// circuit, and the circuit checks that:
+/// 1. The sorted trace is a permutation of the trace. Both are compressed with a random challenge
+///    and compared through a grand product against another one. The challenges are derived from a
+///    Poseidon hash of both traces and live in the extension field.
+/// 2. The sorted trace is consistent: indices go from 0 to length - 1 increasing by 0 or 1, a new
+///    index starts with a write, timestamps increase within an index and every read returns the
+///    value of the previous access to the same index.
+pub struct MemoryTrace {
+    length: usize,
+    indices: Vec<Target>,
+    timestamps: Vec<usize>,
+    values: Vec<Target>,
+    writes: Vec<bool>,
+    generated_reads: Vec<bool>,
+    block_snapshots: Vec<(usize, Vec<Target>)>,
+}
+
+impl MemoryTrace {
+    /// The initialization of position i is recorded as a write with timestamp i, so every access
+    /// performed later has a bigger timestamp.
+    pub fn new_for(init: Vec<Target>, builder: &mut CB) -> Self {
+        let mut trace = Self {
+            length: init.len(),
+            indices: Vec::new(),
+            timestamps: Vec::new(),
+            values: Vec::new(),
+            writes: Vec::new(),
+            generated_reads: Vec::new(),
+            block_snapshots: Vec::new(),
+        };
+        for (position, value) in init.into_iter().enumerate() {
+            let index = builder.constant(F::from_canonical_usize(position));
+            trace.record_write(index, value);
+        }
+        trace
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn record_read(&mut self, index: Target, value: Target) {
+        self._record_access(index, value, false);
+    }
+
+    pub fn record_write(&mut self, index: Target, value: Target) {
+        self._record_access(index, value, true);
+    }
+
+    /// Marks the value of the last access recorded, which must be a read, as generated by the
+    /// replay of the trace: it is the value of the last write to the same index. This way the read
+    /// value doesn't need to be provided when proving.
+    pub fn generate_last_read(&mut self) {
+        *self.generated_reads.last_mut().unwrap() = true;
+    }
+
+    /// Targets with the values the block holds after the accesses recorded so far, for the Brillig
+    /// calls that read the whole block. They're only set by the replay of the trace, since a
+    /// Brillig call doesn't constrain its inputs.
+    pub fn add_current_values(&mut self, builder: &mut CB) -> Vec<Target> {
+        let values = builder.add_virtual_targets(self.length);
+        self.block_snapshots.push((self.indices.len(), values.clone()));
+        values
+    }
+
+    fn _record_access(&mut self, index: Target, value: Target, is_write: bool) {
+        self.timestamps.push(self.indices.len());
+        self.indices.push(index);
+        self.values.push(value);
+        self.writes.push(is_write);
+        self.generated_reads.push(false);
+    }
+
+    /// Adds the constraints of the memory checking argument, along with the generator replaying
+    /// the trace. It must be called once all the accesses to the block have been recorded.
+    pub fn add_consistency_constraints(&self, builder: &mut CB) {
+        builder.add_generators(vec![WitnessGeneratorRef::new(MemoryReplayGenerator {
+            length: self.length,
+            indices: self.indices.clone(),
+            values: self.values.clone(),
+            writes: self.writes.clone(),
+            generated_reads: self.generated_reads.clone(),
+            block_snapshots: self.block_snapshots.clone(),
+        })]);
+        let sorted_trace = SortedMemoryTrace::new_for(self.indices.len(), builder);
+        builder.add_simple_generator(SortedMemoryTraceGenerator {
+            indices: self.indices.clone(),
+            timestamps: self.timestamps.clone(),
+            values: self.values.clone(),
+            writes: self.writes.clone(),
+            sorted_trace: sorted_trace.clone(),
+        });
+
+        self._add_permutation_constraints(&sorted_trace, builder);
+        self._add_sorted_trace_constraints(&sorted_trace, builder);
+    }
+
+    fn _add_permutation_constraints(&self, sorted_trace: &SortedMemoryTrace, builder: &mut CB) {
+        // Constants (like the indices of the initialization) can't be chosen by the prover, so
+        // there's no need to hash them
+        let mut hash_inputs: Vec<Target> = [self.indices.clone(), self.values.clone()]
+            .concat()
+            .into_iter()
+            .filter(|target| builder.target_as_constant(*target).is_none())
+            .collect();
+        hash_inputs.extend(sorted_trace.all_targets());
+        let challenges = builder
+            .hash_n_to_hash_no_pad::<PoseidonHash>(hash_inputs)
+            .elements;
+        let alpha = ExtensionTarget([challenges[0], challenges[1]]);
+        let gamma = ExtensionTarget([challenges[2], challenges[3]]);
+
+        let mut trace_product = builder.one_extension();
+        for access in 0..self.indices.len() {
+            let timestamp = builder.constant(F::from_canonical_usize(self.timestamps[access]));
+            let is_write = builder.constant(F::from_bool(self.writes[access]));
+            let compressed_access = Self::_compress_access(
+                [self.indices[access], timestamp, self.values[access], is_write],
+                alpha,
+                builder,
+            );
+            let factor = builder.sub_extension(gamma, compressed_access);
+            trace_product = builder.mul_extension(trace_product, factor);
+        }
+
+        let mut sorted_trace_product = builder.one_extension();
+        for access in 0..self.indices.len() {
+            let compressed_access =
+                Self::_compress_access(sorted_trace.access(access), alpha, builder);
+            let factor = builder.sub_extension(gamma, compressed_access);
+            sorted_trace_product = builder.mul_extension(sorted_trace_product, factor);
+        }
+
+        builder.connect_extension(trace_product, sorted_trace_product);
+    }
+
+    /// index + alpha * timestamp + alpha^2 * value + alpha^3 * is_write, using Horner's method.
+    fn _compress_access(
+        access: [Target; 4],
+        alpha: ExtensionTarget<D>,
+        builder: &mut CB,
+    ) -> ExtensionTarget<D> {
+        let mut compressed_access = builder.convert_to_ext(access[3]);
+        for target in access[..3].iter().rev() {
+            let term = builder.convert_to_ext(*target);
+            compressed_access = builder.mul_add_extension(alpha, compressed_access, term);
+        }
+        compressed_access
+    }
+
+    fn _add_sorted_trace_constraints(&self, sorted_trace: &SortedMemoryTrace, builder: &mut CB) {
+        let zero = builder.zero();
+        let one = builder.one();
+        let last_position = builder.constant(F::from_canonical_usize(self.length - 1));
+        let amount_of_accesses = self.indices.len();
+        let timestamp_bits = (usize::BITS - amount_of_accesses.leading_zeros()) as usize;
+
+        builder.connect(sorted_trace.indices[0], zero);
+        builder.connect(sorted_trace.writes[0], one);
+        builder.connect(sorted_trace.indices[amount_of_accesses - 1], last_position);
+
+        for access in 1..amount_of_accesses {
+            let index_delta = builder.sub(
+                sorted_trace.indices[access],
+                sorted_trace.indices[access - 1],
+            );
+            builder.assert_bool(BoolTarget::new_unsafe(index_delta));
+            let same_index = builder.sub(one, index_delta);
+            let is_read = builder.sub(one, sorted_trace.writes[access]);
+
+            // A new index can only be accessed for the first time through its initialization
+            let new_index_read = builder.mul(index_delta, is_read);
+            builder.assert_zero(new_index_read);
+
+            // Within the same index, (timestamp delta - 1) must be a small non negative number
+            let timestamp_delta = builder.sub(
+                sorted_trace.timestamps[access],
+                sorted_trace.timestamps[access - 1],
+            );
+            let timestamp_gap = builder.mul_sub(same_index, timestamp_delta, same_index);
+            builder.range_check(timestamp_gap, timestamp_bits);
+
+            // A read within the same index returns the value of the previous access
+            let value_delta = builder.sub(
+                sorted_trace.values[access],
+                sorted_trace.values[access - 1],
+            );
+            let is_read_of_same_index = builder.mul(same_index, is_read);
+            let value_mismatch = builder.mul(is_read_of_same_index, value_delta);
+            builder.assert_zero(value_mismatch);
+        }
+    }
+}
+
+/// Targets holding the trace sorted by (index, timestamp), which are set by the
+/// SortedMemoryTraceGenerator.
+#[derive(Clone, Debug)]
+struct SortedMemoryTrace {
+    indices: Vec<Target>,
+    timestamps: Vec<Target>,
+    values: Vec<Target>,
+    writes: Vec<Target>,
+}
+
+impl SortedMemoryTrace {
+    fn new_for(amount_of_accesses: usize, builder: &mut CB) -> Self {
+        Self {
+            indices: builder.add_virtual_targets(amount_of_accesses),
+            timestamps: builder.add_virtual_targets(amount_of_accesses),
+            values: builder.add_virtual_targets(amount_of_accesses),
+            writes: builder.add_virtual_targets(amount_of_accesses),
+        }
+    }
+
+    fn access(&self, access: usize) -> [Target; 4] {
+        [
+            self.indices[access],
+            self.timestamps[access],
+            self.values[access],
+            self.writes[access],
+        ]
+    }
+
+    fn all_targets(&self) -> Vec<Target> {
+        [
+            self.indices.clone(),
+            self.timestamps.clone(),
+            self.values.clone(),
+            self.writes.clone(),
+        ]
+        .concat()
+    }
+}
+
+#[derive(Debug)]
+struct SortedMemoryTraceGenerator {
+    indices: Vec<Target>,
+    timestamps: Vec<usize>,
+    values: Vec<Target>,
+    writes: Vec<bool>,
+    sorted_trace: SortedMemoryTrace,
+}
+
+impl SimpleGenerator<F, D> for SortedMemoryTraceGenerator {
+    fn id(&self) -> String {
+        "SortedMemoryTraceGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        [self.indices.clone(), self.values.clone()].concat()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let mut accesses: Vec<(u64, usize, F, bool)> = (0..self.indices.len())
+            .map(|access| {
+                (
+                    witness.get_target(self.indices[access]).to_canonical_u64(),
+                    self.timestamps[access],
+                    witness.get_target(self.values[access]),
+                    self.writes[access],
+                )
+            })
+            .collect();
+        accesses.sort_by_key(|(index, timestamp, _, _)| (*index, *timestamp));
+
+        for (position, (index, timestamp, value, is_write)) in accesses.into_iter().enumerate() {
+            let sorted_trace = &self.sorted_trace;
+            out_buffer.set_target(sorted_trace.indices[position], F::from_canonical_u64(index));
+            out_buffer.set_target(
+                sorted_trace.timestamps[position],
+                F::from_canonical_usize(timestamp),
+            );
+            out_buffer.set_target(sorted_trace.values[position], value);
+            out_buffer.set_target(sorted_trace.writes[position], F::from_bool(is_write));
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_target_vec(&self.indices)?;
+        dst.write_target_vec(&self.values)?;
+        for (timestamp, is_write) in self.timestamps.iter().zip(&self.writes) {
+            dst.write_usize(*timestamp)?;
+            dst.write_bool(*is_write)?;
+        }
+        dst.write_target_vec(&self.sorted_trace.indices)?;
+        dst.write_target_vec(&self.sorted_trace.timestamps)?;
+        dst.write_target_vec(&self.sorted_trace.values)?;
+        dst.write_target_vec(&self.sorted_trace.writes)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let indices = src.read_target_vec()?;
+        let values = src.read_target_vec()?;
+        let mut timestamps = Vec::with_capacity(indices.len());
+        let mut writes = Vec::with_capacity(indices.len());
+        for _ in 0..indices.len() {
+            timestamps.push(src.read_usize()?);
+            writes.push(src.read_bool()?);
+        }
+        let sorted_trace = SortedMemoryTrace {
+            indices: src.read_target_vec()?,
+            timestamps: src.read_target_vec()?,
+            values: src.read_target_vec()?,
+            writes: src.read_target_vec()?,
+        };
+        Ok(Self {
+            indices,
+            timestamps,
+            values,
+            writes,
+            sorted_trace,
+        })
+    }
+}
+
+/// Replays the accesses to a block in order, setting the value of every generated read and of
+/// every snapshot of the block taken for a Brillig call. A single generator serves the whole
+/// block, so the prover data holds the trace once.
+///
+/// It can't be a SimpleGenerator: the value written by an access is often computed from a
+/// previous read (or from a Brillig call reading the block), which this same generator sets.
+/// Instead, every run replays the trace from the start up to the first index or written value
+/// that is still unknown, and the generator is run again when one of them is set.
+#[derive(Debug)]
+struct MemoryReplayGenerator {
+    length: usize,
+    indices: Vec<Target>,
+    values: Vec<Target>,
+    writes: Vec<bool>,
+    generated_reads: Vec<bool>,
+    block_snapshots: Vec<(usize, Vec<Target>)>,
+}
+
+impl MemoryReplayGenerator {
+    /// The value of a target, either in the witness or generated earlier in the same run.
+    fn _value_of(
+        target: Target,
+        witness: &PartitionWitness<F>,
+        generated_values: &HashMap<Target, F>,
+    ) -> Option<F> {
+        witness
+            .try_get_target(target)
+            .or_else(|| generated_values.get(&target).copied())
+    }
+
+    fn _set_if_unknown(
+        target: Target,
+        value: F,
+        witness: &PartitionWitness<F>,
+        out_buffer: &mut GeneratedValues<F>,
+    ) {
+        if witness.try_get_target(target).is_none() {
+            out_buffer.set_target(target, value);
+        }
+    }
+}
+
+impl WitnessGenerator<F, D> for MemoryReplayGenerator {
+    fn id(&self) -> String {
+        "MemoryReplayGenerator".to_string()
+    }
+
+    fn watch_list(&self) -> Vec<Target> {
+        let written_values = (0..self.values.len())
+            .filter(|access| self.writes[*access])
+            .map(|access| self.values[access]);
+        self.indices.iter().copied().chain(written_values).collect()
+    }
+
+    fn run(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) -> bool {
+        let mut block_values = vec![F::ZERO; self.length];
+        let mut generated_values: HashMap<Target, F> = HashMap::new();
+        let mut block_snapshots = self.block_snapshots.iter().peekable();
+        for access in 0..=self.indices.len() {
+            while let Some((_, snapshot)) =
+                block_snapshots.next_if(|(accesses_before, _)| *accesses_before == access)
+            {
+                for (target, value) in snapshot.iter().zip(&block_values) {
+                    Self::_set_if_unknown(*target, *value, witness, out_buffer);
+                }
+            }
+            if access == self.indices.len() {
+                break;
+            }
+
+            let Some(index) = Self::_value_of(self.indices[access], witness, &generated_values)
+            else {
+                return false;
+            };
+            let position = index.to_canonical_u64() as usize;
+            if position >= self.length {
+                panic!("Memory access out of bounds: position {}", position);
+            }
+            let value_target = self.values[access];
+            if self.writes[access] {
+                let Some(value) = Self::_value_of(value_target, witness, &generated_values) else {
+                    return false;
+                };
+                block_values[position] = value;
+            } else if self.generated_reads[access] {
+                Self::_set_if_unknown(value_target, block_values[position], witness, out_buffer);
+                generated_values.insert(value_target, block_values[position]);
+            }
+        }
+        true
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        dst.write_usize(self.length)?;
+        dst.write_target_vec(&self.indices)?;
+        dst.write_target_vec(&self.values)?;
+        for (is_write, is_generated_read) in self.writes.iter().zip(&self.generated_reads) {
+            dst.write_bool(*is_write)?;
+            dst.write_bool(*is_generated_read)?;
+        }
+        dst.write_usize(self.block_snapshots.len())?;
+        for (accesses_before, snapshot) in &self.block_snapshots {
+            dst.write_usize(*accesses_before)?;
+            dst.write_target_vec(snapshot)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let length = src.read_usize()?;
+        let indices = src.read_target_vec()?;
+        let values = src.read_target_vec()?;
+        let mut writes = Vec::with_capacity(indices.len());
+        let mut generated_reads = Vec::with_capacity(indices.len());
+        for _ in 0..indices.len() {
+            writes.push(src.read_bool()?);
+            generated_reads.push(src.read_bool()?);
+        }
+        let amount_of_snapshots = src.read_usize()?;
+        let mut block_snapshots = Vec::with_capacity(amount_of_snapshots);
+        for _ in 0..amount_of_snapshots {
+            block_snapshots.push((src.read_usize()?, src.read_target_vec()?));
+        }
+        Ok(Self {
+            length,
+            indices,
+            values,
+            writes,
+            generated_reads,
+            block_snapshots,
+        })
+    }
+}
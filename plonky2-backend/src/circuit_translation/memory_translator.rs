@@ -1,17 +1,37 @@
 use super::*;
+use crate::field_conversion::FieldConversionMode;
 
 /// This module performs memory operations such as creating blocks of memory, reading in a specific
-/// position known in prove time and write a value in a position both known at prove time. The
-/// length of the blocks is fixed and known in circuit building time.
+/// position and writing a value in a specific position, where the positions are either constants
+/// or known at prove time. The length of the blocks is fixed and known in circuit building time.
 ///
 /// The desired length of a memory block may not coincide with the length of the associated vector
 /// of targets representation. This is because we must append some zeroes at the end for making the
 /// length a power of 2, therefore the memory_blocks collaborator must hold the length of each
 /// memory block.
+///
+/// A RandomAccessGate can only index vectors of a bounded length, given by the routed wires of the
+/// circuit configuration (see max_random_access_length). Longer blocks are split into sub-blocks of
+/// that length, and a read at a position is a random access of the low part of the position in
+/// every sub-block, followed by a random access of the high part among the values read. Only the
+/// last sub-block is padded with zeroes, so the padding never doubles the length of a block.
+///
+/// Blocks longer than the memory checking threshold and written at a non constant index are not
+/// represented as a vector of targets, since such a write would cost O(length) gates. Their
+/// accesses are recorded in a MemoryTrace instead (see the memory_checking module), which makes
+/// every access cost O(1) gates.
+///
+/// The constants and products are taken from the translation cache, so the positions of a block
+/// and the products shared with other opcodes are only computed once.
 pub struct MemoryOperationsTranslator<'a> {
     builder: &'a mut CircuitBuilder<F, D>,
     witness_target_map: &'a mut HashMap<Witness, Target>,
     memory_blocks: &'a mut HashMap<BlockId, (Vec<Target>, usize)>,
+    memory_traces: &'a mut HashMap<BlockId, MemoryTrace>,
+    memory_checking_threshold: usize,
+    field_conversion_mode: FieldConversionMode,
+    solved_witnesses: &'a mut HashSet<Witness>,
+    translation_cache: &'a mut TranslationCache,
 }
 
 impl<'a> MemoryOperationsTranslator<'a> {
@@ -19,35 +39,63 @@ impl<'a> MemoryOperationsTranslator<'a> {
         builder: &'a mut CircuitBuilder<F, D>,
         witness_target_map: &'a mut HashMap<Witness, Target>,
         memory_blocks: &'a mut HashMap<BlockId, (Vec<Target>, usize)>,
+        memory_traces: &'a mut HashMap<BlockId, MemoryTrace>,
+        memory_checking_threshold: usize,
+        field_conversion_mode: FieldConversionMode,
+        solved_witnesses: &'a mut HashSet<Witness>,
+        translation_cache: &'a mut TranslationCache,
     ) -> Self {
         Self {
             builder,
             witness_target_map,
             memory_blocks,
+            memory_traces,
+            memory_checking_threshold,
+            field_conversion_mode,
+            solved_witnesses,
+            translation_cache,
         }
     }
 
+    /// Indices that are constant are resolved while building the circuit, so the operation only
+    /// moves targets around instead of adding random access or equality gates. Both the index and
+    /// the value can be any expression, not only a single witness.
     pub fn translate_memory_op(&mut self, block_id: &BlockId, op: &MemOp) {
-        self._register_intermediate_witnesses_for_memory_op(&op);
-
-        let witness_index_to_access = op.index.to_witness().unwrap();
-        let target_index_to_access =
-            self._get_or_create_target_for_witness(witness_index_to_access);
-        MemoryOperationsTranslator::add_restrictions_to_assert_target_is_less_or_equal_to(
-            self.memory_blocks.get(block_id).unwrap().1 - 1,
-            target_index_to_access,
-            &mut self.builder,
-        );
-
         let is_memory_read = op.clone().operation.to_const().unwrap().is_zero();
         let is_memory_write = op.clone().operation.to_const().unwrap().is_one();
-        if is_memory_read {
-            self._translate_memory_read(block_id, op);
-        } else if is_memory_write {
-            self._translate_memory_write(&block_id, op);
-        } else {
+        if !is_memory_read && !is_memory_write {
             panic!("Backend encountered unknown memory operation code (nor 0 or 1)");
         }
+
+        if self.memory_traces.contains_key(block_id) {
+            self._translate_memory_checked_op(block_id, is_memory_read, op);
+            return;
+        }
+
+        match op.index.to_const() {
+            Some(index) => {
+                let position = self._constant_position_in_block(block_id, &index);
+                if is_memory_read {
+                    self._translate_memory_read_at_constant_position(block_id, position, op);
+                } else {
+                    self._translate_memory_write_at_constant_position(block_id, position, op);
+                }
+            }
+            None => {
+                let target_index_to_access =
+                    self._target_for_expression(&op.index, "the memory index");
+                MemoryOperationsTranslator::add_restrictions_to_assert_target_is_less_or_equal_to(
+                    self.memory_blocks.get(block_id).unwrap().1 - 1,
+                    target_index_to_access,
+                    &mut self.builder,
+                );
+                if is_memory_read {
+                    self._translate_memory_read(block_id, target_index_to_access, op);
+                } else {
+                    self._translate_memory_write(block_id, target_index_to_access, op);
+                }
+            }
+        }
     }
 
     /// We use this algorithm to validate in-range access and restrain access to one of the padded
@@ -62,21 +110,19 @@ impl<'a> MemoryOperationsTranslator<'a> {
             .map(|c| c.to_digit(2).unwrap() as u8)
             .collect();
 
-        let binary_target_index = BinaryDigitsTarget {
-            bits: builder
-                .split_le(target_index, binary_representation.len())
-                .into_iter()
-                .rev()
-                .collect(),
-        };
+        let binary_target_index: Vec<BoolTarget> = builder
+            .split_le(target_index, binary_representation.len())
+            .into_iter()
+            .rev()
+            .collect();
 
         let mut acc_target = builder.one();
-        for i in 0..binary_target_index.number_of_digits() {
+        for i in 0..binary_target_index.len() {
             if binary_representation[i] == 0 {
-                let aux = builder.mul(binary_target_index.bits[i].target, acc_target);
+                let aux = builder.mul(binary_target_index[i].target, acc_target);
                 builder.assert_zero(aux);
             } else if binary_representation[i] == 1 {
-                let new_acc_target = builder.mul(acc_target, binary_target_index.bits[i].target);
+                let new_acc_target = builder.mul(acc_target, binary_target_index[i].target);
                 acc_target = new_acc_target;
             }
         }
@@ -86,12 +132,13 @@ impl<'a> MemoryOperationsTranslator<'a> {
     /// time. The solution, replacing all the targets, connecting all the values except for the
     /// one modified. To know what position is being modified, we use the Plonky2 EqualGate.
     /// The key is that the circuit has to be symmetrical for all possible values.
-    fn _translate_memory_write(&mut self, block_id: &BlockId, op: &MemOp) {
-        let witness_idx_to_write = op.index.to_witness().unwrap();
-        let target_idx_to_write = self._get_or_create_target_for_witness(witness_idx_to_write);
-        let witness_holding_new_value = op.value.to_witness().unwrap();
-        let target_holding_new_value =
-            self._get_or_create_target_for_witness(witness_holding_new_value);
+    fn _translate_memory_write(
+        &mut self,
+        block_id: &BlockId,
+        target_idx_to_write: Target,
+        op: &MemOp,
+    ) {
+        let target_holding_new_value = self._target_for_expression(&op.value, "the memory value");
 
         let memory_block_length = (&self.memory_blocks[block_id].0).len();
         for position in 0..memory_block_length {
@@ -112,50 +159,225 @@ impl<'a> MemoryOperationsTranslator<'a> {
     }
 
     /// For this Plonky2 uses the RandomAccessGate
-    fn _translate_memory_read(&mut self, block_id: &BlockId, op: &MemOp) {
-        let witness_idx_to_read = op.index.to_witness().unwrap();
-        let target_idx_to_read = self._get_or_create_target_for_witness(witness_idx_to_read);
-        let witness_to_save_result = op.value.to_witness().unwrap();
+    fn _translate_memory_read(
+        &mut self,
+        block_id: &BlockId,
+        target_idx_to_read: Target,
+        op: &MemOp,
+    ) {
         let block_of_memory = self.memory_blocks[block_id].0.clone();
-        let target_to_save_result = self
-            .builder
-            .random_access(target_idx_to_read, block_of_memory);
-        self.witness_target_map
-            .insert(witness_to_save_result, target_to_save_result);
+        let target_read = self._random_access(target_idx_to_read, block_of_memory);
+        self._bind_read_value(&op.value, target_read);
+    }
+
+    /// The vector must be padded by _pad_to_sub_blocks. When it's longer than a sub-block, the
+    /// index is split into the position inside a sub-block (the low bits) and the sub-block (the
+    /// high bits). The values read from each sub-block form a new vector indexed by the high bits,
+    /// which is split again if there are still too many sub-blocks.
+    fn _random_access(&mut self, index: Target, vector_targets: Vec<Target>) -> Target {
+        let sub_block_length = Self::max_random_access_length(&self.builder.config);
+        if vector_targets.len() <= sub_block_length {
+            return self.builder.random_access(index, vector_targets);
+        }
+        let low_bits = sub_block_length.trailing_zeros() as usize;
+        let num_sub_blocks = vector_targets.len() / sub_block_length;
+        let high_bits = num_sub_blocks.next_power_of_two().trailing_zeros() as usize;
+        let index_bits = self.builder.split_le(index, low_bits + high_bits);
+        let low_index = self.builder.le_sum(index_bits[..low_bits].iter());
+        let high_index = self.builder.le_sum(index_bits[low_bits..].iter());
+
+        let mut values_read: Vec<Target> = vector_targets
+            .chunks(sub_block_length)
+            .map(|sub_block| self.builder.random_access(low_index, sub_block.to_vec()))
+            .collect();
+        self._pad_to_sub_blocks(&mut values_read);
+        self._random_access(high_index, values_read)
+    }
+
+    /// The longest vector a RandomAccessGate can index with the configuration, which is a power of
+    /// two. Each copy of the gate needs a routed wire per element plus two more for the index and
+    /// the value read, and a wire per bit of the index.
+    pub fn max_random_access_length(config: &CircuitConfig) -> usize {
+        let mut bits = 0;
+        loop {
+            let next_length = 1 << (bits + 1);
+            if next_length + 2 > config.num_routed_wires
+                || next_length + 2 + bits + 1 > config.num_wires
+            {
+                return 1 << bits;
+            }
+            bits += 1;
+        }
+    }
+
+    /// With a constant index the position being written is known, so its target is just replaced.
+    fn _translate_memory_write_at_constant_position(
+        &mut self,
+        block_id: &BlockId,
+        position: usize,
+        op: &MemOp,
+    ) {
+        let target_holding_new_value = self._target_for_expression(&op.value, "the memory value");
+        self.memory_blocks.get_mut(block_id).unwrap().0[position] = target_holding_new_value;
+    }
+
+    fn _translate_memory_read_at_constant_position(
+        &mut self,
+        block_id: &BlockId,
+        position: usize,
+        op: &MemOp,
+    ) {
+        let target_read = self.memory_blocks[block_id].0[position];
+        self._bind_read_value(&op.value, target_read);
+    }
+
+    /// The index doesn't need to be range checked: the memory checking argument only accepts
+    /// indices of positions that were initialized.
+    fn _translate_memory_checked_op(
+        &mut self,
+        block_id: &BlockId,
+        is_memory_read: bool,
+        op: &MemOp,
+    ) {
+        let target_index_to_access = match op.index.to_const() {
+            Some(index) => {
+                let position = self._constant_position_in_block(block_id, &index);
+                self.builder.constant(F::from_canonical_usize(position))
+            }
+            None => self._target_for_expression(&op.index, "the memory index"),
+        };
+        let target_holding_value = self._target_for_expression(&op.value, "the memory value");
+
+        let memory_trace = self.memory_traces.get_mut(block_id).unwrap();
+        if is_memory_read {
+            memory_trace.record_read(target_index_to_access, target_holding_value);
+            if let Some(witness) = op.value.to_witness() {
+                if self.solved_witnesses.insert(witness) {
+                    memory_trace.generate_last_read();
+                }
+            }
+        } else {
+            memory_trace.record_write(target_index_to_access, target_holding_value);
+        }
+    }
+
+    /// Accessing a constant position outside the block can't be satisfied by any witness, so
+    /// it's reported while building the circuit.
+    fn _constant_position_in_block(&self, block_id: &BlockId, index: &FieldElement) -> usize {
+        let memory_block_length = match self.memory_traces.get(block_id) {
+            Some(memory_trace) => memory_trace.length(),
+            None => self.memory_blocks[block_id].1,
+        };
+        // to_u128 truncates, so a huge (or negative) index must be rejected before converting it
+        if index.num_bits() > 64 || index.to_u128() >= memory_block_length as u128 {
+            panic!(
+                "Memory access out of bounds: position {} in block {} of length {}",
+                index, block_id.0, memory_block_length
+            );
+        }
+        index.to_u128() as usize
     }
 
-    /// Creates a new block of memory with the associated id
-    pub fn translate_memory_init(&mut self, init: &Vec<Witness>, block_id: &BlockId) {
+    /// When the value is a witness seen for the first time, the target read becomes its target.
+    /// Otherwise, the value is computed and connected to the target read. Either way, a witness
+    /// read gets its value from the target read.
+    fn _bind_read_value(&mut self, value: &Expression, target_read: Target) {
+        if let Some(witness) = value.to_witness() {
+            self.solved_witnesses.insert(witness);
+        }
+        match value.to_witness() {
+            Some(witness) if !self.witness_target_map.contains_key(&witness) => {
+                self.witness_target_map.insert(witness, target_read);
+            }
+            _ => {
+                let target_holding_value = self._target_for_expression(value, "the memory value");
+                self.builder.connect(target_holding_value, target_read);
+            }
+        }
+    }
+
+    /// Creates a new block of memory with the associated id. Memory checking is only used for long
+    /// blocks that are written at a non constant index somewhere in the circuit.
+    pub fn translate_memory_init(
+        &mut self,
+        init: &Vec<Witness>,
+        block_id: &BlockId,
+        written_at_non_constant_index: bool,
+    ) {
         let mut vector_targets: Vec<Target> = init
             .into_iter()
             .map(|w| self._get_or_create_target_for_witness(*w))
             .collect();
+        if written_at_non_constant_index && vector_targets.len() > self.memory_checking_threshold {
+            let memory_trace = MemoryTrace::new_for(vector_targets, &mut self.builder);
+            self.memory_traces.insert(*block_id, memory_trace);
+            return;
+        }
         let real_memory_block_size = vector_targets.len();
-        self._extend_block_with_zeroes_to_have_a_power_of_two_length(&mut vector_targets);
+        self._pad_to_sub_blocks(&mut vector_targets);
         self.memory_blocks
             .insert(*block_id, (vector_targets, real_memory_block_size));
     }
 
-    /// This is necessary because plonky2 can only perform a random_access operation
-    /// on vectors with a length that is a power of two.
-    fn _extend_block_with_zeroes_to_have_a_power_of_two_length(
-        &mut self,
-        vector_targets: &mut Vec<Target>,
-    ) {
+    /// This is necessary because plonky2 can only perform a random_access operation on vectors
+    /// with a length that is a power of two. A vector that fits in a single sub-block is extended
+    /// with zeroes up to the next power of two, and a longer one up to a whole number of
+    /// sub-blocks, so every sub-block has the same length.
+    fn _pad_to_sub_blocks(&mut self, vector_targets: &mut Vec<Target>) {
         let length_of_block = vector_targets.len();
-        let targets_to_add = (length_of_block as u32)
-            .checked_next_power_of_two()
-            .unwrap_or(0)
-            - length_of_block as u32;
-        vector_targets.extend((0..targets_to_add).into_iter().map(|_| self.builder.zero()));
+        let sub_block_length = Self::max_random_access_length(&self.builder.config);
+        let padded_length = if length_of_block <= sub_block_length {
+            length_of_block.next_power_of_two()
+        } else {
+            length_of_block.next_multiple_of(sub_block_length)
+        };
+        let zero = self.builder.zero();
+        vector_targets.resize(padded_length, zero);
     }
 
-    fn _register_intermediate_witnesses_for_memory_op(self: &mut Self, op: &MemOp) {
-        let at = &op.index.linear_combinations[0].1;
-        self._get_or_create_target_for_witness(*at);
+    /// A single witness is mapped to its own target, while any other expression is computed
+    /// with arithmetic gates.
+    fn _target_for_expression(&mut self, expression: &Expression, description: &str) -> Target {
+        if let Some(witness) = expression.to_witness() {
+            return self._get_or_create_target_for_witness(witness);
+        }
+        let g_constant = self._field_element_to_goldilocks_field(&expression.q_c, || {
+            format!("The constant term of {} ({})", description, expression)
+        });
+        let mut current_acc_target = self.builder.constant(g_constant);
+        for (coefficient, witness) in &expression.linear_combinations {
+            let g_coefficient = self._field_element_to_goldilocks_field(coefficient, || {
+                format!("The coefficient of {} in {} ({})", witness.0, description, expression)
+            });
+            let target = self._get_or_create_target_for_witness(*witness);
+            current_acc_target = self
+                .builder
+                .mul_const_add(g_coefficient, target, current_acc_target);
+        }
+        for (coefficient, witness_1, witness_2) in &expression.mul_terms {
+            let g_coefficient = self._field_element_to_goldilocks_field(coefficient, || {
+                format!(
+                    "The coefficient of {}*{} in {} ({})",
+                    witness_1.0, witness_2.0, description, expression
+                )
+            });
+            let target_1 = self._get_or_create_target_for_witness(*witness_1);
+            let target_2 = self._get_or_create_target_for_witness(*witness_2);
+            let cuadratic_target = self.translation_cache.mul(self.builder, target_1, target_2);
+            current_acc_target =
+                self.builder
+                    .mul_const_add(g_coefficient, cuadratic_target, current_acc_target);
+        }
+        current_acc_target
+    }
 
-        let value = &op.value.linear_combinations[0].1;
-        self._get_or_create_target_for_witness(*value);
+    fn _field_element_to_goldilocks_field(
+        &self,
+        fe: &FieldElement,
+        description: impl Fn() -> String,
+    ) -> F {
+        self.field_conversion_mode
+            .field_element_to_goldilocks_field(fe, description)
     }
 
     fn _get_or_create_target_for_witness(&mut self, witness: Witness) -> Target {
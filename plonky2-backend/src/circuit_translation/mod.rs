@@ -1,15 +1,16 @@
 use super::*;
+use acir::circuit::brillig::{BrilligBytecode, BrilligInputs, BrilligOutputs};
+use acir::circuit::directives::Directive;
 use acir::circuit::opcodes;
 use acir::circuit::opcodes::MemOp as GenericMemOp;
-use acir::circuit::opcodes::{BlockId, FunctionInput};
+use acir::circuit::opcodes::{BlockId, BlockType, FunctionInput};
 use acir::circuit::Circuit as GenericCircuit;
 use acir::circuit::Opcode as GenericOpcode;
 use acir::circuit::Program as GenericProgram;
 use acir::native_types::Expression as GenericExpression;
 pub use acir::native_types::Witness;
 use acir::native_types::WitnessStack as GenericWitnessStack;
-use num_bigint::BigUint;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // Generics
 pub use acir_field::AcirField;
@@ -20,17 +21,31 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::CircuitConfig;
 use plonky2::plonk::circuit_data::CircuitData;
 
+mod byte_lookups;
+mod memory_checking;
 mod memory_translator;
 mod sha256_translator;
+mod sha256_u32_translator;
+pub mod translation_cache;
+mod witness_generators;
 
 use crate::binary_digits_target::BinaryDigitsTarget;
+use acir_expression_gate::AcirExpressionGate;
+use byte_lookups::{ByteLookupTable, BITWISE_LOOKUP_THRESHOLD};
+use memory_checking::{MemoryTrace, MEMORY_CHECKING_THRESHOLD};
 use memory_translator::MemoryOperationsTranslator;
+pub use sha256_translator::Sha256Implementation;
 use sha256_translator::Sha256CompressionTranslator;
+use sha256_u32_translator::Sha256U32CompressionTranslator;
+use translation_cache::TranslationCache;
+use witness_generators::{BrilligCallGenerator, TargetExpression, ToLeRadixGenerator};
 use crate::circuit_translation::ecdsa_secp256k1_translator::EcdsaSecp256k1Translator;
+use crate::field_conversion::FieldConversionMode;
 
 #[cfg(test)]
 mod tests;
 
+pub mod acir_expression_gate;
 pub mod assert_zero_translator;
 mod ecdsa_secp256k1_translator;
 
@@ -46,34 +61,223 @@ pub type Expression = GenericExpression<FieldElement>;
 pub type MemOp = GenericMemOp<FieldElement>;
 pub type WitnessStack = GenericWitnessStack<FieldElement>;
 
+/// Human readable name of the kind of an opcode, used for reporting the cost of each kind of
+/// opcode in the resulting Plonky2 circuit. Blackbox functions are distinguished by name.
+pub fn opcode_kind_name(opcode: &Opcode) -> String {
+    match opcode {
+        Opcode::AssertZero(_) => String::from("AssertZero"),
+        Opcode::BlackBoxFuncCall(func_call) => format!("BlackBoxFuncCall::{}", func_call.name()),
+        Opcode::Directive(_) => String::from("Directive"),
+        Opcode::MemoryOp { .. } => String::from("MemoryOp"),
+        Opcode::MemoryInit { .. } => String::from("MemoryInit"),
+        Opcode::BrilligCall { .. } => String::from("BrilligCall"),
+        Opcode::Call { .. } => String::from("Call"),
+    }
+}
+
+/// Witnesses the opcode reads or writes, in no particular order. Brillig calls and directives are
+/// included even though they don't constrain their witnesses.
+pub fn opcode_witnesses(opcode: &Opcode) -> Vec<Witness> {
+    match opcode {
+        Opcode::AssertZero(expression) => expression_witnesses(expression),
+        Opcode::BlackBoxFuncCall(func_call) => {
+            let mut witnesses: Vec<Witness> =
+                func_call.get_inputs_vec().iter().map(|input| input.witness).collect();
+            witnesses.extend(func_call.get_outputs_vec());
+            witnesses
+        }
+        Opcode::Directive(Directive::ToLeRadix { a, b, .. }) => {
+            [expression_witnesses(a), b.clone()].concat()
+        }
+        Opcode::MemoryOp { op, .. } => {
+            [expression_witnesses(&op.index), expression_witnesses(&op.value)].concat()
+        }
+        Opcode::MemoryInit { init, .. } => init.clone(),
+        Opcode::BrilligCall { inputs, outputs, .. } => {
+            let mut witnesses = Vec::new();
+            for input in inputs {
+                match input {
+                    BrilligInputs::Single(expression) => {
+                        witnesses.extend(expression_witnesses(expression))
+                    }
+                    BrilligInputs::Array(expressions) => {
+                        witnesses.extend(expressions.iter().flat_map(expression_witnesses))
+                    }
+                    BrilligInputs::MemoryArray(_) => {}
+                }
+            }
+            for output in outputs {
+                match output {
+                    BrilligOutputs::Simple(witness) => witnesses.push(*witness),
+                    BrilligOutputs::Array(output_witnesses) => {
+                        witnesses.extend(output_witnesses.iter().cloned())
+                    }
+                }
+            }
+            witnesses
+        }
+        Opcode::Call { inputs, outputs, .. } => [inputs.clone(), outputs.clone()].concat(),
+    }
+}
+
+/// Witnesses of the expression sorted by index, without repetitions.
+pub fn expression_witnesses(expression: &Expression) -> Vec<Witness> {
+    let mut witnesses: Vec<Witness> = expression
+        .linear_combinations
+        .iter()
+        .map(|(_, witness)| *witness)
+        .collect();
+    for (_, witness_1, witness_2) in &expression.mul_terms {
+        witnesses.push(*witness_1);
+        witnesses.push(*witness_2);
+    }
+    witnesses.sort();
+    witnesses.dedup();
+    witnesses
+}
+
+/// Witnesses whose values are the public inputs of the Plonky2 proof, in the same order.
+pub fn public_input_witnesses(circuit: &Circuit) -> Vec<Witness> {
+    let mut witnesses: Vec<Witness> = circuit
+        .public_parameters
+        .0
+        .iter()
+        .chain(circuit.return_values.0.iter())
+        .cloned()
+        .collect();
+    let mut seen_witnesses: HashSet<Witness> = witnesses.iter().cloned().collect();
+    for databus_witness in databus_witnesses(circuit) {
+        if seen_witnesses.insert(databus_witness) {
+            witnesses.push(databus_witness);
+        }
+    }
+    witnesses
+}
+
+/// Witnesses initializing the CallData and ReturnData memory blocks (the databus), in the order
+/// of their MemoryInit opcodes.
+pub fn databus_witnesses(circuit: &Circuit) -> Vec<Witness> {
+    circuit
+        .opcodes
+        .iter()
+        .filter_map(|opcode| match opcode {
+            Opcode::MemoryInit {
+                block_id: _,
+                init,
+                block_type: BlockType::CallData | BlockType::ReturnData,
+            } => Some(init.clone()),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
 /// This is the most important part of the backend. The CircuitBuilderFromAcirToPlonky2 translates
 /// the ACIR Circuit into an equivalent Plonky2 circuit. Besides the Plonky2 circuit, the output
 /// contains a mapping from ACIR Witnesses to Plonky2 Targets, which is not only for internal use
 /// but for assigning values to the targets when generating the proof.
 ///
+/// The public inputs of the resulting Plonky2 circuit are the public parameters of the ACIR
+/// circuit followed by its return values, each group sorted by witness index, and then the
+/// witnesses of the databus blocks that aren't public yet (see public_input_witnesses).
+///
 /// The opcodes suported are: AssertZero, MemoryInit, MemoryOp, BrilligCall, Directive(ToLeRadix),
 /// and the BlackboxFunctions: Range, And, Xor, SHA256Compression.
 ///
 /// Internally it uses a Plonky2 CircuitBuilder for generating the circuit, a mapping of memory
 /// blocks for the memory operations and the witness to targets mapping to retain the information
-/// about which target is which.
+/// about which target is which. The AssertZero opcodes are checked with the AcirExpressionGate,
+/// whose number of terms per operation is set by acir_expression_gate; by default it has as many
+/// operations per row as fit in the routed wires of the configuration.
+///
+/// The translation_cache keeps the products and bit decompositions computed so far, so the opcodes
+/// that need them again reuse their targets instead of adding more gates.
+///
+/// The Sha256Compression opcodes are translated with the implementation chosen by
+/// sha256_implementation. The AND and XOR opcodes use byte lookup tables when the circuit looks
+/// up at least bitwise_lookup_threshold bytes in them, and bit decompositions otherwise.
+///
+/// Besides the constraints, the translation registers Plonky2 generators for the witnesses the
+/// ACVM would solve (see the witness_generators module), following the same order: a witness is
+/// solved once an opcode computes it, starting from the parameters of the circuit. The Brillig
+/// calls can only be run if the Brillig functions of the program are known (see
+/// translate_program), otherwise their outputs must be provided when proving.
 
 pub struct CircuitBuilderFromAcirToPlonky2 {
     pub builder: CB,
     pub witness_target_map: HashMap<Witness, Target>,
     pub memory_blocks: HashMap<BlockId, (Vec<Target>, usize)>,
+    pub memory_traces: HashMap<BlockId, MemoryTrace>,
+    pub memory_checking_threshold: usize,
+    pub opcode_gate_counts: Vec<usize>,
+    pub field_conversion_mode: FieldConversionMode,
+    pub solved_witnesses: HashSet<Witness>,
+    pub brillig_functions: Vec<BrilligBytecode<FieldElement>>,
+    pub acir_expression_gate: AcirExpressionGate,
+    pub translation_cache: TranslationCache,
+    pub sha256_implementation: Sha256Implementation,
+    pub bitwise_lookup_threshold: usize,
+    pub(crate) bitwise_lookup_tables: HashSet<ByteLookupTable>,
+    pub(crate) blocks_written_at_non_constant_indices: HashSet<BlockId>,
 }
 
 impl CircuitBuilderFromAcirToPlonky2 {
     pub fn new() -> Self {
-        let config = CircuitConfig::wide_ecc_config();
+        Self::new_with_config(Self::circuit_config(false))
+    }
+
+    pub fn new_with_config(config: CircuitConfig) -> Self {
+        let acir_expression_gate = AcirExpressionGate::new_from_config(&config);
         let builder = CB::new(config);
         let witness_target_map: HashMap<Witness, Target> = HashMap::new();
         let memory_blocks: HashMap<BlockId, (Vec<Target>, usize)> = HashMap::new();
+        let opcode_gate_counts: Vec<usize> = Vec::new();
         Self {
             builder,
             witness_target_map,
             memory_blocks,
+            memory_traces: HashMap::new(),
+            memory_checking_threshold: MEMORY_CHECKING_THRESHOLD,
+            opcode_gate_counts,
+            field_conversion_mode: FieldConversionMode::default(),
+            solved_witnesses: HashSet::new(),
+            brillig_functions: Vec::new(),
+            acir_expression_gate,
+            translation_cache: TranslationCache::new(),
+            sha256_implementation: Sha256Implementation::default(),
+            bitwise_lookup_threshold: BITWISE_LOOKUP_THRESHOLD,
+            bitwise_lookup_tables: HashSet::new(),
+            blocks_written_at_non_constant_indices: HashSet::new(),
+        }
+    }
+
+    pub fn new_with_field_conversion_mode(field_conversion_mode: FieldConversionMode) -> Self {
+        Self {
+            field_conversion_mode,
+            ..Self::new()
+        }
+    }
+
+    /// The translator used by the prove and write_vk commands, which must agree on the options
+    /// since they change the resulting circuit.
+    pub fn new_with_options(
+        field_conversion_mode: FieldConversionMode,
+        zero_knowledge: bool,
+    ) -> Self {
+        Self {
+            field_conversion_mode,
+            ..Self::new_with_config(Self::circuit_config(zero_knowledge))
+        }
+    }
+
+    /// The wide ECC configuration (the ECDSA gadgets need its wires), with zero knowledge on
+    /// demand. Zero knowledge blinds the witness polynomials, so the proof reveals nothing about
+    /// the private inputs, at the cost of a bigger and slower proof. It's part of the common
+    /// circuit data, so the verification key records whether it was used.
+    pub fn circuit_config(zero_knowledge: bool) -> CircuitConfig {
+        CircuitConfig {
+            zero_knowledge,
+            ..CircuitConfig::wide_ecc_config()
         }
     }
 
@@ -81,114 +285,316 @@ impl CircuitBuilderFromAcirToPlonky2 {
         (self.builder.build::<C>(), self.witness_target_map)
     }
 
+    /// Translates the main function of the program, running its Brillig functions when
+    /// generating the witnesses.
+    pub fn translate_program(self: &mut Self, program: &Program) {
+        self.brillig_functions = program.unconstrained_functions.clone();
+        self.translate_circuit(&program.functions[0]);
+    }
+
     /// Main function of the module. It sequentially parses the ACIR opcodes, applying changes
-    /// in the CircuitBuilder accordingly.
+    /// in the CircuitBuilder accordingly. The amount of gates added by each opcode is recorded in
+    /// opcode_gate_counts (in the same order as the opcodes) so the cost of the circuit can be
+    /// attributed back to the ACIR program. The range checks are enforced once all the opcodes are
+    /// translated, and their gates are added to the count of the opcode that requested them. The
+    /// memory checking arguments aren't attributed to any opcode, and neither are the rows laid
+    /// out when the circuit is built (lookups, lookup tables, constants and public inputs).
     pub fn translate_circuit(self: &mut Self, circuit: &Circuit) {
         self._register_witnesses_from_acir_circuit(circuit);
+        self.bitwise_lookup_tables = self._bitwise_lookup_tables_worth_adding(circuit);
+        self.blocks_written_at_non_constant_indices =
+            Self::_blocks_written_at_non_constant_indices(circuit);
         for opcode in &circuit.opcodes {
-            match opcode {
-                Opcode::AssertZero(expr) => {
-                    let mut translator = assert_zero_translator::AssertZeroTranslator::new_for(
-                        &mut self.builder,
-                        &mut self.witness_target_map,
-                        &expr,
-                    );
-                    translator.translate();
+            self.translation_cache
+                .start_opcode(self.opcode_gate_counts.len());
+            let gates_before_opcode = self.builder.num_gates();
+            self._translate_opcode(opcode);
+            self.opcode_gate_counts.push(self.builder.num_gates() - gates_before_opcode);
+        }
+        self._add_memory_checking_constraints();
+        self.translation_cache
+            .add_range_check_constraints(&mut self.builder, &mut self.opcode_gate_counts);
+    }
+
+    /// The tables of the AND and XOR operations that look up enough bytes in them to pay for
+    /// their rows (see BITWISE_LOOKUP_THRESHOLD).
+    fn _bitwise_lookup_tables_worth_adding(&self, circuit: &Circuit) -> HashSet<ByteLookupTable> {
+        let mut looked_up_bytes: HashMap<ByteLookupTable, usize> = HashMap::new();
+        for opcode in &circuit.opcodes {
+            let (table, lhs) = match opcode {
+                Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::AND { lhs, .. }) => {
+                    (ByteLookupTable::And, lhs)
                 }
-                Opcode::BrilligCall {
-                    id: _,
-                    inputs: _,
-                    outputs: _,
-                    predicate: _,
-                } => {} // The brillig call is ignored since it has no impact in the circuit
-                Opcode::Directive(_directive) => {} // The same happens with the Directive
-                Opcode::MemoryInit {
-                    block_id,
-                    init,
-                    block_type: _,
-                } => {
-                    MemoryOperationsTranslator::new_for(
-                        &mut self.builder,
-                        &mut self.witness_target_map,
-                        &mut self.memory_blocks,
-                    )
-                    .translate_memory_init(init, block_id);
+                Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::XOR { lhs, .. }) => {
+                    (ByteLookupTable::Xor, lhs)
                 }
-                Opcode::MemoryOp {
-                    block_id,
-                    op,
-                    predicate: _,
-                } => {
-                    MemoryOperationsTranslator::new_for(
-                        &mut self.builder,
-                        &mut self.witness_target_map,
-                        &mut self.memory_blocks,
-                    )
-                    .translate_memory_op(block_id, op);
+                _ => continue,
+            };
+            *looked_up_bytes.entry(table).or_default() += (lhs.num_bits as usize).div_ceil(8);
+        }
+        looked_up_bytes
+            .into_iter()
+            .filter(|(_, bytes)| *bytes >= self.bitwise_lookup_threshold)
+            .map(|(table, _)| table)
+            .collect()
+    }
+
+    /// The blocks that may need the memory checking argument: writing at a constant index only
+    /// replaces a target, so it doesn't make a block expensive to keep as a vector of targets.
+    fn _blocks_written_at_non_constant_indices(circuit: &Circuit) -> HashSet<BlockId> {
+        circuit
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::MemoryOp { block_id, op, .. }
+                    if op.operation.to_const() != Some(FieldElement::zero())
+                        && op.index.to_const().is_none() =>
+                {
+                    Some(*block_id)
                 }
-                Opcode::BlackBoxFuncCall(func_call) => {
-                    match func_call {
-                        opcodes::BlackBoxFuncCall::RANGE { input } => {
-                            let long_max_bits = input.num_bits.clone() as usize;
-                            assert!(long_max_bits <= 33,
-                                    "Range checks with more than 33 bits are not allowed yet while using Plonky2 prover");
-                            let witness = input.witness;
-                            let target = self._get_or_create_target_for_witness(witness);
-                            self.builder.range_check(target, long_max_bits)
-                        }
-                        opcodes::BlackBoxFuncCall::AND { lhs, rhs, output } => {
-                            self._extend_circuit_with_bitwise_operation(
-                                lhs,
-                                rhs,
-                                output,
-                                BinaryDigitsTarget::and,
-                            );
-                        }
-                        opcodes::BlackBoxFuncCall::XOR { lhs, rhs, output } => {
-                            self._extend_circuit_with_bitwise_operation(
-                                lhs,
-                                rhs,
-                                output,
-                                BinaryDigitsTarget::xor,
-                            );
-                        }
-                        opcodes::BlackBoxFuncCall::Sha256Compression {
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The memory checking argument of a block needs all its accesses, so it's added once every
+    /// opcode has been translated. Blocks are processed in order to keep the circuit deterministic.
+    fn _add_memory_checking_constraints(self: &mut Self) {
+        let mut memory_checked_blocks: Vec<&BlockId> = self.memory_traces.keys().collect();
+        memory_checked_blocks.sort_by_key(|block_id| block_id.0);
+        for block_id in memory_checked_blocks {
+            self.memory_traces[block_id].add_consistency_constraints(&mut self.builder);
+        }
+    }
+
+    fn _translate_opcode(self: &mut Self, opcode: &Opcode) {
+        match opcode {
+            Opcode::AssertZero(expr) => {
+                let mut translator = assert_zero_translator::AssertZeroTranslator::new_for(
+                    &mut self.builder,
+                    &mut self.witness_target_map,
+                    &expr,
+                    self.field_conversion_mode,
+                    &mut self.solved_witnesses,
+                    self.acir_expression_gate,
+                    &mut self.translation_cache,
+                );
+                translator.translate();
+            }
+            Opcode::BrilligCall {
+                id,
+                inputs,
+                outputs,
+                predicate,
+            } => {
+                // The brillig call has no impact in the circuit, it only computes witnesses
+                self._add_generator_for_brillig_call(*id, inputs, outputs, predicate);
+            }
+            Opcode::Directive(Directive::ToLeRadix { a, b, radix }) => {
+                // The same happens with the Directive
+                self._add_generator_for_to_le_radix_directive(a, b, *radix);
+            }
+            Opcode::MemoryInit {
+                block_id,
+                init,
+                block_type: _, // Databus blocks were made public when registering the witnesses
+            } => {
+                MemoryOperationsTranslator::new_for(
+                    &mut self.builder,
+                    &mut self.witness_target_map,
+                    &mut self.memory_blocks,
+                    &mut self.memory_traces,
+                    self.memory_checking_threshold,
+                    self.field_conversion_mode,
+                    &mut self.solved_witnesses,
+                    &mut self.translation_cache,
+                )
+                .translate_memory_init(
+                    init,
+                    block_id,
+                    self.blocks_written_at_non_constant_indices.contains(block_id),
+                );
+            }
+            Opcode::MemoryOp {
+                block_id,
+                op,
+                predicate: _,
+            } => {
+                MemoryOperationsTranslator::new_for(
+                    &mut self.builder,
+                    &mut self.witness_target_map,
+                    &mut self.memory_blocks,
+                    &mut self.memory_traces,
+                    self.memory_checking_threshold,
+                    self.field_conversion_mode,
+                    &mut self.solved_witnesses,
+                    &mut self.translation_cache,
+                )
+                .translate_memory_op(block_id, op);
+            }
+            Opcode::BlackBoxFuncCall(func_call) => {
+                match func_call {
+                    opcodes::BlackBoxFuncCall::RANGE { input } => {
+                        let long_max_bits = input.num_bits.clone() as usize;
+                        assert!(long_max_bits <= 33,
+                                "Range checks with more than 33 bits are not allowed yet while using Plonky2 prover");
+                        let witness = input.witness;
+                        let target = self._get_or_create_target_for_witness(witness);
+                        self.translation_cache.range_check(target, long_max_bits)
+                    }
+                    opcodes::BlackBoxFuncCall::AND { lhs, rhs, output } => {
+                        self._extend_circuit_with_bitwise_operation(
+                            lhs,
+                            rhs,
+                            output,
+                            ByteLookupTable::And,
+                            BinaryDigitsTarget::and,
+                        );
+                    }
+                    opcodes::BlackBoxFuncCall::XOR { lhs, rhs, output } => {
+                        self._extend_circuit_with_bitwise_operation(
+                            lhs,
+                            rhs,
+                            output,
+                            ByteLookupTable::Xor,
+                            BinaryDigitsTarget::xor,
+                        );
+                    }
+                    opcodes::BlackBoxFuncCall::Sha256Compression {
+                        inputs,
+                        hash_values,
+                        outputs,
+                    } => {
+                        self._extend_circuit_with_sha256_compression_operation(
                             inputs,
                             hash_values,
                             outputs,
-                        } => {
-                            self._extend_circuit_with_sha256_compression_operation(
-                                inputs,
-                                hash_values,
-                                outputs,
-                            );
-                        }
-                        opcodes::BlackBoxFuncCall::EcdsaSecp256k1 {
+                        );
+                    }
+                    opcodes::BlackBoxFuncCall::EcdsaSecp256k1 {
+                        public_key_x,
+                        public_key_y,
+                        signature,
+                        hashed_message,
+                        output,
+                    } => {
+                        self._extend_circuit_with_ecdsa_secp256k1_operation(
                             public_key_x,
                             public_key_y,
                             signature,
                             hashed_message,
-                            output,
-                        } => {
-                            self._extend_circuit_with_ecdsa_secp256k1_operation(
-                                public_key_x,
-                                public_key_y,
-                                signature,
-                                hashed_message,
-                                *output
-                            );
-                        }
-                        blackbox_func => {
-                            panic!("Blackbox func not supported yet: {:?}", blackbox_func);
+                            *output
+                        );
+                    }
+                    blackbox_func => {
+                        panic!("Blackbox func not supported yet: {:?}", blackbox_func);
+                    }
+                };
+            }
+
+            opcode => {
+                panic!("Opcode not supported yet: {:?}", opcode);
+            }
+        }
+    }
+
+    /// The generator runs the Brillig function on the values of the inputs, so it's only added
+    /// when the function is known. Memory blocks that use memory checking have no target per
+    /// position, so their values at the time of the call are generated by replaying the accesses
+    /// recorded so far.
+    fn _add_generator_for_brillig_call(
+        self: &mut Self,
+        id: u32,
+        inputs: &Vec<BrilligInputs<FieldElement>>,
+        outputs: &Vec<BrilligOutputs>,
+        predicate: &Option<Expression>,
+    ) {
+        let Some(brillig_function) = self.brillig_functions.get(id as usize) else {
+            return;
+        };
+        let bytecode = brillig_function.bytecode.clone();
+        let mut calldata: Vec<TargetExpression> = Vec::new();
+        for input in inputs {
+            match input {
+                BrilligInputs::Single(expression) => {
+                    calldata.push(self._target_expression_for(expression));
+                }
+                BrilligInputs::Array(expressions) => {
+                    for expression in expressions {
+                        calldata.push(self._target_expression_for(expression));
+                    }
+                }
+                BrilligInputs::MemoryArray(block_id) => {
+                    let block_values = match self.memory_traces.get_mut(block_id) {
+                        Some(memory_trace) => memory_trace.add_current_values(&mut self.builder),
+                        None => {
+                            let (block, block_length) =
+                                self.memory_blocks.get(block_id).unwrap_or_else(|| {
+                                    panic!(
+                                        "Brillig call {} reads memory block {} before it is \
+                                         initialized",
+                                        id, block_id.0
+                                    )
+                                });
+                            block[..*block_length].to_vec()
                         }
                     };
-                }
-
-                opcode => {
-                    panic!("Opcode not supported yet: {:?}", opcode);
+                    calldata.extend(block_values.into_iter().map(TargetExpression::from_target));
                 }
             }
         }
+        let predicate = predicate
+            .as_ref()
+            .map(|expression| self._target_expression_for(expression));
+
+        let output_witnesses = witness_generators::brillig_output_witnesses(outputs);
+        let output_targets = output_witnesses
+            .iter()
+            .map(|witness| self._get_or_create_target_for_witness(*witness))
+            .collect();
+        self.builder.add_simple_generator(BrilligCallGenerator {
+            bytecode,
+            calldata,
+            predicate,
+            outputs: output_targets,
+            field_conversion_mode: self.field_conversion_mode,
+        });
+        self.solved_witnesses.extend(output_witnesses);
+    }
+
+    fn _add_generator_for_to_le_radix_directive(
+        self: &mut Self,
+        a: &Expression,
+        b: &Vec<Witness>,
+        radix: u32,
+    ) {
+        let a = self._target_expression_for(a);
+        let digits = b
+            .iter()
+            .map(|witness| self._get_or_create_target_for_witness(*witness))
+            .collect();
+        self.builder.add_simple_generator(ToLeRadixGenerator {
+            a,
+            radix,
+            digits,
+            field_conversion_mode: self.field_conversion_mode,
+        });
+        self.solved_witnesses.extend(b.iter().cloned());
+    }
+
+    fn _target_expression_for(self: &mut Self, expression: &Expression) -> TargetExpression {
+        for witness in expression.linear_combinations.iter().map(|(_, witness)| witness) {
+            self._get_or_create_target_for_witness(*witness);
+        }
+        for (_, witness_1, witness_2) in &expression.mul_terms {
+            self._get_or_create_target_for_witness(*witness_1);
+            self._get_or_create_target_for_witness(*witness_2);
+        }
+        TargetExpression::new_for(
+            expression,
+            &self.witness_target_map,
+            self.field_conversion_mode,
+        )
     }
 
     fn _extend_circuit_with_sha256_compression_operation(
@@ -197,9 +603,16 @@ impl CircuitBuilderFromAcirToPlonky2 {
         hash_values: &Box<[FunctionInput; 8]>,
         outputs: &Box<[Witness; 8]>,
     ) {
-        let mut sha256_compression_translator =
-            Sha256CompressionTranslator::new_for(self, inputs, hash_values, outputs);
-        sha256_compression_translator.translate();
+        match self.sha256_implementation {
+            Sha256Implementation::Bitwise => {
+                Sha256CompressionTranslator::new_for(self, inputs, hash_values, outputs)
+                    .translate()
+            }
+            Sha256Implementation::U32Lookups => {
+                Sha256U32CompressionTranslator::new_for(self, inputs, hash_values, outputs)
+                    .translate()
+            }
+        }
     }
 
     fn _extend_circuit_with_ecdsa_secp256k1_operation(
@@ -215,29 +628,76 @@ impl CircuitBuilderFromAcirToPlonky2 {
         ecdsa_secp256k1_translator.translate();
     }
 
+    /// When the table of the operation is worth adding, both operands are split into bytes
+    /// (reusing the bytes of previous operations) and each pair of bytes is looked up in it.
+    /// Otherwise, the operation is applied to the bits of the operands. Either way, the
+    /// decomposition of the output is recorded too, so an operation on the output doesn't need to
+    /// split it again.
     fn _extend_circuit_with_bitwise_operation(
         self: &mut Self,
         lhs: &FunctionInput,
         rhs: &FunctionInput,
         output: &Witness,
+        table: ByteLookupTable,
         operation: fn(BinaryDigitsTarget, BinaryDigitsTarget, &mut CB) -> BinaryDigitsTarget,
     ) {
         assert_eq!(lhs.num_bits, rhs.num_bits);
-        let binary_digits = lhs.num_bits as usize;
-        let lhs_binary_target = self.binary_number_target_for_witness(lhs.witness, binary_digits);
-        let rhs_binary_target = self.binary_number_target_for_witness(rhs.witness, binary_digits);
+        let num_bits = lhs.num_bits as usize;
+        if !self.bitwise_lookup_tables.contains(&table) {
+            let lhs_binary_target = self.binary_number_target_for_witness(lhs.witness, num_bits);
+            let rhs_binary_target = self.binary_number_target_for_witness(rhs.witness, num_bits);
+            let output_binary_target =
+                operation(lhs_binary_target, rhs_binary_target, &mut self.builder);
+            let output_target = self.convert_binary_number_to_number(output_binary_target.clone());
+            self.translation_cache
+                .record_bit_decomposition(output_target, output_binary_target);
+            self.set_target_for_witness(*output, output_target);
+            return;
+        }
+        let lhs_bytes = self.bytes_for_witness(lhs.witness, num_bits);
+        let rhs_bytes = self.bytes_for_witness(rhs.witness, num_bits);
 
-        let output_binary_target =
-            operation(lhs_binary_target, rhs_binary_target, &mut self.builder);
+        let table_index = self.translation_cache.lookup_table(&mut self.builder, table);
+        let byte_base = F::from_canonical_u64(256);
+        let output_bytes: Vec<Target> = lhs_bytes
+            .into_iter()
+            .zip(rhs_bytes)
+            .map(|(lhs_byte, rhs_byte)| {
+                let lookup_input = self.builder.mul_const_add(byte_base, lhs_byte, rhs_byte);
+                self.builder.add_lookup_from_index(lookup_input, table_index)
+            })
+            .collect();
 
-        let output_target = self.convert_binary_number_to_number(output_binary_target);
-        self.witness_target_map.insert(*output, output_target);
+        let output_target = TranslationCache::recompose_bytes(&mut self.builder, &output_bytes);
+        self.translation_cache
+            .record_byte_decomposition(output_target, num_bits, output_bytes);
+        self.set_target_for_witness(*output, output_target);
+    }
+
+    pub fn bytes_for_witness(&mut self, w: Witness, num_bits: usize) -> Vec<Target> {
+        let target = self._get_or_create_target_for_witness(w);
+        self.translation_cache
+            .split_bytes(&mut self.builder, target, num_bits)
     }
 
     pub fn target_for_witness(&mut self, w: Witness) -> Target {
         self._get_or_create_target_for_witness(w)
     }
 
+    /// Used by the opcodes that compute the value of a witness. If the witness already has a
+    /// target (because it's a return value or it was used by a previous opcode) both targets are
+    /// connected, since replacing it would leave the previous target unconstrained. Either way,
+    /// the witness is solved by the generators of the opcode.
+    pub fn set_target_for_witness(&mut self, witness: Witness, target: Target) {
+        self.solved_witnesses.insert(witness);
+        match self.witness_target_map.get(&witness) {
+            Some(previous_target) => self.builder.connect(*previous_target, target),
+            None => {
+                self.witness_target_map.insert(witness, target);
+            }
+        }
+    }
+
     pub fn binary_number_target_for_witness(
         &mut self,
         w: Witness,
@@ -256,26 +716,22 @@ impl CircuitBuilderFromAcirToPlonky2 {
             .map(|bit_position| self._constant_bool_target_for_bit(constant, bit_position))
             .rev()
             .collect();
-        BinaryDigitsTarget { bits: bit_targets }
+        BinaryDigitsTarget::from_bits(bit_targets)
     }
 
+    /// The decomposition is taken from the translation cache when the number was decomposed
+    /// before, or is the result of a bitwise operation.
     fn convert_number_to_binary_number(
         &mut self,
         number_target: Target,
         digits: usize,
     ) -> BinaryDigitsTarget {
-        BinaryDigitsTarget {
-            bits: self
-                .builder
-                .split_le(number_target, digits)
-                .into_iter()
-                .rev()
-                .collect(),
-        }
+        self.translation_cache
+            .split_be(&mut self.builder, number_target, digits)
     }
 
     fn convert_binary_number_to_number(&mut self, a: BinaryDigitsTarget) -> Target {
-        self.builder.le_sum(a.bits.into_iter().rev())
+        a.to_target(&mut self.builder)
     }
 
     fn _constant_bool_target_for_bit(
@@ -294,9 +750,32 @@ impl CircuitBuilderFromAcirToPlonky2 {
         for public_parameter_witness in public_parameters_as_list {
             self._register_new_public_input_from_witness(public_parameter_witness);
         }
+        // Return values
+        let return_values_as_list: Vec<Witness> =
+            circuit.return_values.0.iter().cloned().collect();
+        for return_value_witness in return_values_as_list {
+            self._register_new_public_output_from_witness(return_value_witness);
+        }
+        // Databus: the content of CallData blocks is public like the public parameters, and the
+        // content of ReturnData blocks is public like the return values
+        let mut public_witnesses: HashSet<Witness> = circuit
+            .public_parameters
+            .0
+            .union(&circuit.return_values.0)
+            .cloned()
+            .collect();
+        for databus_witness in databus_witnesses(circuit) {
+            if public_witnesses.insert(databus_witness) {
+                self._register_new_public_output_from_witness(databus_witness);
+            }
+        }
         // Private parameters
         let private_parameters_as_list: Vec<Witness> =
             circuit.private_parameters.iter().cloned().collect();
+        // The parameters are the inputs of the program, every other witness is solved by the
+        // opcodes
+        self.solved_witnesses.extend(circuit.public_parameters.0.iter().cloned());
+        self.solved_witnesses.extend(private_parameters_as_list.iter().cloned());
         for private_parameter_witness in private_parameters_as_list {
             self._register_new_private_input_from_witness(private_parameter_witness);
         }
@@ -309,6 +788,15 @@ impl CircuitBuilderFromAcirToPlonky2 {
             .insert(public_input_witness, public_input_target);
     }
 
+    /// The return values (and databus witnesses) are registered before translating the opcodes,
+    /// so the opcodes that compute them must connect their results to these targets (see
+    /// set_target_for_witness). A return value that is also a public parameter shows up twice in
+    /// the public inputs.
+    fn _register_new_public_output_from_witness(self: &mut Self, return_value_witness: Witness) {
+        let return_value_target = self._get_or_create_target_for_witness(return_value_witness);
+        self.builder.register_public_input(return_value_target);
+    }
+
     fn _register_new_private_input_from_witness(self: &mut Self, private_input_witness: Witness) {
         self._get_or_create_target_for_witness(private_input_witness);
     }
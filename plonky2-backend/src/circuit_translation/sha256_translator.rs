@@ -1,5 +1,42 @@
 use super::*;
 
+/// The round constants of SHA256.
+pub(super) const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+    0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+    0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+    0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+    0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+    0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+    0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+    0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+    0xc67178f2,
+];
+
+/// How the Sha256Compression opcode is translated. Both implementations constrain the same
+/// function, but the U32Lookups one (the default) needs far fewer gates: the modular additions
+/// are done with U32AddManyGate instead of bit by bit, and Ch, Maj and the Σ/σ functions are
+/// looked up a nibble at a time. The Bitwise implementation works on the bits of the words
+/// alone, without lookup tables, and can be chosen with the sha256_implementation option of the
+/// backend (the --bitwise-sha256 flag of the commands).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sha256Implementation {
+    Bitwise,
+    #[default]
+    U32Lookups,
+}
+
+impl Sha256Implementation {
+    pub fn from_bitwise_flag(bitwise: bool) -> Self {
+        if bitwise {
+            Sha256Implementation::Bitwise
+        } else {
+            Sha256Implementation::U32Lookups
+        }
+    }
+}
+
 /// This struct represents the outputs of each iteration in the 64-loop of the sha256 algorithm.
 #[derive(Clone)]
 struct CompressionIterationState {
@@ -110,16 +147,14 @@ impl<'a> Sha256CompressionTranslator<'a> {
             let new_output_target = self
                 .circuit_builder
                 .convert_binary_number_to_number(output_binary_target.clone());
-            self.circuit_builder
-                .witness_target_map
-                .insert(*output_witness, new_output_target);
+            self.circuit_builder.set_target_for_witness(*output_witness, new_output_target);
         }
     }
 
     fn sigma_0(&mut self, target: &BinaryDigitsTarget) -> BinaryDigitsTarget {
-        let x1 = BinaryDigitsTarget::rotate_right(target, 7, &mut self.circuit_builder.builder);
-        let x2 = BinaryDigitsTarget::rotate_right(target, 18, &mut self.circuit_builder.builder);
-        let x3 = BinaryDigitsTarget::shift_right(target, 3, &mut self.circuit_builder.builder);
+        let x1 = target.rotate_right(7);
+        let x2 = target.rotate_right(18);
+        let x3 = target.shift_right(3);
 
         let y1 = BinaryDigitsTarget::xor(x1, x2, &mut self.circuit_builder.builder);
         let y2 = BinaryDigitsTarget::xor(y1, x3, &mut self.circuit_builder.builder);
@@ -128,9 +163,9 @@ impl<'a> Sha256CompressionTranslator<'a> {
     }
 
     fn sigma_1(&mut self, target: &BinaryDigitsTarget) -> BinaryDigitsTarget {
-        let x1 = BinaryDigitsTarget::rotate_right(target, 17, &mut self.circuit_builder.builder);
-        let x2 = BinaryDigitsTarget::rotate_right(target, 19, &mut self.circuit_builder.builder);
-        let x3 = BinaryDigitsTarget::shift_right(target, 10, &mut self.circuit_builder.builder);
+        let x1 = target.rotate_right(17);
+        let x2 = target.rotate_right(19);
+        let x3 = target.shift_right(10);
 
         let y1 = BinaryDigitsTarget::xor(x1, x2, &mut self.circuit_builder.builder);
         let y2 = BinaryDigitsTarget::xor(y1, x3, &mut self.circuit_builder.builder);
@@ -139,9 +174,9 @@ impl<'a> Sha256CompressionTranslator<'a> {
     }
 
     fn big_sigma_0(&mut self, target: &BinaryDigitsTarget) -> BinaryDigitsTarget {
-        let x1 = BinaryDigitsTarget::rotate_right(target, 2, &mut self.circuit_builder.builder);
-        let x2 = BinaryDigitsTarget::rotate_right(target, 13, &mut self.circuit_builder.builder);
-        let x3 = BinaryDigitsTarget::rotate_right(target, 22, &mut self.circuit_builder.builder);
+        let x1 = target.rotate_right(2);
+        let x2 = target.rotate_right(13);
+        let x3 = target.rotate_right(22);
 
         let y1 = BinaryDigitsTarget::xor(x1, x2, &mut self.circuit_builder.builder);
         let y2 = BinaryDigitsTarget::xor(y1, x3, &mut self.circuit_builder.builder);
@@ -149,9 +184,9 @@ impl<'a> Sha256CompressionTranslator<'a> {
     }
 
     fn big_sigma_1(&mut self, target: &BinaryDigitsTarget) -> BinaryDigitsTarget {
-        let x1 = BinaryDigitsTarget::rotate_right(target, 6, &mut self.circuit_builder.builder);
-        let x2 = BinaryDigitsTarget::rotate_right(target, 11, &mut self.circuit_builder.builder);
-        let x3 = BinaryDigitsTarget::rotate_right(target, 25, &mut self.circuit_builder.builder);
+        let x1 = target.rotate_right(6);
+        let x2 = target.rotate_right(11);
+        let x3 = target.rotate_right(25);
 
         let y1 = BinaryDigitsTarget::xor(x1, x2, &mut self.circuit_builder.builder);
         let y2 = BinaryDigitsTarget::xor(y1, x3, &mut self.circuit_builder.builder);
@@ -171,19 +206,7 @@ impl<'a> Sha256CompressionTranslator<'a> {
     }
 
     fn initial_k(&mut self) -> Vec<BinaryDigitsTarget> {
-        let sha256_k_constants: Vec<u32> = vec![
-            0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
-            0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
-            0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
-            0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
-            0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
-            0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
-            0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
-            0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
-            0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
-            0xc67178f2,
-        ];
-        let binary_constants = sha256_k_constants
+        let binary_constants = SHA256_K
             .iter()
             .map(|n| {
                 self.circuit_builder
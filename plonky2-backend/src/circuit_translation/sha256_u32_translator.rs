@@ -0,0 +1,190 @@
+use super::*;
+use crate::plonky2_ecdsa::biguint::gadgets::arithmetic_u32::{CircuitBuilderU32, U32Target};
+use sha256_translator::SHA256_K;
+
+/// This module translates the sha256 compression function like Sha256CompressionTranslator, but
+/// working with 32 bit words packed in a single target:
+///
+/// - Every modular addition of a round is a single U32AddManyGate (add_many_u32), which also
+///   range checks its result, instead of a chain of bitwise additions.
+/// - Ch, Maj and the Σ/σ functions are computed a nibble at a time with the nibble tables of
+///   ByteLookupTable. Their results are only added, so they're kept packed too.
+/// - The bits of a word are only decomposed when a lookup needs them, and the rotations and
+///   shifts are views over those bits, so they're free.
+pub struct Sha256U32CompressionTranslator<'a> {
+    circuit_builder: &'a mut CircuitBuilderFromAcirToPlonky2,
+    inputs: &'a Box<[FunctionInput; 16]>,
+    hash_values: &'a Box<[FunctionInput; 8]>,
+    outputs: &'a Box<[Witness; 8]>,
+}
+
+impl<'a> Sha256U32CompressionTranslator<'a> {
+    pub fn new_for(
+        circuit_builder: &'a mut CircuitBuilderFromAcirToPlonky2,
+        inputs: &'a Box<[FunctionInput; 16]>,
+        hash_values: &'a Box<[FunctionInput; 8]>,
+        outputs: &'a Box<[Witness; 8]>,
+    ) -> Sha256U32CompressionTranslator<'a> {
+        Self {
+            circuit_builder,
+            inputs,
+            hash_values,
+            outputs,
+        }
+    }
+
+    pub fn translate(&mut self) {
+        // The inputs are decomposed right away, which range checks them
+        let mut w: Vec<BinaryDigitsTarget> = self
+            .inputs
+            .iter()
+            .map(|input| {
+                self.circuit_builder
+                    .binary_number_target_for_witness(input.witness, 32)
+            })
+            .collect();
+        let initial_h: Vec<BinaryDigitsTarget> = self
+            .hash_values
+            .iter()
+            .map(|input| {
+                self.circuit_builder
+                    .binary_number_target_for_witness(input.witness, 32)
+            })
+            .collect();
+
+        for t in 16..64 {
+            let sigma_1 = self.sigma_1(&w[t - 2]);
+            let sigma_0 = self.sigma_0(&w[t - 15]);
+            let w_t_7 = self.packed(&w[t - 7]);
+            let w_t_16 = self.packed(&w[t - 16]);
+            let w_t = self.add_words(&[sigma_1, w_t_7, sigma_0, w_t_16]);
+            w.push(w_t);
+        }
+
+        let mut state = initial_h.clone();
+        for t in 0..64 {
+            state = self.compression_function_iteration(&state, &w[t], SHA256_K[t]);
+        }
+
+        for i in 0..8 {
+            let initial_h_i = self.packed(&initial_h[i]);
+            let state_i = self.packed(&state[i]);
+            let final_h_i = self.add_words(&[initial_h_i, state_i]);
+            let output_target = self.packed(&final_h_i).0;
+            self.circuit_builder
+                .set_target_for_witness(self.outputs[i], output_target);
+        }
+    }
+
+    /// The state is [a, b, c, d, e, f, g, h].
+    fn compression_function_iteration(
+        &mut self,
+        state: &[BinaryDigitsTarget],
+        w_t: &BinaryDigitsTarget,
+        k_t: u32,
+    ) -> Vec<BinaryDigitsTarget> {
+        let [a, b, c, d, e, f, g, h] = state else {
+            panic!("The sha256 state must have 8 words");
+        };
+        let big_sigma_1 = self.big_sigma_1(e);
+        let choose_e_f_g = self.ternary_lookup(ByteLookupTable::ChooseNibbles, e, f, g);
+        let big_sigma_0 = self.big_sigma_0(a);
+        let majority_a_b_c = self.ternary_lookup(ByteLookupTable::MajorityNibbles, a, b, c);
+        let k_t = self.circuit_builder.builder.constant_u32(k_t);
+        let w_t = self.packed(w_t);
+        let h = self.packed(h);
+        let d = self.packed(d);
+
+        // With t_1 = h + Σ1(e) + Ch(e, f, g) + k_t + w_t and t_2 = Σ0(a) + Maj(a, b, c), the new
+        // words are t_1 + t_2 and d + t_1, each computed with a single addition
+        let new_a = self.add_words(&[
+            h,
+            big_sigma_1,
+            choose_e_f_g,
+            k_t,
+            w_t,
+            big_sigma_0,
+            majority_a_b_c,
+        ]);
+        let new_e = self.add_words(&[d, h, big_sigma_1, choose_e_f_g, k_t, w_t]);
+
+        vec![new_a, a.clone(), b.clone(), c.clone(), new_e, e.clone(), f.clone(), g.clone()]
+    }
+
+    fn sigma_0(&mut self, target: &BinaryDigitsTarget) -> U32Target {
+        let x1 = target.rotate_right(7);
+        let x2 = target.rotate_right(18);
+        let x3 = target.shift_right(3);
+        self.ternary_lookup(ByteLookupTable::Xor3Nibbles, &x1, &x2, &x3)
+    }
+
+    fn sigma_1(&mut self, target: &BinaryDigitsTarget) -> U32Target {
+        let x1 = target.rotate_right(17);
+        let x2 = target.rotate_right(19);
+        let x3 = target.shift_right(10);
+        self.ternary_lookup(ByteLookupTable::Xor3Nibbles, &x1, &x2, &x3)
+    }
+
+    fn big_sigma_0(&mut self, target: &BinaryDigitsTarget) -> U32Target {
+        let x1 = target.rotate_right(2);
+        let x2 = target.rotate_right(13);
+        let x3 = target.rotate_right(22);
+        self.ternary_lookup(ByteLookupTable::Xor3Nibbles, &x1, &x2, &x3)
+    }
+
+    fn big_sigma_1(&mut self, target: &BinaryDigitsTarget) -> U32Target {
+        let x1 = target.rotate_right(6);
+        let x2 = target.rotate_right(11);
+        let x3 = target.rotate_right(25);
+        self.ternary_lookup(ByteLookupTable::Xor3Nibbles, &x1, &x2, &x3)
+    }
+
+    /// Applies the function of a nibble table to three 32 bit words: for each nibble position
+    /// the nibbles of x, y and z are packed into the input x * 256 + y * 16 + z of a lookup, and
+    /// the resulting nibbles are recomposed into a word.
+    fn ternary_lookup(
+        &mut self,
+        table: ByteLookupTable,
+        x: &BinaryDigitsTarget,
+        y: &BinaryDigitsTarget,
+        z: &BinaryDigitsTarget,
+    ) -> U32Target {
+        let builder = &mut self.circuit_builder.builder;
+        let table_index = self
+            .circuit_builder
+            .translation_cache
+            .lookup_table(builder, table);
+        let x_bits = x.bits(builder);
+        let y_bits = y.bits(builder);
+        let z_bits = z.bits(builder);
+
+        let two = F::from_canonical_u64(2);
+        let sixteen = F::from_canonical_u64(16);
+        let mut word = builder.zero();
+        for nibble in 0..8 {
+            let nibble_bits = (4 * nibble)..(4 * nibble + 4);
+            let mut lookup_input = builder.zero();
+            for bit in x_bits[nibble_bits.clone()]
+                .iter()
+                .chain(&y_bits[nibble_bits.clone()])
+                .chain(&z_bits[nibble_bits])
+            {
+                lookup_input = builder.mul_const_add(two, lookup_input, bit.target);
+            }
+            let output_nibble = builder.add_lookup_from_index(lookup_input, table_index);
+            word = builder.mul_const_add(sixteen, word, output_nibble);
+        }
+        U32Target(word)
+    }
+
+    /// The sum modulo 2^32. The result is range checked by the addition, so its bits are left to
+    /// be decomposed only if they're needed.
+    fn add_words(&mut self, addends: &[U32Target]) -> BinaryDigitsTarget {
+        let (sum, _carry) = self.circuit_builder.builder.add_many_u32(addends);
+        BinaryDigitsTarget::from_target(sum.0, 32)
+    }
+
+    fn packed(&mut self, word: &BinaryDigitsTarget) -> U32Target {
+        U32Target(word.to_target(&mut self.circuit_builder.builder))
+    }
+}
@@ -1,7 +1,12 @@
 use super::*;
 use acir::circuit::opcodes::FunctionInput;
 use acir::circuit::{opcodes, ExpressionWidth, PublicInputs};
+use base64::Engine;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::json;
 use std::collections::BTreeSet;
+use std::io::Write;
 
 pub fn x_equals_0_opcode(public_input_witness: Witness) -> Opcode {
     Opcode::AssertZero(Expression {
@@ -195,6 +200,20 @@ pub fn black_box_range_opcode(public_input: Witness, max_bits: u32) -> Opcode {
     Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::RANGE { input })
 }
 
+/// A range check of the given bits for each of the witnesses, which are public inputs.
+pub fn range_checks_circuit(public_inputs: Vec<Witness>, max_bits: u32) -> Circuit {
+    let mut circuit = circuit_with_single_opcode(
+        black_box_range_opcode(public_inputs[0], max_bits),
+        public_inputs.clone(),
+    );
+    for public_input in &public_inputs[1..] {
+        circuit
+            .opcodes
+            .push(black_box_range_opcode(*public_input, max_bits));
+    }
+    circuit
+}
+
 pub fn circuit_with_a_public_input_and_two_assert_zero_operands(
     public_input_witness: Witness,
     intermediate_witness: Witness,
@@ -334,3 +353,69 @@ fn _bitwise_xor_acir_opcode(
         output,
     })
 }
+
+/// fn main(x: pub Field) {
+///     let y = x + 4;
+///     assert(y * y == 25);
+/// }
+/// The public input is Witness(0) and y is Witness(1), so x = 1 and y = 5 satisfy it.
+pub fn x_plus_4_squared_equals_25_program() -> Program {
+    let circuit =
+        circuit_with_a_public_input_and_two_assert_zero_operands(Witness(0), Witness(1));
+    program_with_main(circuit)
+}
+
+pub fn program_with_main(circuit: Circuit) -> Program {
+    Program {
+        functions: vec![circuit],
+        unconstrained_functions: vec![],
+    }
+}
+
+/// The json written by Nargo for the program, without debug symbols.
+pub fn program_json(program: &Program, abi: serde_json::Value) -> serde_json::Value {
+    let bytecode = Program::serialize_program(program);
+    json!({
+        "bytecode": base64::prelude::BASE64_STANDARD.encode(bytecode),
+        "abi": abi,
+    })
+}
+
+/// The ABI of a main function whose only parameter is a public field.
+pub fn public_field_parameter_abi(name: &str) -> serde_json::Value {
+    json!({
+        "parameters": [{"name": name, "type": {"kind": "field"}, "visibility": "public"}],
+        "return_type": null,
+        "error_types": {},
+    })
+}
+
+const MAIN_SOURCE: &str = "fn main(x: pub Field) {\n    let y = x - 4;\n    assert(y * y == 25);\n}\n";
+
+/// The debug symbols and file map of a program json, locating its opcodes 0 and 1 in the
+/// MAIN_SOURCE lines 2 and 3.
+pub fn program_json_with_debug_symbols() -> serde_json::Value {
+    let debug_symbols = json!({
+        "debug_infos": [{
+            "locations": {
+                "0": [{"span": {"start": 36, "end": 41}, "file": 1}],
+                "1": [{"span": {"start": 47, "end": 66}, "file": 1}],
+            },
+            "variables": {},
+            "functions": {},
+            "types": {},
+        }]
+    });
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(debug_symbols.to_string().as_bytes())
+        .unwrap();
+    let compressed_debug_symbols = encoder.finish().unwrap();
+
+    json!({
+        "debug_symbols": base64::prelude::BASE64_STANDARD.encode(compressed_debug_symbols),
+        "file_map": {
+            "1": {"source": MAIN_SOURCE, "path": "src/main.nr"},
+        },
+    })
+}
@@ -5,12 +5,20 @@ fn parse_circuit_and_witnesses(
     circuit_path: &String,
     witness_path: String,
 ) -> (Circuit, WitnessStack) {
+    let (acir_program, witness) = parse_program_and_witnesses(circuit_path, witness_path);
+    let circuit = acir_program.functions[0].clone();
+    (circuit, witness)
+}
+
+fn parse_program_and_witnesses(
+    circuit_path: &String,
+    witness_path: String,
+) -> (Program, WitnessStack) {
     let acir_program: Program =
         noir_and_plonky2_serialization::deserialize_program_within_file_path(circuit_path);
-    let circuit = acir_program.functions[0].clone();
     let witness =
         noir_and_plonky2_serialization::deserialize_witnesses_within_file_path(witness_path);
-    (circuit, witness)
+    (acir_program, witness)
 }
 
 fn _path_for_circuit(nargo_project_name: &str) -> String {
@@ -31,3 +39,10 @@ pub fn precompiled_circuit_and_withesses_with_name(program_name: &str) -> (Circu
     let witness_path = _path_for_witnesses(program_name);
     parse_circuit_and_witnesses(&circuit_path, witness_path)
 }
+
+/// Unlike precompiled_circuit_and_withesses_with_name, it keeps the Brillig functions.
+pub fn precompiled_program_and_witnesses_with_name(program_name: &str) -> (Program, WitnessStack) {
+    let circuit_path = _path_for_circuit(program_name);
+    let witness_path = _path_for_witnesses(program_name);
+    parse_program_and_witnesses(&circuit_path, witness_path)
+}
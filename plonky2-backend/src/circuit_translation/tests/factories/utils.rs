@@ -13,6 +13,14 @@ pub fn generate_plonky2_circuit_from_acir_circuit(
     translator.unpack()
 }
 
+pub fn generate_plonky2_circuit_from_acir_program(
+    program: &Program,
+) -> (CircuitData<F, C, 2>, HashMap<Witness, Target>) {
+    let mut translator = circuit_translation::CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_program(program);
+    translator.unpack()
+}
+
 pub fn generate_plonky2_proof_using_witness_values(
     witness_assignment: Vec<(Witness, F)>,
     witness_target_map: &HashMap<Witness, Target>,
@@ -28,15 +36,17 @@ pub fn generate_plonky2_proof_using_witness_values(
 
 pub fn check_linked_output_targets_property(
     circuit: &Circuit,
+    circuit_data: &CircuitData<F, C, 2>,
     witness_target_map: &HashMap<Witness, Target>,
 ) {
-    // We must make sure that all targets linked to output witness exist and are actual Wires
-    // (instead of VirtualTargets). Otherwise, it means that te circuit is not doing what we
-    // expect and might fall into false positive tests.
+    // We must make sure that all targets linked to output witness exist and are either actual
+    // Wires or public inputs (instead of loose VirtualTargets). Otherwise, it means that te
+    // circuit is not doing what we expect and might fall into false positive tests.
+    let public_input_targets = &circuit_data.prover_only.public_inputs;
     for witness_index in circuit.return_values.indices() {
         match witness_target_map.get(&Witness(witness_index)) {
             Some(target) => match target {
-                Target::VirtualTarget { index: _ } => {
+                Target::VirtualTarget { index: _ } if !public_input_targets.contains(target) => {
                     panic!(
                         "{}",
                         format!(
@@ -45,9 +55,24 @@ pub fn check_linked_output_targets_property(
                         )
                     )
                 }
-                Target::Wire(_wire) => {}
+                _ => {}
             },
             None => panic!("An output witness has not an associated target"),
         }
     }
 }
+
+/// The public inputs of the proof must be the values of the public parameters followed by the
+/// values of the return values.
+pub fn check_public_inputs_match_witness_values(
+    circuit: &Circuit,
+    proof: &ProofWithPublicInputs<GoldilocksField, C, 2>,
+    witness_assignment: &Vec<(Witness, F)>,
+) {
+    let witness_values: HashMap<Witness, F> = witness_assignment.iter().cloned().collect();
+    let expected_public_inputs: Vec<F> = circuit_translation::public_input_witnesses(circuit)
+        .iter()
+        .map(|witness| witness_values[witness])
+        .collect();
+    assert_eq!(expected_public_inputs, proof.public_inputs);
+}
@@ -3,7 +3,19 @@ use super::*;
 pub mod factories;
 
 mod test_assert_zero;
+mod test_audit;
+mod test_backend_api;
 mod test_blackbox;
+mod test_constraint_debugger;
+mod test_ecdsa_secp256k1;
+mod test_execute;
+mod test_field_conversion;
+mod test_info;
+mod test_file_format;
 mod test_memory_operations;
 mod test_precompiled;
+mod test_profiling;
 mod test_sha256_internal;
+mod test_translation_cache;
+mod test_verify_program;
+mod test_zero_knowledge;
@@ -1,6 +1,11 @@
 use super::factories::circuit_factory::*;
-use super::factories::utils;
+use super::factories::{circuit_parser, utils};
 use super::*;
+use crate::circuit_translation::acir_expression_gate::AcirExpressionGate;
+use crate::field_conversion::field_element_to_goldilocks_field;
+use acir::circuit::PublicInputs;
+use parameterized::parameterized;
+use std::collections::BTreeSet;
 
 #[test]
 fn test_plonky2_vm_can_traslate_the_assert_x_equals_zero_program() {
@@ -283,3 +288,249 @@ fn test_current_noir_is_using_goldilocks_field() {
     assert_eq!(1, obtained_one);
     assert_eq!(goldilocks_prime - 1, obtained_minus_one);
 }
+
+#[test]
+fn test_return_values_are_public_inputs_after_the_public_parameters() {
+    // fn main(x: pub Field, y: Field) -> pub Field {
+    //     x * y
+    // }
+
+    // Given
+    let public_input_witness = Witness(0);
+    let private_input_witness = Witness(1);
+    let return_value_witness = Witness(2);
+    let mut circuit = circuit_with_single_opcode(
+        Opcode::AssertZero(Expression {
+            mul_terms: vec![(FieldElement::one(), public_input_witness, private_input_witness)],
+            linear_combinations: vec![(-FieldElement::one(), return_value_witness)],
+            q_c: FieldElement::zero(),
+        }),
+        vec![public_input_witness],
+    );
+    circuit.private_parameters = BTreeSet::from([private_input_witness]);
+    circuit.return_values = PublicInputs(BTreeSet::from([return_value_witness]));
+
+    // When
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    // Then
+    let three = F::from_canonical_u64(3);
+    let five = F::from_canonical_u64(5);
+    let fifteen = F::from_canonical_u64(15);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        vec![
+            (public_input_witness, three),
+            (private_input_witness, five),
+            (return_value_witness, fifteen),
+        ],
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert_eq!(vec![three, fifteen], proof.public_inputs);
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_plonky2_vm_can_translate_an_expression_split_across_several_gates() {
+    // Given
+    let public_inputs: Vec<Witness> = (0..6).map(Witness).collect();
+    let circuit = circuit_with_single_opcode(
+        _long_expression_opcode(&public_inputs),
+        public_inputs.clone(),
+    );
+
+    // When
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    // Then
+    let one = F::from_canonical_u64(1);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        public_inputs.iter().map(|witness| (*witness, one)).collect(),
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert_eq!(vec![one; 6], proof.public_inputs);
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+#[should_panic]
+fn test_an_expression_split_across_several_gates_fails_for_values_that_do_not_satisfy_it() {
+    // Given
+    let public_inputs: Vec<Witness> = (0..6).map(Witness).collect();
+    let circuit = circuit_with_single_opcode(
+        _long_expression_opcode(&public_inputs),
+        public_inputs.clone(),
+    );
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    // When
+    let two = F::from_canonical_u64(2);
+    utils::generate_plonky2_proof_using_witness_values(
+        public_inputs.iter().map(|witness| (*witness, two)).collect(),
+        &witness_target_map,
+        &circuit_data,
+    );
+}
+
+#[test]
+fn test_each_gate_of_an_expression_evaluates_several_of_its_terms() {
+    // Given
+    let public_inputs: Vec<Witness> = (0..6).map(Witness).collect();
+    let circuit = circuit_with_single_opcode(
+        _long_expression_opcode(&public_inputs),
+        public_inputs.clone(),
+    );
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+    let mut single_op_translator = CircuitBuilderFromAcirToPlonky2::new();
+    single_op_translator.acir_expression_gate = AcirExpressionGate::new(4, 1, 1);
+    single_op_translator.translate_circuit(&circuit);
+
+    // Then
+    // 6 linear terms fit in 2 operations of 4, but each of the 3 products needs its own
+    // operation, and the 3 operations share a row.
+    assert!(translator.acir_expression_gate.num_ops >= 3);
+    assert_eq!(1, translator.opcode_gate_counts[0]);
+    assert_eq!(3, single_op_translator.opcode_gate_counts[0]);
+}
+
+#[test]
+fn test_the_operations_of_several_expressions_share_rows() {
+    // Given
+    let public_inputs: Vec<Witness> = (0..6).map(Witness).collect();
+    let mut circuit = circuit_with_single_opcode(
+        _long_expression_opcode(&public_inputs),
+        public_inputs.clone(),
+    );
+    circuit.opcodes.push(_long_expression_opcode(&public_inputs));
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+
+    // Then
+    let num_ops = translator.acir_expression_gate.num_ops;
+    assert_eq!(6usize.div_ceil(num_ops), translator.builder.num_gates());
+}
+
+#[parameterized(program_name = {
+    "basic_memory_write",
+    "assert_x_equals_5",
+    "node_guardians_example",
+    "array_dynamic",
+    "1_mul",
+    "3_add",
+    "5_over",
+    "7_function",
+    "sha256_4",
+    "ecdsa_secp256k1",
+})]
+fn test_the_acir_expression_gate_never_needs_more_rows_than_arithmetic_gates(program_name: &str) {
+    // Given
+    let (program, _) = circuit_parser::precompiled_program_and_witnesses_with_name(program_name);
+    let circuit = _only_assert_zero_opcodes(&program.functions[0]);
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+    let rows = translator.builder.num_gates();
+    let degree = translator.builder.build::<C>().common.degree();
+    let (arithmetic_gate_rows, arithmetic_gate_degree) = _arithmetic_gate_lowering_size(&circuit);
+
+    // Then
+    assert!(
+        rows <= arithmetic_gate_rows && degree <= arithmetic_gate_degree,
+        "{} needs {} rows (degree {}) with the AcirExpressionGate and {} (degree {}) with \
+        ArithmeticGates",
+        program_name,
+        rows,
+        degree,
+        arithmetic_gate_rows,
+        arithmetic_gate_degree
+    );
+}
+
+#[test]
+fn test_the_acir_expression_gate_needs_fewer_rows_than_arithmetic_gates_for_sha256() {
+    // Given
+    let (program, _) = circuit_parser::precompiled_program_and_witnesses_with_name("sha256_4");
+    let circuit = _only_assert_zero_opcodes(&program.functions[0]);
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+    let (arithmetic_gate_rows, _) = _arithmetic_gate_lowering_size(&circuit);
+
+    // Then
+    assert!(translator.builder.num_gates() < arithmetic_gate_rows);
+}
+
+fn _only_assert_zero_opcodes(circuit: &Circuit) -> Circuit {
+    Circuit {
+        opcodes: circuit
+            .opcodes
+            .iter()
+            .filter(|opcode| matches!(opcode, Opcode::AssertZero(_)))
+            .cloned()
+            .collect(),
+        ..circuit.clone()
+    }
+}
+
+/// The rows (before building, so without the ones of the constants) and the degree of the circuit
+/// checking the AssertZero opcodes the way they were translated before the AcirExpressionGate:
+/// one ArithmeticGate operation per coefficient, product and addition.
+fn _arithmetic_gate_lowering_size(circuit: &Circuit) -> (usize, usize) {
+    let mut builder = CB::new(CircuitBuilderFromAcirToPlonky2::circuit_config(false));
+    let mut witness_target_map: HashMap<Witness, Target> = HashMap::new();
+    let mut target_for = |builder: &mut CB, witness: &Witness| {
+        *witness_target_map
+            .entry(*witness)
+            .or_insert_with(|| builder.add_virtual_target())
+    };
+    for opcode in &circuit.opcodes {
+        let Opcode::AssertZero(expression) = opcode else {
+            continue;
+        };
+        let mut acc = builder.constant(field_element_to_goldilocks_field(&expression.q_c));
+        for (coefficient, witness) in &expression.linear_combinations {
+            let target = target_for(&mut builder, witness);
+            let term = builder.mul_const(field_element_to_goldilocks_field(coefficient), target);
+            acc = builder.add(term, acc);
+        }
+        for (coefficient, witness_1, witness_2) in &expression.mul_terms {
+            let target_1 = target_for(&mut builder, witness_1);
+            let target_2 = target_for(&mut builder, witness_2);
+            let product = builder.mul(target_1, target_2);
+            let term = builder.mul_const(field_element_to_goldilocks_field(coefficient), product);
+            acc = builder.add(term, acc);
+        }
+        builder.assert_zero(acc);
+    }
+    let rows = builder.num_gates();
+    (rows, builder.build::<C>().common.degree())
+}
+
+/// w0 + w1 + w2 + w3 + w4 + w5 + 2·w0·w1 + 2·w2·w3 + 2·w4·w5 - 12 = 0, which holds when every
+/// witness is one.
+fn _long_expression_opcode(witnesses: &[Witness]) -> Opcode {
+    let two = FieldElement::from(2u128);
+    Opcode::AssertZero(Expression {
+        mul_terms: vec![
+            (two, witnesses[0], witnesses[1]),
+            (two, witnesses[2], witnesses[3]),
+            (two, witnesses[4], witnesses[5]),
+        ],
+        linear_combinations: witnesses
+            .iter()
+            .map(|witness| (FieldElement::one(), *witness))
+            .collect(),
+        q_c: -FieldElement::from(12u128),
+    })
+}
@@ -0,0 +1,90 @@
+use acir::circuit::brillig::{BrilligInputs, BrilligOutputs};
+use serde_json::json;
+
+use super::factories::circuit_factory;
+use super::*;
+use crate::actions::audit_action::AuditAction;
+use crate::field_conversion::FieldConversionMode;
+
+/// Tests for the detection of witnesses left unconstrained by the translation.
+
+fn _audit_action() -> AuditAction {
+    AuditAction {
+        acir_program_json_path: String::new(),
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge: false,
+        sha256_implementation: Sha256Implementation::default(),
+    }
+}
+
+fn _program_json(circuit: Circuit) -> serde_json::Value {
+    circuit_factory::program_json(&circuit_factory::program_with_main(circuit), json!({}))
+}
+
+#[test]
+fn test_audit_reports_no_witnesses_when_every_witness_is_constrained() {
+    // Given
+    let circuit = circuit_factory::circuit_with_a_public_input_and_two_assert_zero_operands(
+        Witness(0),
+        Witness(1),
+    );
+
+    // When
+    let report = _audit_action().generate_report(&_program_json(circuit));
+
+    // Then
+    assert_eq!(1, report.functions.len());
+    assert_eq!("main", report.functions[0].name);
+    assert!(report.functions[0].unconstrained_witnesses.is_empty());
+}
+
+#[test]
+fn test_audit_reports_an_unused_brillig_call_output() {
+    // Given
+    let public_input_witness = Witness(0);
+    let brillig_output_witness = Witness(1);
+    let mut circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_equals_4_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    circuit.opcodes.insert(
+        0,
+        Opcode::BrilligCall {
+            id: 0,
+            inputs: vec![BrilligInputs::Single(Expression::from(public_input_witness))],
+            outputs: vec![BrilligOutputs::Simple(brillig_output_witness)],
+            predicate: None,
+        },
+    );
+
+    // When
+    let report = _audit_action().generate_report(&_program_json(circuit));
+
+    // Then
+    let unconstrained_witnesses = &report.functions[0].unconstrained_witnesses;
+    assert_eq!(1, unconstrained_witnesses.len());
+    assert_eq!(1, unconstrained_witnesses[0].witness);
+    assert_eq!(Some(0), unconstrained_witnesses[0].opcode_index);
+    assert_eq!(Some(String::from("BrilligCall")), unconstrained_witnesses[0].opcode_kind);
+}
+
+#[test]
+fn test_audit_reports_an_unused_private_parameter() {
+    // Given
+    let public_input_witness = Witness(0);
+    let unused_parameter_witness = Witness(1);
+    let mut circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_equals_4_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    circuit.private_parameters.insert(unused_parameter_witness);
+
+    // When
+    let report = _audit_action().generate_report(&_program_json(circuit));
+
+    // Then
+    let unconstrained_witnesses = &report.functions[0].unconstrained_witnesses;
+    assert_eq!(1, unconstrained_witnesses.len());
+    assert_eq!(1, unconstrained_witnesses[0].witness);
+    assert_eq!(None, unconstrained_witnesses[0].opcode_index);
+}
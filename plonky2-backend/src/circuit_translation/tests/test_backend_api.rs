@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use acir::native_types::WitnessMap;
+
+use super::factories::circuit_factory;
+use super::*;
+use crate::backend::{CompiledCircuit, Plonky2Backend, Proof};
+use crate::field_conversion::FieldConversionMode;
+
+fn _witness_stack(x: u128, x_plus_4: u128) -> WitnessStack {
+    WitnessStack::from(WitnessMap::from(BTreeMap::from([
+        (Witness(0), FieldElement::from(x)),
+        (Witness(1), FieldElement::from(x_plus_4)),
+    ])))
+}
+
+fn _backend() -> Plonky2Backend {
+    Plonky2Backend {
+        zero_knowledge: false,
+        ..Plonky2Backend::default()
+    }
+}
+
+fn _compiled_circuit() -> CompiledCircuit {
+    _backend().compile(&circuit_factory::x_plus_4_squared_equals_25_program())
+}
+
+#[test]
+fn test_a_proof_of_the_library_api_verifies_and_returns_the_public_inputs() {
+    // Given
+    let backend = _backend();
+    let compiled_circuit = _compiled_circuit();
+    let verifying_key = backend.verification_key(&compiled_circuit);
+
+    // When
+    let proof = backend
+        .prove(&compiled_circuit, _witness_stack(1, 5))
+        .unwrap();
+
+    // Then
+    let public_inputs = backend.verify(&verifying_key, &proof).unwrap();
+    assert_eq!(vec![FieldElement::from(1_u128)], public_inputs);
+}
+
+#[test]
+fn test_the_proof_and_the_verifying_key_of_the_library_api_survive_a_round_trip_to_bytes() {
+    // Given
+    let backend = _backend();
+    let compiled_circuit = _compiled_circuit();
+    let proof = backend
+        .prove(&compiled_circuit, _witness_stack(1, 5))
+        .unwrap();
+    let verifying_key_bytes = backend.verification_key(&compiled_circuit).to_bytes();
+    let proof_bytes = proof.to_bytes();
+
+    // When
+    let verifying_key = VerifyingKey::from_bytes(&verifying_key_bytes).unwrap();
+    let proof = Proof::from_bytes(&proof_bytes, &verifying_key).unwrap();
+
+    // Then
+    let public_inputs = backend.verify(&verifying_key, &proof).unwrap();
+    assert_eq!(vec![FieldElement::from(1_u128)], public_inputs);
+}
+
+#[test]
+fn test_the_library_api_fails_to_prove_a_witness_stack_that_doesnt_satisfy_the_program() {
+    // Given
+    let backend = _backend();
+    let compiled_circuit = _compiled_circuit();
+
+    // When
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        backend
+            .prove(&compiled_circuit, _witness_stack(2, 5))
+            .unwrap()
+    }));
+
+    // Then
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_the_library_api_returns_an_error_for_a_witness_that_is_not_a_goldilocks_element() {
+    // Given
+    let backend = Plonky2Backend {
+        field_conversion_mode: FieldConversionMode::Strict,
+        ..Plonky2Backend::default()
+    };
+    let compiled_circuit = backend.compile(&circuit_factory::x_plus_4_squared_equals_25_program());
+    let witness_stack = WitnessStack::from(WitnessMap::from(BTreeMap::from([
+        (Witness(0), FieldElement::from(1_u128 << 100)),
+        (Witness(1), FieldElement::from(5_u128)),
+    ])));
+
+    // When
+    let result = backend.prove(&compiled_circuit, witness_stack);
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.starts_with("Witness 0 has value 0x"), "{}", error);
+    assert!(error.contains("is not a Goldilocks field element"), "{}", error);
+}
+
+#[test]
+fn test_the_library_api_rejects_a_proof_with_the_verifying_key_of_another_program() {
+    // Given
+    let backend = _backend();
+    let proof = backend
+        .prove(&_compiled_circuit(), _witness_stack(1, 5))
+        .unwrap();
+    let mut another_program = circuit_factory::x_plus_4_squared_equals_25_program();
+    another_program.functions[0].public_parameters = Default::default();
+    let another_verifying_key = backend.verification_key(&backend.compile(&another_program));
+
+    // When
+    let result = backend.verify(&another_verifying_key, &proof);
+
+    // Then
+    assert!(result.is_err());
+}
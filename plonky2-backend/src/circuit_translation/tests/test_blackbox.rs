@@ -1,6 +1,8 @@
 use plonky2::field::goldilocks_field::GoldilocksField;
 
+use crate::circuit_translation::byte_lookups::BITWISE_LOOKUP_THRESHOLD;
 use crate::circuit_translation::tests::factories::{circuit_factory, utils};
+use crate::circuit_translation::translation_cache::RANGE_CHECK_LOOKUP_THRESHOLD;
 
 use super::*;
 
@@ -97,7 +99,7 @@ fn test_range_check_with_witness_value(witness_value: F, max_num_bits: u32) {
         utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
 
     //Then
-    utils::check_linked_output_targets_property(&circuit, &witness_target_map);
+    utils::check_linked_output_targets_property(&circuit, &circuit_data, &witness_target_map);
     let proof = utils::generate_plonky2_proof_using_witness_values(
         vec![(public_input_witness, witness_value)],
         &witness_target_map,
@@ -106,6 +108,70 @@ fn test_range_check_with_witness_value(witness_value: F, max_num_bits: u32) {
     assert!(circuit_data.verify(proof).is_ok());
 }
 
+#[test]
+fn test_backend_can_translate_many_range_checks_u8() {
+    let witness_values = (0..RANGE_CHECK_LOOKUP_THRESHOLD as u64 * 2)
+        .map(|value| F::from_canonical_u64(value * 17 % 256))
+        .collect();
+    test_range_checks_with_witness_values(witness_values, 8);
+}
+
+#[test]
+#[should_panic]
+fn test_backend_cannot_provide_witness_value_bigger_than_u8_for_many_u8_range_checks() {
+    let mut witness_values = vec![F::from_canonical_u64(255); RANGE_CHECK_LOOKUP_THRESHOLD * 2];
+    witness_values[3] = F::from_canonical_u64(256);
+    test_range_checks_with_witness_values(witness_values, 8);
+}
+
+#[test]
+fn test_backend_can_translate_many_range_checks_u12() {
+    let witness_values = vec![F::from_canonical_u64((1 << 12) - 1); RANGE_CHECK_LOOKUP_THRESHOLD];
+    test_range_checks_with_witness_values(witness_values, 12);
+}
+
+#[test]
+#[should_panic]
+fn test_backend_cannot_provide_witness_value_bigger_than_u12_for_many_u12_range_checks() {
+    let mut witness_values =
+        vec![F::from_canonical_u64((1 << 12) - 1); RANGE_CHECK_LOOKUP_THRESHOLD];
+    witness_values[0] = F::from_canonical_u64(1 << 12);
+    test_range_checks_with_witness_values(witness_values, 12);
+}
+
+#[test]
+fn test_many_range_checks_cost_less_than_a_gate_each() {
+    // Given
+    let num_range_checks = RANGE_CHECK_LOOKUP_THRESHOLD * 64;
+    let witnesses = (0..num_range_checks as u32).map(Witness).collect();
+    let circuit = circuit_factory::range_checks_circuit(witnesses, 16);
+
+    // When
+    let (circuit_data, _) = utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    // Then
+    // The degree of the built circuit counts the rows of the Range table and the padding too
+    assert!(circuit_data.common.degree() < num_range_checks);
+}
+
+fn test_range_checks_with_witness_values(witness_values: Vec<F>, max_num_bits: u32) {
+    //Given
+    let witnesses: Vec<Witness> = (0..witness_values.len() as u32).map(Witness).collect();
+    let circuit = circuit_factory::range_checks_circuit(witnesses.clone(), max_num_bits);
+
+    // When
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        witnesses.into_iter().zip(witness_values).collect(),
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
 // ---------------- BITWISE OPERATIONS ------------------ //
 
 #[test]
@@ -238,23 +304,125 @@ fn _assert_backend_supports_bitwise_operation(
         bit_size,
     );
 
+    // Both the bit decomposition and the lookup table
+    for bitwise_lookup_threshold in [BITWISE_LOOKUP_THRESHOLD, 0] {
+        // When
+        let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+        translator.bitwise_lookup_threshold = bitwise_lookup_threshold;
+        translator.translate_circuit(&circuit);
+        let (circuit_data, witness_target_map) = translator.unpack();
+
+        //Then
+        let witness_assignment = vec![
+            (public_input_witness_0, a),
+            (public_input_witness_1, b),
+            (output_witness_2, output),
+        ];
+
+        utils::check_linked_output_targets_property(&circuit, &circuit_data, &witness_target_map);
+        let proof = utils::generate_plonky2_proof_using_witness_values(
+            witness_assignment,
+            &witness_target_map,
+            &circuit_data,
+        );
+
+        // The return value is exposed after the public parameters
+        assert_eq!(vec![a, b, output], proof.public_inputs);
+        assert!(circuit_data.verify(proof).is_ok());
+    }
+}
+
+#[test]
+fn test_a_few_bitwise_operations_dont_pay_for_the_rows_of_the_lookup_table() {
+    // Given
+    let circuit = circuit_factory::bitwise_and_circuit(Witness(0), Witness(1), Witness(2), 32);
+
     // When
+    let degree = _built_circuit_degree(&circuit, BITWISE_LOOKUP_THRESHOLD);
+    let degree_with_lookup_table = _built_circuit_degree(&circuit, 0);
+
+    // Then
+    // The And table alone has 65,536 entries, so it takes thousands of rows
+    assert!(degree_with_lookup_table >= 2048);
+    assert!(degree < degree_with_lookup_table);
+}
+
+#[test]
+fn test_many_bitwise_operations_are_smaller_with_the_lookup_table() {
+    // Given
+    let num_operations = BITWISE_LOOKUP_THRESHOLD * 2;
+    let circuit = _chained_bitwise_and_circuit(num_operations);
+
+    // When
+    let degree = _built_circuit_degree(&circuit, BITWISE_LOOKUP_THRESHOLD);
+    let degree_with_bit_decompositions = _built_circuit_degree(&circuit, usize::MAX);
+
+    // Then
+    assert!(degree < degree_with_bit_decompositions);
+}
+
+/// Every 32 bits AND takes the output of the previous one and a new input, so no operand is
+/// decomposed twice.
+fn _chained_bitwise_and_circuit(num_operations: usize) -> Circuit {
+    let and_opcode = |lhs: Witness, rhs: Witness, output: Witness| {
+        Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::AND {
+            lhs: FunctionInput {
+                witness: lhs,
+                num_bits: 32,
+            },
+            rhs: FunctionInput {
+                witness: rhs,
+                num_bits: 32,
+            },
+            output,
+        })
+    };
+    let num_operations = num_operations as u32;
+    let mut circuit = circuit_factory::circuit_with_single_opcode(
+        and_opcode(Witness(0), Witness(1), Witness(num_operations + 1)),
+        vec![],
+    );
+    for index in 1..num_operations {
+        circuit.opcodes.push(and_opcode(
+            Witness(num_operations + index),
+            Witness(index + 1),
+            Witness(num_operations + index + 1),
+        ));
+    }
+    circuit
+}
+
+fn _built_circuit_degree(circuit: &Circuit, bitwise_lookup_threshold: usize) -> usize {
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.bitwise_lookup_threshold = bitwise_lookup_threshold;
+    translator.translate_circuit(circuit);
+    let (circuit_data, _) = translator.unpack();
+    circuit_data.common.degree()
+}
+
+#[test]
+fn test_backend_rejects_a_wrong_return_value_of_a_bitwise_operation() {
+    // Given
+    let circuit = circuit_factory::bitwise_and_circuit(Witness(0), Witness(1), Witness(2), 8);
     let (circuit_data, witness_target_map) =
         utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
 
-    //Then
+    // When
+    // 0b1100 AND 0b1010 is 0b1000, not 0b1110
     let witness_assignment = vec![
-        (public_input_witness_0, a),
-        (public_input_witness_1, b),
-        (output_witness_2, output),
+        (Witness(0), F::from_canonical_u64(0b1100)),
+        (Witness(1), F::from_canonical_u64(0b1010)),
+        (Witness(2), F::from_canonical_u64(0b1110)),
     ];
-
-    utils::check_linked_output_targets_property(&circuit, &witness_target_map);
-    let proof = utils::generate_plonky2_proof_using_witness_values(
-        witness_assignment,
-        &witness_target_map,
-        &circuit_data,
-    );
-
-    assert!(circuit_data.verify(proof).is_ok());
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let proof = utils::generate_plonky2_proof_using_witness_values(
+            witness_assignment,
+            &witness_target_map,
+            &circuit_data,
+        );
+        circuit_data.verify(proof).unwrap();
+    }));
+
+    // Then
+    assert!(result.is_err());
 }
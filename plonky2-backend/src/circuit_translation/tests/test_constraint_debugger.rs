@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use acir::native_types::WitnessMap;
+
+use super::factories::circuit_factory::*;
+use super::*;
+use crate::actions::prove_action::ProveAction;
+use crate::constraint_debugger::ConstraintDebugger;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_debug_info::ProgramDebugInfo;
+
+/// Tests for the checks of the debug mode of the prove command.
+
+fn _witness_map(values: Vec<(u32, u128)>) -> WitnessMap<FieldElement> {
+    WitnessMap::from(BTreeMap::from_iter(
+        values
+            .into_iter()
+            .map(|(witness, value)| (Witness(witness), FieldElement::from(value))),
+    ))
+}
+
+fn _prove_action() -> ProveAction {
+    ProveAction {
+        acir_program_json_path: String::new(),
+        witness_stack_zip_path: String::new(),
+        resulting_proof_file_path: String::new(),
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge: false,
+        sha256_implementation: Sha256Implementation::default(),
+        debug: true,
+    }
+}
+
+#[test]
+fn test_debugger_reports_the_first_violated_assert_zero_with_its_source_location() {
+    // Given
+    let circuit =
+        circuit_with_a_public_input_and_two_assert_zero_operands(Witness(0), Witness(1));
+    let witness_map = _witness_map(vec![(0, 2), (1, 6)]);
+    let debug_info = ProgramDebugInfo::from_program_json(&program_json_with_debug_symbols());
+
+    // When
+    let violation =
+        ConstraintDebugger::new_for(&circuit, &witness_map, FieldConversionMode::Lenient)
+            .first_violated_opcode()
+            .unwrap();
+
+    // Then
+    assert_eq!(1, violation.opcode_index);
+    assert_eq!(vec![(Witness(1), F::from_canonical_u64(6))], violation.values);
+    let report = violation.report(&debug_info, 0);
+    assert!(report.starts_with("Opcode 1 (AssertZero) is not satisfied"), "{}", report);
+    assert!(report.contains("w1 = 6"), "{}", report);
+    assert!(report.contains("at src/main.nr:3:5: assert(y * y == 25)"), "{}", report);
+}
+
+#[test]
+fn test_debugger_reports_a_wrong_output_of_a_bitwise_operation() {
+    // Given
+    let circuit = bitwise_and_circuit(Witness(0), Witness(1), Witness(2), 8);
+    let witness_map = _witness_map(vec![(0, 0b1100), (1, 0b1010), (2, 0b1110)]);
+
+    // When
+    let violation =
+        ConstraintDebugger::new_for(&circuit, &witness_map, FieldConversionMode::Lenient)
+            .first_violated_opcode()
+            .unwrap();
+
+    // Then
+    assert_eq!("BlackBoxFuncCall::AND", violation.opcode_kind);
+    assert!(violation.reason.contains("output witness 2 is 14 but the function returns 8"));
+}
+
+#[test]
+fn test_debugger_reports_an_input_of_a_bitwise_operation_out_of_range() {
+    // Given
+    let circuit = bitwise_xor_circuit(Witness(0), Witness(1), Witness(2), 8);
+    let witness_map = _witness_map(vec![(0, 256), (1, 1), (2, 257)]);
+
+    // When
+    let violation =
+        ConstraintDebugger::new_for(&circuit, &witness_map, FieldConversionMode::Lenient)
+            .first_violated_opcode()
+            .unwrap();
+
+    // Then
+    assert!(violation.reason.contains("doesn't fit in 8 bits"), "{}", violation.reason);
+}
+
+#[test]
+fn test_debugger_reports_a_sha256_input_wider_than_a_word_as_out_of_range() {
+    // Given
+    let input = |witness: u32| FunctionInput {
+        witness: Witness(witness),
+        num_bits: 64,
+    };
+    let sha256_compression = Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::Sha256Compression {
+        inputs: Box::new(std::array::from_fn(|i| input(i as u32))),
+        hash_values: Box::new(std::array::from_fn(|i| input(16 + i as u32))),
+        outputs: Box::new(std::array::from_fn(|i| Witness(24 + i as u32))),
+    });
+    let circuit = circuit_with_single_opcode(sha256_compression, vec![]);
+    let mut values: Vec<(u32, u128)> = (0..32).map(|witness| (witness, 0)).collect();
+    values[0] = (0, 1 << 32);
+    let witness_map = _witness_map(values);
+
+    // When
+    let violation =
+        ConstraintDebugger::new_for(&circuit, &witness_map, FieldConversionMode::Lenient)
+            .first_violated_opcode()
+            .unwrap();
+
+    // Then
+    assert!(violation.reason.contains("doesn't fit in 32 bits"), "{}", violation.reason);
+}
+
+#[test]
+fn test_witnesses_satisfying_the_circuit_pass_the_debug_checks() {
+    // Given
+    let circuit =
+        circuit_with_a_public_input_and_two_assert_zero_operands(Witness(0), Witness(1));
+    let witness_stack = WitnessStack::from(_witness_map(vec![(0, 1), (1, 5)]));
+    let debug_info = ProgramDebugInfo::from_program_json(&program_json_with_debug_symbols());
+    let prove_action = _prove_action();
+    let (circuit_data, witness_target_map) =
+        prove_action.generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    // When
+    let result = prove_action.check_witness_stack(
+        &circuit,
+        &witness_stack,
+        &debug_info,
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    // Then
+    assert_eq!(Ok(()), result);
+}
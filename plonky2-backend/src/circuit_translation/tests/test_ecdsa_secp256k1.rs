@@ -0,0 +1,152 @@
+use super::factories::utils;
+use super::*;
+use acir::circuit::opcodes::FunctionInput;
+use acir::circuit::{opcodes, ExpressionWidth, PublicInputs};
+use parameterized::parameterized;
+use std::collections::BTreeSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// Signature taken from the ecdsa_secp256k1 Noir program used for testing (see its Prover.toml)
+const HASHED_MESSAGE: [u8; 32] = [
+    0xce, 0x7d, 0xf6, 0xb1, 0xb2, 0x85, 0x2c, 0x5c, 0x15, 0x6b, 0x68, 0x3a, 0x9f, 0x8d, 0x4a, 0x8d,
+    0xae, 0xda, 0x2f, 0x35, 0xf0, 0x25, 0xcb, 0x0c, 0xf3, 0x49, 0x43, 0xdc, 0xac, 0x70, 0xd6, 0xa3,
+];
+
+const PUBLIC_KEY_X: [u8; 32] = [
+    0x7b, 0x83, 0xad, 0x6a, 0xfb, 0x12, 0x09, 0xf3, 0xc8, 0x2e, 0xbe, 0xb0, 0x8c, 0x0c, 0x5f, 0xa9,
+    0xbf, 0x67, 0x24, 0x54, 0x85, 0x06, 0xf2, 0xfb, 0x4f, 0x99, 0x1e, 0x22, 0x87, 0xa7, 0x70, 0x90,
+];
+
+const PUBLIC_KEY_Y: [u8; 32] = [
+    0x17, 0x73, 0x16, 0xca, 0x82, 0xb0, 0xbd, 0xf7, 0x0c, 0xd9, 0xde, 0xe1, 0x45, 0xc3, 0x00, 0x2c,
+    0x0d, 0xa1, 0xd9, 0x26, 0x26, 0x44, 0x98, 0x75, 0x97, 0x2a, 0x27, 0x80, 0x7b, 0x73, 0xb4, 0x2e,
+];
+
+const SIGNATURE: [u8; 64] = [
+    0x6f, 0x01, 0x56, 0x09, 0x1c, 0xbe, 0x91, 0x2f, 0x2d, 0x5d, 0x12, 0x15, 0xcc, 0x3c, 0xd8, 0x1c,
+    0x09, 0x63, 0xc8, 0x83, 0x9b, 0x93, 0xaf, 0x60, 0xe0, 0x92, 0x1b, 0x61, 0xa1, 0x9c, 0x54, 0x30,
+    0x0c, 0x71, 0x00, 0x6d, 0xd9, 0x3f, 0x35, 0x08, 0xc4, 0x32, 0xda, 0xca, 0x21, 0xdb, 0x00, 0x95,
+    0xf4, 0xb1, 0x65, 0x42, 0x78, 0x2b, 0x79, 0x86, 0xf4, 0x8a, 0x5d, 0x0a, 0xe3, 0xc5, 0x83, 0xd4,
+];
+
+#[test]
+fn test_backend_accepts_a_valid_ecdsa_secp256k1_signature() {
+    // Given
+    let circuit = _ecdsa_secp256k1_circuit();
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    // When
+    let witness_assignment = _witness_assignment(&HASHED_MESSAGE, &SIGNATURE, 1);
+
+    // Then
+    assert!(_proof_is_valid(&circuit_data, &witness_target_map, witness_assignment));
+}
+
+#[test]
+fn test_backend_does_not_accept_a_signature_whose_r_is_lower_than_the_x_of_r_point() {
+    // The last byte of the message is changed so that R.x (the x coordinate of the point computed
+    // while verifying) is bigger than r. A less-or-equal comparison would accept the signature.
+
+    // Given
+    let circuit = _ecdsa_secp256k1_circuit();
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+    let mut tampered_hashed_message = HASHED_MESSAGE;
+    tampered_hashed_message[31] = 0x00;
+
+    // When
+    let accepted_assignment = _witness_assignment(&tampered_hashed_message, &SIGNATURE, 1);
+    let rejected_assignment = _witness_assignment(&tampered_hashed_message, &SIGNATURE, 0);
+
+    // Then
+    assert!(!_proof_is_valid(&circuit_data, &witness_target_map, accepted_assignment));
+    assert!(_proof_is_valid(&circuit_data, &witness_target_map, rejected_assignment));
+}
+
+#[parameterized(zeroed_half = {0, 1})]
+fn test_backend_proves_that_a_signature_with_a_zero_r_or_s_is_invalid(zeroed_half: usize) {
+    // Given
+    let circuit = _ecdsa_secp256k1_circuit();
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+    let mut signature = SIGNATURE;
+    signature[32 * zeroed_half..32 * (zeroed_half + 1)].fill(0);
+
+    // When
+    let accepted_assignment = _witness_assignment(&HASHED_MESSAGE, &signature, 1);
+    let rejected_assignment = _witness_assignment(&HASHED_MESSAGE, &signature, 0);
+
+    // Then
+    assert!(!_proof_is_valid(&circuit_data, &witness_target_map, accepted_assignment));
+    assert!(_proof_is_valid(&circuit_data, &witness_target_map, rejected_assignment));
+}
+
+fn _proof_is_valid(
+    circuit_data: &CircuitData<F, C, 2>,
+    witness_target_map: &HashMap<Witness, Target>,
+    witness_assignment: Vec<(Witness, F)>,
+) -> bool {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let proof = utils::generate_plonky2_proof_using_witness_values(
+            witness_assignment,
+            witness_target_map,
+            circuit_data,
+        );
+        circuit_data.verify(proof)
+    }));
+    matches!(result, Ok(Ok(())))
+}
+
+/// Witnesses 0 to 159 hold the bytes of the hashed message, the public key (x and y) and the
+/// signature, and witness 160 holds the result of the verification.
+fn _witness_assignment(
+    hashed_message: &[u8; 32],
+    signature: &[u8; 64],
+    output: u64,
+) -> Vec<(Witness, F)> {
+    let bytes = [
+        hashed_message.as_slice(),
+        PUBLIC_KEY_X.as_slice(),
+        PUBLIC_KEY_Y.as_slice(),
+        signature.as_slice(),
+    ]
+    .concat();
+    let mut witness_assignment: Vec<(Witness, F)> = bytes
+        .iter()
+        .enumerate()
+        .map(|(index, byte)| (Witness(index as u32), F::from_canonical_u8(*byte)))
+        .collect();
+    witness_assignment.push((Witness(160), F::from_canonical_u64(output)));
+    witness_assignment
+}
+
+// ------------ CIRCUITS ------------ //
+
+fn _ecdsa_secp256k1_circuit() -> Circuit {
+    // BLACKBOX::ECDSA_SECP256K1 [(_0..._31), (_32..._63), (_64..._95), (_96..._159)] [ _160]
+    fn byte_inputs<const N: usize>(first_witness: u32) -> Box<[FunctionInput; N]> {
+        Box::new(core::array::from_fn(|index| FunctionInput {
+            witness: Witness(first_witness + index as u32),
+            num_bits: 8,
+        }))
+    }
+    Circuit {
+        current_witness_index: 160,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![Opcode::BlackBoxFuncCall(
+            opcodes::BlackBoxFuncCall::EcdsaSecp256k1 {
+                hashed_message: byte_inputs(0),
+                public_key_x: byte_inputs(32),
+                public_key_y: byte_inputs(64),
+                signature: byte_inputs(96),
+                output: Witness(160),
+            },
+        )],
+        private_parameters: BTreeSet::from_iter((0..160).map(Witness)),
+        public_parameters: PublicInputs(BTreeSet::new()),
+        return_values: PublicInputs(BTreeSet::from([Witness(160)])),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
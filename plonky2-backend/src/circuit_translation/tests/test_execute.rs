@@ -0,0 +1,69 @@
+use super::factories::circuit_factory;
+use super::*;
+use crate::actions::execute_action::ExecuteAction;
+use crate::backend::Plonky2Backend;
+use crate::field_conversion::FieldConversionMode;
+
+fn _x_plus_4_squared_equals_25_program() -> (Circuit, serde_json::Value) {
+    let program = circuit_factory::x_plus_4_squared_equals_25_program();
+    let program_json =
+        circuit_factory::program_json(&program, circuit_factory::public_field_parameter_abi("x"));
+    (program.functions[0].clone(), program_json)
+}
+
+fn _execute_action() -> ExecuteAction {
+    ExecuteAction {
+        acir_program_json_path: String::new(),
+        prover_toml_path: String::new(),
+        resulting_witness_file_path: String::new(),
+        field_conversion_mode: FieldConversionMode::Lenient,
+    }
+}
+
+#[test]
+fn test_execute_solves_the_intermediate_witnesses_from_the_inputs() {
+    // Given
+    let (_, program_json) = _x_plus_4_squared_equals_25_program();
+
+    // When
+    let mut witness_stack = _execute_action().solve_witness_stack(&program_json, "x = \"1\"");
+
+    // Then
+    let witness_map = witness_stack.pop().unwrap().witness;
+    assert_eq!(Some(&FieldElement::from(1_u128)), witness_map.get(&Witness(0)));
+    assert_eq!(Some(&FieldElement::from(5_u128)), witness_map.get(&Witness(1)));
+}
+
+#[test]
+#[should_panic(expected = "Failed to solve the witnesses")]
+fn test_execute_fails_for_inputs_that_dont_satisfy_the_program() {
+    let (_, program_json) = _x_plus_4_squared_equals_25_program();
+    _execute_action().solve_witness_stack(&program_json, "x = \"2\"");
+}
+
+#[test]
+#[should_panic]
+fn test_execute_fails_when_an_input_is_missing() {
+    let (_, program_json) = _x_plus_4_squared_equals_25_program();
+    _execute_action().solve_witness_stack(&program_json, "y = \"1\"");
+}
+
+#[test]
+fn test_the_executed_witness_stack_can_be_proven() {
+    // Given
+    let (_, program_json) = _x_plus_4_squared_equals_25_program();
+    let witness_stack = _execute_action().solve_witness_stack(&program_json, "x = \"1\"");
+    let backend = Plonky2Backend {
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge: false,
+        sha256_implementation: Sha256Implementation::default(),
+    };
+    let compiled_circuit = backend.compile_program_json(&program_json);
+
+    // When
+    let proof = backend.prove(&compiled_circuit, witness_stack).unwrap();
+
+    // Then
+    let verifying_key = backend.verification_key(&compiled_circuit);
+    assert!(backend.verify(&verifying_key, &proof).is_ok());
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use acir::native_types::WitnessMap;
+use num_bigint::BigUint;
+
+use super::factories::circuit_factory::*;
+use super::factories::utils;
+use super::*;
+use crate::backend::Plonky2Backend;
+use crate::field_conversion::{strict_biguint_to_goldilocks_field, FieldConversionMode};
+
+const BN254_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+fn _bn254_modulus() -> BigUint {
+    BigUint::parse_bytes(BN254_MODULUS.as_bytes(), 10).unwrap()
+}
+
+#[test]
+fn test_strict_conversion_keeps_canonical_goldilocks_values() {
+    let value = BigUint::from(0xFFFFFFFF00000000u64);
+
+    let converted = strict_biguint_to_goldilocks_field(&value, &_bn254_modulus());
+
+    assert_eq!(Some(F::from_canonical_u64(0xFFFFFFFF00000000)), converted);
+}
+
+#[test]
+fn test_strict_conversion_interprets_values_near_the_bn254_modulus_as_negatives() {
+    let minus_one = _bn254_modulus() - BigUint::from(1u32);
+    let minus_five = _bn254_modulus() - BigUint::from(5u32);
+
+    assert_eq!(
+        Some(-F::from_canonical_u64(1)),
+        strict_biguint_to_goldilocks_field(&minus_one, &_bn254_modulus())
+    );
+    assert_eq!(
+        Some(-F::from_canonical_u64(5)),
+        strict_biguint_to_goldilocks_field(&minus_five, &_bn254_modulus())
+    );
+}
+
+#[test]
+fn test_strict_conversion_rejects_values_that_are_not_goldilocks_elements() {
+    let goldilocks_modulus = BigUint::from(0xFFFFFFFF00000001u64);
+    let big_value = BigUint::from(1u32) << 128;
+
+    assert_eq!(
+        None,
+        strict_biguint_to_goldilocks_field(&goldilocks_modulus, &_bn254_modulus())
+    );
+    assert_eq!(
+        None,
+        strict_biguint_to_goldilocks_field(&big_value, &_bn254_modulus())
+    );
+}
+
+#[test]
+fn test_lenient_conversion_reduces_values_modulo_goldilocks() {
+    let value = FieldElement::from(5u128);
+
+    let converted = FieldConversionMode::Lenient
+        .field_element_to_goldilocks_field(&value, || String::from("Witness 0"));
+
+    assert_eq!(F::from_canonical_u64(5), converted);
+}
+
+#[test]
+fn test_strict_mode_translates_circuits_with_canonical_constants() {
+    // Given
+    let public_input_witness = Witness(0);
+    let only_opcode = x_equals_4_opcode(public_input_witness);
+    let circuit = circuit_with_single_opcode(only_opcode, vec![public_input_witness]);
+
+    // When
+    let mut translator =
+        CircuitBuilderFromAcirToPlonky2::new_with_field_conversion_mode(FieldConversionMode::Strict);
+    translator.translate_circuit(&circuit);
+    let (circuit_data, witness_target_map) = translator.unpack();
+
+    // Then
+    let four = F::from_canonical_u64(4);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        vec![(public_input_witness, four)],
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_strict_conversion_back_reads_the_upper_half_of_goldilocks_as_negatives() {
+    let minus_five = -F::from_canonical_u64(5);
+
+    assert_eq!(
+        -FieldElement::from(5u128),
+        FieldConversionMode::Strict.goldilocks_field_to_field_element(minus_five)
+    );
+    assert_eq!(
+        FieldElement::from(5u128),
+        FieldConversionMode::Strict.goldilocks_field_to_field_element(F::from_canonical_u64(5))
+    );
+    assert_eq!(
+        FieldElement::from(0xFFFFFFFF00000001u128 - 5),
+        FieldConversionMode::Lenient.goldilocks_field_to_field_element(minus_five)
+    );
+}
+
+#[test]
+fn test_strict_mode_returns_negative_public_inputs_as_negative_field_elements() {
+    // Given
+    let public_input_witness = Witness(0);
+    let x_plus_4_equals_0 = Opcode::AssertZero(Expression {
+        mul_terms: Vec::new(),
+        linear_combinations: vec![(FieldElement::one(), public_input_witness)],
+        q_c: FieldElement::from(4u128),
+    });
+    let circuit = circuit_with_single_opcode(x_plus_4_equals_0, vec![public_input_witness]);
+    let backend = Plonky2Backend {
+        field_conversion_mode: FieldConversionMode::Strict,
+        zero_knowledge: false,
+        sha256_implementation: Sha256Implementation::default(),
+    };
+    let compiled_circuit = backend.compile(&program_with_main(circuit));
+    let witness_stack = WitnessStack::from(WitnessMap::from(BTreeMap::from([(
+        public_input_witness,
+        -FieldElement::from(4u128),
+    )])));
+
+    // When
+    let proof = backend.prove(&compiled_circuit, witness_stack).unwrap();
+
+    // Then
+    let public_inputs = backend
+        .verify(&backend.verification_key(&compiled_circuit), &proof)
+        .unwrap();
+    assert_eq!(vec![-FieldElement::from(4u128)], public_inputs);
+}
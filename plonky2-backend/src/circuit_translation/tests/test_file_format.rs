@@ -0,0 +1,188 @@
+use plonky2::plonk::proof::CompressedProofWithPublicInputs;
+
+use super::factories::{circuit_factory, utils};
+use super::*;
+use crate::file_format::{FileHeader, FileKind, FORMAT_VERSION, MAGIC_NUMBER};
+use crate::noir_and_plonky2_serialization::{
+    deserialize_proof, deserialize_verifying_key, serialize_compressed_proof,
+    serialize_verifying_key, VerifyingKey,
+};
+
+const PROGRAM_HASH: [u8; 32] = [7; 32];
+
+fn _verifying_key(circuit_data: &CircuitData<F, C, 2>) -> VerifyingKey {
+    VerifyingKey {
+        program_hash: PROGRAM_HASH.to_vec(),
+        verifier_data: circuit_data.verifier_data(),
+    }
+}
+
+fn _x_equals_4_circuit_data_and_compressed_proof() -> (
+    CircuitData<F, C, 2>,
+    CompressedProofWithPublicInputs<F, C, 2>,
+) {
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_equals_4_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        vec![(public_input_witness, F::from_canonical_u64(4))],
+        &witness_target_map,
+        &circuit_data,
+    );
+    let compressed_proof = proof
+        .compress(&circuit_data.verifier_only.circuit_digest, &circuit_data.common)
+        .unwrap();
+    (circuit_data, compressed_proof)
+}
+
+fn _verifying_key_of_another_circuit() -> VerifyingKey {
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_times_3_equals_12_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    let (circuit_data, _) = utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+    _verifying_key(&circuit_data)
+}
+
+#[test]
+fn test_serialized_verifying_key_and_proof_can_be_read_and_verified() {
+    // Given
+    let (circuit_data, compressed_proof) = _x_equals_4_circuit_data_and_compressed_proof();
+
+    // When
+    let serialized_verifying_key = serialize_verifying_key(&_verifying_key(&circuit_data));
+    let serialized_proof =
+        serialize_compressed_proof(&compressed_proof, &PROGRAM_HASH, &circuit_data);
+    let verifying_key = deserialize_verifying_key(&serialized_verifying_key).unwrap();
+    let read_proof = deserialize_proof(&serialized_proof, &verifying_key).unwrap();
+
+    // Then
+    assert!(serialized_proof.starts_with(&MAGIC_NUMBER));
+    assert_eq!(PROGRAM_HASH.to_vec(), verifying_key.program_hash);
+    assert!(verifying_key.verifier_data.verify_compressed(read_proof).is_ok());
+}
+
+#[test]
+fn test_header_is_read_back_as_written() {
+    // Given
+    let (circuit_data, _) = _x_equals_4_circuit_data_and_compressed_proof();
+    let header = FileHeader::new_for(
+        FileKind::Proof,
+        &PROGRAM_HASH,
+        &circuit_data.verifier_only,
+        &circuit_data.common,
+    );
+
+    // When
+    let bytes = header.prepend_to(vec![1, 2, 3]);
+    let (read_header, payload) = FileHeader::split_from(&bytes).unwrap();
+
+    // Then
+    assert_eq!(header, read_header);
+    assert_eq!(1, read_header.num_public_inputs);
+    assert_eq!("KeccakGoldilocksConfig", read_header.hash_config);
+    assert_eq!(vec![1, 2, 3], payload);
+}
+
+#[test]
+fn test_proof_of_another_circuit_is_rejected_because_of_the_circuit_digest() {
+    // Given
+    let (circuit_data, compressed_proof) = _x_equals_4_circuit_data_and_compressed_proof();
+    let serialized_proof =
+        serialize_compressed_proof(&compressed_proof, &PROGRAM_HASH, &circuit_data);
+
+    // When
+    let result = deserialize_proof(&serialized_proof, &_verifying_key_of_another_circuit());
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("belongs to the circuit with digest"), "{}", error);
+}
+
+#[test]
+fn test_proof_generated_from_another_program_is_rejected_because_of_the_program_hash() {
+    // Given
+    let (circuit_data, compressed_proof) = _x_equals_4_circuit_data_and_compressed_proof();
+    let another_program_hash = [8; 32];
+    let serialized_proof =
+        serialize_compressed_proof(&compressed_proof, &another_program_hash, &circuit_data);
+
+    // When
+    let result = deserialize_proof(&serialized_proof, &_verifying_key(&circuit_data));
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("generated from the ACIR program with hash 0x0808"), "{}", error);
+}
+
+#[test]
+fn test_files_without_header_are_rejected_because_of_the_magic_number() {
+    // Given
+    let (circuit_data, compressed_proof) = _x_equals_4_circuit_data_and_compressed_proof();
+
+    // When
+    let result = deserialize_proof(&compressed_proof.to_bytes(), &_verifying_key(&circuit_data));
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("wrong magic number"), "{}", error);
+}
+
+#[test]
+fn test_files_with_another_format_version_are_rejected() {
+    // Given
+    let (circuit_data, _) = _x_equals_4_circuit_data_and_compressed_proof();
+    let mut serialized_verifying_key = serialize_verifying_key(&_verifying_key(&circuit_data));
+    let newer_format_version = (FORMAT_VERSION + 1).to_le_bytes();
+    serialized_verifying_key[4..6].copy_from_slice(&newer_format_version);
+
+    // When
+    let result = deserialize_verifying_key(&serialized_verifying_key);
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains(&format!("format version {}", FORMAT_VERSION + 1)), "{}", error);
+}
+
+#[test]
+fn test_files_written_by_another_backend_version_are_rejected() {
+    // Given
+    let (circuit_data, compressed_proof) = _x_equals_4_circuit_data_and_compressed_proof();
+    let mut header = FileHeader::new_for(
+        FileKind::Proof,
+        &PROGRAM_HASH,
+        &circuit_data.verifier_only,
+        &circuit_data.common,
+    );
+    header.backend_version = "0.0.0-old".to_string();
+    let serialized_proof = header.prepend_to(compressed_proof.to_bytes());
+
+    // When
+    let result = deserialize_proof(&serialized_proof, &_verifying_key(&circuit_data));
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("backend version 0.0.0-old"), "{}", error);
+}
+
+#[test]
+fn test_a_proof_is_not_accepted_as_a_verifying_key() {
+    // Given
+    let (circuit_data, compressed_proof) = _x_equals_4_circuit_data_and_compressed_proof();
+    let serialized_proof =
+        serialize_compressed_proof(&compressed_proof, &PROGRAM_HASH, &circuit_data);
+
+    // When
+    let result = deserialize_verifying_key(&serialized_proof);
+
+    // Then
+    assert_eq!(
+        Some("Expected a verifying key but the file contains a proof".to_string()),
+        result.err()
+    );
+}
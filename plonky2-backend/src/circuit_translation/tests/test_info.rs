@@ -0,0 +1,187 @@
+use super::factories::circuit_factory;
+use super::*;
+use crate::actions::info_action::InfoAction;
+use crate::backend::Plonky2Backend;
+use crate::field_conversion::FieldConversionMode;
+
+/// Tests for the report of the info command, which follows nargo's `info --json` output.
+
+fn _info_action() -> InfoAction {
+    InfoAction {
+        acir_program_json_path: String::from("target/x_plus_4.json"),
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge: false,
+        sha256_implementation: Sha256Implementation::default(),
+    }
+}
+
+fn _x_plus_4_squared_equals_25_program_json() -> serde_json::Value {
+    circuit_factory::program_json(
+        &circuit_factory::x_plus_4_squared_equals_25_program(),
+        circuit_factory::public_field_parameter_abi("x"),
+    )
+}
+
+#[test]
+fn test_translation_records_the_gates_added_by_each_opcode() {
+    // Given
+    let circuit = circuit_factory::circuit_with_a_public_input_and_two_assert_zero_operands(
+        Witness(0),
+        Witness(1),
+    );
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+
+    // Then
+    assert_eq!(circuit.opcodes.len(), translator.opcode_gate_counts.len());
+    assert!(translator.opcode_gate_counts.iter().sum::<usize>() <= translator.builder.num_gates());
+}
+
+#[test]
+fn test_info_report_has_the_json_shape_of_nargo_info() {
+    // When
+    let report = _info_action().report_for_program_json(&_x_plus_4_squared_equals_25_program_json());
+
+    // Then
+    let json = serde_json::to_value(&report).unwrap();
+    let program = &json["programs"][0];
+    assert_eq!("x_plus_4", program["package_name"]);
+    let function = &program["functions"][0];
+    assert_eq!("main", function["name"]);
+    assert_eq!(2, function["acir_opcodes"]);
+    assert!(function["circuit_size"].is_u64());
+    assert_eq!(2, function["gates_per_opcode"].as_array().unwrap().len());
+    for plonky2_field in [
+        "degree",
+        "degree_bits",
+        "gates",
+        "num_public_inputs",
+        "num_virtual_targets",
+        "num_lookup_tables",
+    ] {
+        assert!(
+            !function["plonky2"][plonky2_field].is_null(),
+            "Missing field {}",
+            plonky2_field
+        );
+    }
+}
+
+#[test]
+fn test_info_report_matches_the_built_circuit() {
+    // When
+    let report = _info_action().report_for_program_json(&_x_plus_4_squared_equals_25_program_json());
+
+    // Then
+    let function = &report.programs[0].functions[0];
+    assert_eq!(function.plonky2.degree, function.circuit_size);
+    assert_eq!(1 << function.plonky2.degree_bits, function.plonky2.degree);
+    assert_eq!(1, function.plonky2.num_public_inputs);
+    let assert_zero_cost = &function.opcode_kinds["AssertZero"];
+    assert_eq!(2, assert_zero_cost.opcodes);
+    assert_eq!(
+        function.gates_per_opcode.iter().sum::<usize>(),
+        assert_zero_cost.gates
+    );
+}
+
+#[test]
+fn test_gate_histogram_counts_every_row_of_the_circuit() {
+    // When
+    let report = _info_action().report_for_program_json(&_x_plus_4_squared_equals_25_program_json());
+
+    // Then
+    let plonky2_info = &report.programs[0].functions[0].plonky2;
+    assert_eq!(plonky2_info.degree, plonky2_info.gates.values().sum::<usize>());
+    assert_eq!(Some(&1), plonky2_info.gates.get("PublicInputGate"));
+}
+
+#[test]
+fn test_info_reports_every_function_of_the_program() {
+    // Given
+    let main_circuit = circuit_factory::circuit_with_a_public_input_and_two_assert_zero_operands(
+        Witness(0),
+        Witness(1),
+    );
+    let other_circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_equals_4_opcode(Witness(0)),
+        vec![Witness(0)],
+    );
+    let program = Program {
+        functions: vec![main_circuit, other_circuit],
+        unconstrained_functions: vec![],
+    };
+    let mut program_json = circuit_factory::program_json(&program, serde_json::json!({}));
+    program_json["names"] = serde_json::json!(["main", "other"]);
+
+    // When
+    let report = _info_action().report_for_program_json(&program_json);
+
+    // Then
+    let functions = &report.programs[0].functions;
+    assert_eq!(2, functions.len());
+    assert_eq!("main", functions[0].name);
+    assert_eq!(2, functions[0].acir_opcodes);
+    assert_eq!("other", functions[1].name);
+    assert_eq!(1, functions[1].acir_opcodes);
+}
+
+#[test]
+fn test_range_checks_are_attributed_to_the_opcode_that_requested_them() {
+    // Given
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::black_box_range_opcode(Witness(0), 32),
+        vec![Witness(0)],
+    );
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+
+    // Then
+    assert!(translator.opcode_gate_counts[0] > 0);
+    assert_eq!(translator.builder.num_gates(), translator.opcode_gate_counts[0]);
+}
+
+#[test]
+fn test_info_report_accounts_for_every_row_of_the_circuit() {
+    // When
+    let report = _info_action().report_for_program_json(&_x_plus_4_squared_equals_25_program_json());
+
+    // Then
+    let function = &report.programs[0].functions[0];
+    assert!(function.unattributed_gates > 0);
+    assert_eq!(
+        function.circuit_size,
+        function.gates_per_opcode.iter().sum::<usize>() + function.unattributed_gates
+    );
+}
+
+#[test]
+fn test_info_reports_the_circuit_that_prove_builds_with_the_same_options() {
+    // Given
+    let program_json = _x_plus_4_squared_equals_25_program_json();
+    let backend = Plonky2Backend {
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge: true,
+        sha256_implementation: Sha256Implementation::default(),
+    };
+    let info_action = InfoAction {
+        acir_program_json_path: String::from("target/x_plus_4.json"),
+        field_conversion_mode: backend.field_conversion_mode,
+        zero_knowledge: backend.zero_knowledge,
+        sha256_implementation: backend.sha256_implementation,
+    };
+
+    // When
+    let report = info_action.report_for_program_json(&program_json);
+
+    // Then
+    let compiled_circuit = backend.compile_program_json(&program_json);
+    assert_eq!(
+        compiled_circuit.circuit_data.common.degree(),
+        report.programs[0].functions[0].circuit_size
+    );
+}
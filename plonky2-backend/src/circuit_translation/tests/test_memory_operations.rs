@@ -1,7 +1,9 @@
 use super::*;
 use crate::circuit_translation::tests::factories::utils::*;
+use crate::circuit_translation::witness_generators::BrilligOpcode;
+use acir::brillig::MemoryAddress;
 use acir::circuit::opcodes::BlockId;
-use acir::circuit::opcodes::BlockType::Memory;
+use acir::circuit::opcodes::BlockType::{CallData, Memory, ReturnData};
 use acir::circuit::{ExpressionWidth, PublicInputs};
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use std::collections::BTreeSet;
@@ -116,6 +118,446 @@ fn test_backend_supports_creation_of_memory_blocks_with_irregular_size() {
     assert!(circuit_data.verify(proof).is_ok());
 }
 
+#[test]
+fn test_backend_resolves_memory_operations_with_constant_indices() {
+    // fn main(mut x: pub [Field; 2], v: pub Field){
+    //     x[1] = v;
+    //     assert(x[1] == x[0]);
+    // }
+
+    //Given
+    let array_input_witnesses = vec![Witness(0), Witness(1)];
+    let value_input_witness = Witness(2);
+    let circuit =
+        _memory_constant_index_circuit(array_input_witnesses.clone(), value_input_witness);
+
+    // When
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    let seven = F::from_canonical_u64(7);
+    let eight = F::from_canonical_u64(8);
+    let proof = generate_plonky2_proof_using_witness_values(
+        vec![
+            (array_input_witnesses[0], seven),
+            (array_input_witnesses[1], eight),
+            (value_input_witness, seven),
+        ],
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_constant_index_memory_operations_do_not_add_random_access_gates() {
+    //Given
+    let array_input_witnesses = vec![Witness(0), Witness(1)];
+    let value_input_witness = Witness(2);
+    let circuit =
+        _memory_constant_index_circuit(array_input_witnesses.clone(), value_input_witness);
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+
+    //Then
+    assert_eq!(0, translator.opcode_gate_counts[1]);
+    assert_eq!(0, translator.opcode_gate_counts[2]);
+}
+
+#[test]
+#[should_panic(expected = "Memory access out of bounds")]
+fn test_backend_rejects_constant_indices_out_of_the_memory_block() {
+    //Given
+    let array_input_witnesses = vec![Witness(0), Witness(1)];
+    let mut circuit = _memory_constant_index_circuit(array_input_witnesses, Witness(2));
+    circuit.opcodes[2] = Opcode::MemoryOp {
+        block_id: BlockId(0),
+        op: MemOp {
+            operation: expression_read(),
+            index: expression_constant(2),
+            value: expression_witness(Witness(3)),
+        },
+        predicate: None,
+    };
+
+    // When
+    generate_plonky2_circuit_from_acir_circuit(&circuit);
+}
+
+#[test]
+#[should_panic(expected = "Memory access out of bounds")]
+fn test_backend_rejects_constant_indices_that_only_fit_in_the_block_when_truncated() {
+    //Given
+    let array_input_witnesses = vec![Witness(0), Witness(1)];
+    let mut circuit = _memory_constant_index_circuit(array_input_witnesses, Witness(2));
+    // 2^128 + 1 is 1 once truncated to a u128
+    let mut index = expression_constant(u128::MAX);
+    index.q_c += FieldElement::from(2_u128);
+    circuit.opcodes[2] = Opcode::MemoryOp {
+        block_id: BlockId(0),
+        op: MemOp {
+            operation: expression_read(),
+            index,
+            value: expression_witness(Witness(3)),
+        },
+        predicate: None,
+    };
+
+    // When
+    generate_plonky2_circuit_from_acir_circuit(&circuit);
+}
+
+#[test]
+fn test_backend_supports_memory_operations_with_expressions_as_index_and_value() {
+    // fn main(mut x: pub [Field; 2], y: pub Field, v: pub Field){
+    //     x[y + 1] = 2 * v + 1;
+    //     assert(x[y] + 4 == x[y + 1]);
+    // }
+
+    //Given
+    let array_input_witnesses = vec![Witness(0), Witness(1)];
+    let index_input_witness = Witness(2);
+    let value_input_witness = Witness(3);
+    let circuit = _memory_expression_index_and_value_circuit(
+        array_input_witnesses.clone(),
+        index_input_witness,
+        value_input_witness,
+    );
+
+    // When
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    let zero = F::from_canonical_u64(0);
+    let five = F::from_canonical_u64(5);
+    let nine = F::from_canonical_u64(9);
+    let proof = generate_plonky2_proof_using_witness_values(
+        vec![
+            (array_input_witnesses[0], five),
+            (array_input_witnesses[1], zero),
+            (index_input_witness, zero),
+            (value_input_witness, F::from_canonical_u64(4)),
+            (Witness(4), five),
+            (Witness(5), nine),
+        ],
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_backend_translates_long_memory_blocks_with_memory_checking() {
+    // fn main(mut x: pub [Field; 100], y: pub Field, v: pub Field){
+    //     x[y] = v;
+    //     assert(x[y] == v);
+    //     assert(x[0] == 0);
+    // }
+
+    //Given
+    let circuit = _long_memory_block_write_and_read_circuit(100);
+    let witness_values = _long_memory_block_witness_values(100, 7, 42, 42);
+
+    // When
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    let proof = generate_plonky2_proof_using_witness_values(
+        witness_values,
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_memory_checking_rejects_reading_an_overwritten_value() {
+    //Given
+    let circuit = _long_memory_block_write_and_read_circuit(100);
+    // The value read at position 7 is the one it was initialized with instead of 42
+    let witness_values = _long_memory_block_witness_values(100, 7, 42, 7);
+
+    // When
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let proof = generate_plonky2_proof_using_witness_values(
+            witness_values,
+            &witness_target_map,
+            &circuit_data,
+        );
+        circuit_data.verify(proof).unwrap();
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_memory_checking_generates_reads_of_values_written_from_previous_reads() {
+    // fn main(mut x: pub [Field; 100], y: pub Field){
+    //     x[y] = x[y] + 1;
+    //     assert(x[y] == y + 1);
+    // }
+
+    //Given
+    let length = 100;
+    let circuit = _long_memory_block_increment_circuit(length);
+    let mut witness_values: Vec<(Witness, F)> = (0..length)
+        .map(|position| (Witness(position), F::from_canonical_u32(position)))
+        .collect();
+    witness_values.push((Witness(length), F::from_canonical_u32(7)));
+
+    // When
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    let proof = generate_plonky2_proof_using_witness_values(
+        witness_values,
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_memory_checking_can_be_used_for_short_memory_blocks() {
+    //Given
+    let array_input_witnesses = vec![Witness(0), Witness(1)];
+    let index_input_witness = Witness(2);
+    let value_input_witness = Witness(3);
+    let circuit = _memory_simple_write_circuit(
+        array_input_witnesses.clone(),
+        index_input_witness,
+        value_input_witness,
+    );
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.memory_checking_threshold = 0;
+    translator.translate_circuit(&circuit);
+    let (circuit_data, witness_target_map) = translator.unpack();
+
+    //Then
+    let zero = F::from_canonical_u64(0);
+    let one = F::from_canonical_u64(1);
+    let eleven = F::from_canonical_u64(11);
+    let proof = generate_plonky2_proof_using_witness_values(
+        vec![
+            (array_input_witnesses[0], F::from_canonical_u64(10)),
+            (array_input_witnesses[1], eleven),
+            (index_input_witness, zero),
+            (value_input_witness, one),
+            (Witness(4), zero),
+            (Witness(5), one),
+            (Witness(6), one),
+            (Witness(7), eleven),
+        ],
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_memory_checking_reduces_the_gates_of_writing_a_long_memory_block() {
+    //Given
+    let circuit = _long_memory_block_many_writes_circuit(100);
+
+    // When
+    let mut translator_with_memory_checking = CircuitBuilderFromAcirToPlonky2::new();
+    translator_with_memory_checking.translate_circuit(&circuit);
+    let mut translator_without_memory_checking = CircuitBuilderFromAcirToPlonky2::new();
+    translator_without_memory_checking.memory_checking_threshold = usize::MAX;
+    translator_without_memory_checking.translate_circuit(&circuit);
+
+    //Then
+    let gates_with_memory_checking = translator_with_memory_checking.builder.num_gates();
+    let gates_without_memory_checking = translator_without_memory_checking.builder.num_gates();
+    assert!(gates_with_memory_checking < gates_without_memory_checking);
+}
+
+#[test]
+fn test_brillig_call_reads_the_current_values_of_a_memory_checked_block() {
+    //Given
+    let length = 100;
+    let program = _brillig_call_reading_a_long_memory_block_program(length);
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_program(&program);
+
+    // When
+    let mut witness_assignment: Vec<(Witness, F)> = (0..length)
+        .map(|position| (Witness(position), F::from_canonical_u32(position)))
+        .collect();
+    witness_assignment.push((Witness(length), F::from_canonical_u64(77)));
+    witness_assignment.push((Witness(length + 2), F::from_canonical_u64(2)));
+    let proof = generate_plonky2_proof_using_witness_values(
+        witness_assignment,
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    //Then
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_long_memory_blocks_that_are_only_read_are_split_into_sub_blocks() {
+    // fn main(x: pub [Field; 300], y: pub Field){
+    //     assert(x[y] == y);
+    // }
+
+    //Given
+    let length = 300;
+    let circuit = _long_memory_block_read_circuit(length);
+    let mut witness_values: Vec<(Witness, F)> = (0..length)
+        .map(|position| (Witness(position), F::from_canonical_u32(position)))
+        .collect();
+    witness_values.push((Witness(length), F::from_canonical_u32(250)));
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translate_circuit(&circuit);
+
+    //Then
+    assert!(translator.memory_traces.is_empty());
+    let (block, block_length) = &translator.memory_blocks[&BlockId(0)];
+    assert_eq!(300, *block_length);
+    assert_eq!(320, block.len());
+    let (circuit_data, witness_target_map) = translator.unpack();
+    let proof = generate_plonky2_proof_using_witness_values(
+        witness_values,
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_max_random_access_length_of_the_circuit_config() {
+    let config = CircuitBuilderFromAcirToPlonky2::circuit_config(false);
+    assert_eq!(64, MemoryOperationsTranslator::max_random_access_length(&config));
+}
+
+#[test]
+fn test_memory_blocks_longer_than_a_random_access_gate_are_padded_per_sub_block() {
+    //Given
+    let circuit = _long_memory_block_write_and_read_circuit(300);
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.memory_checking_threshold = usize::MAX;
+    translator.translate_circuit(&circuit);
+
+    //Then
+    let (block, block_length) = &translator.memory_blocks[&BlockId(0)];
+    assert_eq!(300, *block_length);
+    assert_eq!(320, block.len());
+}
+
+#[test]
+fn test_backend_reads_memory_blocks_longer_than_a_random_access_gate() {
+    //Given
+    let circuit = _long_memory_block_write_and_read_circuit(300);
+    let witness_values = _long_memory_block_witness_values(300, 250, 42, 42);
+
+    // When
+    let (circuit_data, witness_target_map) =
+        _generate_plonky2_circuit_without_memory_checking(&circuit);
+
+    //Then
+    let proof = generate_plonky2_proof_using_witness_values(
+        witness_values,
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_backend_reads_memory_blocks_with_more_sub_blocks_than_a_random_access_gate() {
+    //Given
+    let length = 64 * 64 + 100;
+    let circuit = _long_memory_block_write_and_read_circuit(length);
+    let witness_values = _long_memory_block_witness_values(length, 4150, 42, 42);
+
+    // When
+    let (circuit_data, witness_target_map) =
+        _generate_plonky2_circuit_without_memory_checking(&circuit);
+
+    //Then
+    let proof = generate_plonky2_proof_using_witness_values(
+        witness_values,
+        &witness_target_map,
+        &circuit_data,
+    );
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_reading_a_long_memory_block_rejects_a_wrong_value() {
+    //Given
+    let circuit = _long_memory_block_write_and_read_circuit(300);
+    // The value read at position 250 is the one it was initialized with instead of 42
+    let witness_values = _long_memory_block_witness_values(300, 250, 42, 250);
+
+    // When
+    let (circuit_data, witness_target_map) =
+        _generate_plonky2_circuit_without_memory_checking(&circuit);
+
+    //Then
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let proof = generate_plonky2_proof_using_witness_values(
+            witness_values,
+            &witness_target_map,
+            &circuit_data,
+        );
+        circuit_data.verify(proof).unwrap();
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_databus_blocks_are_public_inputs_after_the_return_values() {
+    // fn main(x: call_data [Field; 2], idx: Field) -> return_data Field {
+    //     x[idx] * 2
+    // }
+
+    //Given
+    let call_data_witnesses = vec![Witness(0), Witness(1)];
+    let index_input_witness = Witness(2);
+    let circuit = _databus_circuit(call_data_witnesses.clone(), index_input_witness);
+
+    // When
+    let (circuit_data, witness_target_map) = generate_plonky2_circuit_from_acir_circuit(&circuit);
+
+    //Then
+    let one = F::from_canonical_u64(1);
+    let seven = F::from_canonical_u64(7);
+    let nine = F::from_canonical_u64(9);
+    let eighteen = F::from_canonical_u64(18);
+    let proof = generate_plonky2_proof_using_witness_values(
+        vec![
+            (call_data_witnesses[0], seven),
+            (call_data_witnesses[1], nine),
+            (index_input_witness, one),
+            (Witness(3), nine),
+            (Witness(4), eighteen),
+        ],
+        &witness_target_map,
+        &circuit_data,
+    );
+    // The return data witness is also a return value, so it's only exposed once
+    assert_eq!(
+        vec![Witness(4), Witness(0), Witness(1)],
+        crate::circuit_translation::public_input_witnesses(&circuit)
+    );
+    assert_eq!(vec![eighteen, seven, nine], proof.public_inputs);
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
 // Test less or equal
 
 #[test]
@@ -360,8 +802,484 @@ fn _memory_simple_write_circuit(
     }
 }
 
-fn expression_write() -> Expression {
-    Expression {
+fn _memory_constant_index_circuit(
+    array_input_witnesses: Vec<Witness>,
+    value_input_witness: Witness,
+) -> Circuit {
+    // INIT (id: 0, len: 2)
+    // MEM (id: 0, write x2 at: 1)
+    // MEM (id: 0, read at: 1, value: x0)
+    Circuit {
+        current_witness_index: 0,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: array_input_witnesses.clone(),
+                block_type: Memory,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_write(),
+                    index: expression_constant(1),
+                    value: expression_witness(value_input_witness),
+                },
+                predicate: None,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_constant(1),
+                    value: expression_witness(array_input_witnesses[0]),
+                },
+                predicate: None,
+            },
+        ],
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(vec![
+            array_input_witnesses[0],
+            array_input_witnesses[1],
+            value_input_witness,
+        ])),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+fn _memory_expression_index_and_value_circuit(
+    array_input_witnesses: Vec<Witness>,
+    index_input_witness: Witness,
+    value_input_witness: Witness,
+) -> Circuit {
+    // INIT (id: 0, len: 2)
+    // MEM (id: 0, write EXPR [ (2, _3) 1 ] at: EXPR [ (1, _2) 1 ])
+    // MEM (id: 0, read at: x2, value: x4)
+    // MEM (id: 0, read at: EXPR [ (1, _2) 1 ], value: x5)
+    // EXPR [ (1, _4) (-1, _5) 4 ]
+    let index_plus_one = Expression {
+        mul_terms: Vec::new(),
+        linear_combinations: vec![(FieldElement::one(), index_input_witness)],
+        q_c: FieldElement::one(),
+    };
+    Circuit {
+        current_witness_index: 0,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: array_input_witnesses.clone(),
+                block_type: Memory,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_write(),
+                    index: index_plus_one.clone(),
+                    value: Expression {
+                        mul_terms: Vec::new(),
+                        linear_combinations: vec![(FieldElement::from(2u128), value_input_witness)],
+                        q_c: FieldElement::one(),
+                    },
+                },
+                predicate: None,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(4)),
+                },
+                predicate: None,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: index_plus_one,
+                    value: expression_witness(Witness(5)),
+                },
+                predicate: None,
+            },
+            Opcode::AssertZero(Expression {
+                mul_terms: Vec::new(),
+                linear_combinations: vec![
+                    (FieldElement::one(), Witness(4)),
+                    (-FieldElement::one(), Witness(5)),
+                ],
+                q_c: FieldElement::from(4u128),
+            }),
+        ],
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(vec![
+            array_input_witnesses[0],
+            array_input_witnesses[1],
+            index_input_witness,
+            value_input_witness,
+        ])),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+fn _long_memory_block_write_and_read_circuit(length: u32) -> Circuit {
+    // INIT (id: 0, len: length)
+    // MEM (id: 0, write x(length + 1) at: x(length))
+    // MEM (id: 0, read at: x(length), value: x(length + 2))
+    // EXPR [ (1, _(length + 1)) (-1, _(length + 2)) 0 ]
+    // MEM (id: 0, read at: 0, value: x(length + 3))
+    // EXPR [ (1, _(length + 3)) 0 ]
+    let array_input_witnesses: Vec<Witness> = (0..length).map(Witness).collect();
+    let index_input_witness = Witness(length);
+    let value_input_witness = Witness(length + 1);
+    let mut public_parameters = array_input_witnesses.clone();
+    public_parameters.extend([index_input_witness, value_input_witness]);
+    Circuit {
+        current_witness_index: 0,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: array_input_witnesses,
+                block_type: Memory,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_write(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(value_input_witness),
+                },
+                predicate: None,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(length + 2)),
+                },
+                predicate: None,
+            },
+            Opcode::AssertZero(Expression {
+                mul_terms: Vec::new(),
+                linear_combinations: vec![
+                    (FieldElement::one(), value_input_witness),
+                    (-FieldElement::one(), Witness(length + 2)),
+                ],
+                q_c: FieldElement::zero(),
+            }),
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_constant(0),
+                    value: expression_witness(Witness(length + 3)),
+                },
+                predicate: None,
+            },
+            Opcode::AssertZero(expression_witness(Witness(length + 3))),
+        ],
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(public_parameters)),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+fn _long_memory_block_increment_circuit(length: u32) -> Circuit {
+    // INIT (id: 0, len: length)
+    // MEM (id: 0, read at: x(length), value: x(length + 1))
+    // EXPR [ (1, _(length + 2)) (-1, _(length + 1)) -1 ]
+    // MEM (id: 0, write x(length + 2) at: x(length))
+    // MEM (id: 0, read at: x(length), value: x(length + 3))
+    // EXPR [ (1, _(length + 3)) (-1, _(length)) -1 ]
+    let array_input_witnesses: Vec<Witness> = (0..length).map(Witness).collect();
+    let index_input_witness = Witness(length);
+    let mut public_parameters = array_input_witnesses.clone();
+    public_parameters.push(index_input_witness);
+    let plus_one = |result: Witness, operand: Witness| {
+        Opcode::AssertZero(Expression {
+            mul_terms: Vec::new(),
+            linear_combinations: vec![
+                (FieldElement::one(), result),
+                (-FieldElement::one(), operand),
+            ],
+            q_c: -FieldElement::one(),
+        })
+    };
+    Circuit {
+        current_witness_index: 0,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: array_input_witnesses,
+                block_type: Memory,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(length + 1)),
+                },
+                predicate: None,
+            },
+            plus_one(Witness(length + 2), Witness(length + 1)),
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_write(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(length + 2)),
+                },
+                predicate: None,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(length + 3)),
+                },
+                predicate: None,
+            },
+            plus_one(Witness(length + 3), index_input_witness),
+        ],
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(public_parameters)),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+fn _long_memory_block_read_circuit(length: u32) -> Circuit {
+    // INIT (id: 0, len: length)
+    // MEM (id: 0, read at: x(length), value: x(length + 1))
+    // EXPR [ (1, _(length + 1)) (-1, _(length)) 0 ]
+    let array_input_witnesses: Vec<Witness> = (0..length).map(Witness).collect();
+    let index_input_witness = Witness(length);
+    let mut public_parameters = array_input_witnesses.clone();
+    public_parameters.push(index_input_witness);
+    Circuit {
+        current_witness_index: 0,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: array_input_witnesses,
+                block_type: Memory,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(length + 1)),
+                },
+                predicate: None,
+            },
+            Opcode::AssertZero(Expression {
+                mul_terms: Vec::new(),
+                linear_combinations: vec![
+                    (FieldElement::one(), Witness(length + 1)),
+                    (-FieldElement::one(), index_input_witness),
+                ],
+                q_c: FieldElement::zero(),
+            }),
+        ],
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(public_parameters)),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+/// Every block is kept as a vector of targets, however long it is.
+fn _generate_plonky2_circuit_without_memory_checking(
+    circuit: &Circuit,
+) -> (CircuitData<F, C, 2>, HashMap<Witness, Target>) {
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.memory_checking_threshold = usize::MAX;
+    translator.translate_circuit(circuit);
+    translator.unpack()
+}
+
+/// The array is initialized with its positions (x[i] = i)
+fn _long_memory_block_witness_values(
+    length: u32,
+    index: u64,
+    value_written: u64,
+    value_read: u64,
+) -> Vec<(Witness, F)> {
+    let mut witness_values: Vec<(Witness, F)> = (0..length)
+        .map(|position| (Witness(position), F::from_canonical_u32(position)))
+        .collect();
+    witness_values.extend([
+        (Witness(length), F::from_canonical_u64(index)),
+        (Witness(length + 1), F::from_canonical_u64(value_written)),
+        (Witness(length + 2), F::from_canonical_u64(value_read)),
+        (Witness(length + 3), F::from_canonical_u64(0)),
+    ]);
+    witness_values
+}
+
+fn _long_memory_block_many_writes_circuit(length: u32) -> Circuit {
+    // INIT (id: 0, len: length)
+    // MEM (id: 0, write x(length + 2i + 1) at: x(length + 2i)) for i in 0..length
+    let array_input_witnesses: Vec<Witness> = (0..length).map(Witness).collect();
+    let mut opcodes = vec![Opcode::MemoryInit {
+        block_id: BlockId(0),
+        init: array_input_witnesses.clone(),
+        block_type: Memory,
+    }];
+    opcodes.extend((0..length).map(|write| Opcode::MemoryOp {
+        block_id: BlockId(0),
+        op: MemOp {
+            operation: expression_write(),
+            index: expression_witness(Witness(length + 2 * write)),
+            value: expression_witness(Witness(length + 2 * write + 1)),
+        },
+        predicate: None,
+    }));
+    Circuit {
+        current_witness_index: 0,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes,
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(array_input_witnesses)),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+fn _brillig_call_reading_a_long_memory_block_program(length: u32) -> Program {
+    // INIT (id: 0, len: length)
+    // MEM (id: 0, write x(length) at: x(length + 2))
+    // BRILLIG CALL func 0: inputs: [MemoryArray(0)], outputs: [x(length + 1)]
+    // EXPR [ (1, x(length + 1)) (-1, x(length)) 0 ]
+    // where the Brillig function returns the position 2 of its calldata and x(length + 2) is 2
+    let array_input_witnesses: Vec<Witness> = (0..length).map(Witness).collect();
+    let (value, read_value, index) = (Witness(length), Witness(length + 1), Witness(length + 2));
+    let mut public_parameters = array_input_witnesses.clone();
+    public_parameters.extend([value, index]);
+    let opcodes = vec![
+        Opcode::MemoryInit {
+            block_id: BlockId(0),
+            init: array_input_witnesses,
+            block_type: Memory,
+        },
+        Opcode::MemoryOp {
+            block_id: BlockId(0),
+            op: MemOp {
+                operation: expression_write(),
+                index: expression_witness(index),
+                value: expression_witness(value),
+            },
+            predicate: None,
+        },
+        Opcode::BrilligCall {
+            id: 0,
+            inputs: vec![BrilligInputs::MemoryArray(BlockId(0))],
+            outputs: vec![BrilligOutputs::Simple(read_value)],
+            predicate: None,
+        },
+        Opcode::AssertZero(Expression {
+            mul_terms: Vec::new(),
+            linear_combinations: vec![
+                (FieldElement::one(), read_value),
+                (-FieldElement::one(), value),
+            ],
+            q_c: FieldElement::zero(),
+        }),
+    ];
+    let circuit = Circuit {
+        current_witness_index: length + 2,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes,
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from_iter(public_parameters)),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    };
+    let bytecode = vec![
+        BrilligOpcode::CalldataCopy {
+            destination_address: MemoryAddress(0),
+            size: length as usize,
+            offset: 0,
+        },
+        BrilligOpcode::Stop {
+            return_data_offset: 2,
+            return_data_size: 1,
+        },
+    ];
+    Program {
+        functions: vec![circuit],
+        unconstrained_functions: vec![BrilligBytecode { bytecode }],
+    }
+}
+
+fn _databus_circuit(call_data_witnesses: Vec<Witness>, index_input_witness: Witness) -> Circuit {
+    // private parameters indices : [0, 1, 2]
+    // return value indices : [4]
+    // INIT CALLDATA (id: 0, len: 2)
+    // MEM (id: 0, read at: x2, value: x3)
+    // EXPR [ (2, _3) (-1, _4) 0 ]
+    // INIT RETURNDATA (id: 1, len: 1)
+    let mut private_parameters = call_data_witnesses.clone();
+    private_parameters.push(index_input_witness);
+    Circuit {
+        current_witness_index: 4,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: call_data_witnesses,
+                block_type: CallData,
+            },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp {
+                    operation: expression_read(),
+                    index: expression_witness(index_input_witness),
+                    value: expression_witness(Witness(3)),
+                },
+                predicate: None,
+            },
+            Opcode::AssertZero(Expression {
+                mul_terms: Vec::new(),
+                linear_combinations: vec![
+                    (FieldElement::from(2u128), Witness(3)),
+                    (-FieldElement::one(), Witness(4)),
+                ],
+                q_c: FieldElement::zero(),
+            }),
+            Opcode::MemoryInit {
+                block_id: BlockId(1),
+                init: vec![Witness(4)],
+                block_type: ReturnData,
+            },
+        ],
+        private_parameters: BTreeSet::from_iter(private_parameters),
+        public_parameters: PublicInputs(BTreeSet::new()),
+        return_values: PublicInputs(BTreeSet::from([Witness(4)])),
+        assert_messages: Default::default(),
+        recursive: false,
+    }
+}
+
+fn expression_write() -> Expression {
+    Expression {
         mul_terms: Vec::new(),
         linear_combinations: Vec::new(),
         q_c: FieldElement::one(),
@@ -376,6 +1294,14 @@ fn expression_read() -> Expression {
     }
 }
 
+fn expression_constant(value: u128) -> Expression {
+    Expression {
+        mul_terms: Vec::new(),
+        linear_combinations: Vec::new(),
+        q_c: FieldElement::from(value),
+    }
+}
+
 fn expression_witness(witness: Witness) -> Expression {
     Expression {
         mul_terms: Vec::new(),
@@ -33,12 +33,57 @@ fn test_noir_program(program_name: &str) {
         witness_assignment.push((witness, F::from_canonical_u64(value.try_to_u64().unwrap())));
     }
 
-    // utils::check_linked_output_targets_property(&circuit, &witness_target_map);
+    utils::check_linked_output_targets_property(&circuit, &circuit_data, &witness_target_map);
     let proof = utils::generate_plonky2_proof_using_witness_values(
-        witness_assignment,
+        witness_assignment.clone(),
         &witness_target_map,
         &circuit_data,
     );
 
+    utils::check_public_inputs_match_witness_values(&circuit, &proof, &witness_assignment);
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[parameterized(program_name = {
+    "basic_memory_write",
+    "assert_x_equals_5",
+    "node_guardians_example",
+    "array_dynamic",
+    "1_mul",
+    "3_add",
+    "5_over",
+    "7_function",
+    "sha256_4",
+    "ecdsa_secp256k1",
+})]
+fn test_noir_program_proven_from_its_parameters(program_name: &str) {
+    let (program, mut witnesses) =
+        circuit_parser::precompiled_program_and_witnesses_with_name(program_name);
+    let circuit = &program.functions[0];
+    let witness_mapping = witnesses.pop().unwrap().witness;
+
+    // When
+    let (circuit_data, witness_target_map) =
+        utils::generate_plonky2_circuit_from_acir_program(&program);
+
+    //Then
+    let mut witness_assignment: Vec<(Witness, F)> = vec![];
+    for (witness, value) in witness_mapping {
+        witness_assignment.push((witness, F::from_canonical_u64(value.try_to_u64().unwrap())));
+    }
+    let parameters = circuit.circuit_arguments();
+    let parameter_assignment: Vec<(Witness, F)> = witness_assignment
+        .iter()
+        .filter(|(witness, _)| parameters.contains(witness))
+        .cloned()
+        .collect();
+
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        parameter_assignment,
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    utils::check_public_inputs_match_witness_values(circuit, &proof, &witness_assignment);
     assert!(circuit_data.verify(proof).is_ok());
 }
@@ -0,0 +1,77 @@
+use super::factories::circuit_factory::*;
+use super::*;
+use crate::actions::profile_action::ProfileAction;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_debug_info::{ProgramDebugInfo, SourceLocation};
+
+/// Tests for the attribution of the gates of the Plonky2 circuit to the Noir source code.
+
+#[test]
+fn test_debug_info_maps_opcodes_to_noir_source_locations() {
+    // Given
+    let debug_info = ProgramDebugInfo::from_program_json(&program_json_with_debug_symbols());
+
+    // When
+    let call_stack = debug_info.opcode_call_stack(0, 1);
+
+    // Then
+    assert_eq!(
+        vec![SourceLocation {
+            path: String::from("src/main.nr"),
+            line: 3,
+            column: 5,
+            snippet: String::from("assert(y * y == 25)"),
+        }],
+        call_stack
+    );
+    assert!(debug_info.opcode_call_stack(0, 2).is_empty());
+}
+
+#[test]
+fn test_profiler_attributes_gates_to_noir_source_locations() {
+    // Given
+    let circuit =
+        circuit_with_a_public_input_and_two_assert_zero_operands(Witness(0), Witness(1));
+    let debug_info = ProgramDebugInfo::from_program_json(&program_json_with_debug_symbols());
+    let opcode_gate_counts = vec![3, 2];
+
+    // When
+    let folded_stacks =
+        ProfileAction::folded_stacks("main", &circuit, &opcode_gate_counts, 11, &debug_info, 0);
+
+    // Then
+    assert_eq!(
+        "main;src/main.nr:2:13::x - 4;AssertZero 3\n\
+         main;src/main.nr:3:5::assert(y * y == 25);AssertZero 2\n\
+         main;unattributed 11\n",
+        folded_stacks
+    );
+}
+
+#[test]
+fn test_profiler_reports_every_function_of_the_program() {
+    // Given
+    let main_circuit =
+        circuit_with_a_public_input_and_two_assert_zero_operands(Witness(0), Witness(1));
+    let other_circuit = circuit_with_single_opcode(x_equals_4_opcode(Witness(0)), vec![Witness(0)]);
+    let program = Program {
+        functions: vec![main_circuit, other_circuit],
+        unconstrained_functions: vec![],
+    };
+    let mut program_json = program_json(&program, serde_json::json!({}));
+    program_json["names"] = serde_json::json!(["main", "other"]);
+    let profile_action = ProfileAction {
+        acir_program_json_path: String::new(),
+        resulting_folded_stacks_file_path: String::new(),
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge: false,
+        sha256_implementation: Sha256Implementation::default(),
+    };
+
+    // When
+    let folded_stacks = profile_action.folded_stacks_of_program_json(&program_json);
+
+    // Then
+    assert!(folded_stacks.lines().any(|stack| stack.starts_with("main;")));
+    assert!(folded_stacks.lines().any(|stack| stack.starts_with("other;")));
+}
@@ -2,6 +2,7 @@ use acir::circuit::opcodes::BlackBoxFuncCall::Sha256Compression;
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 
 use super::*;
+use crate::backend::Plonky2Backend;
 use crate::binary_digits_target::BinaryDigitsTarget;
 use crate::circuit_translation::tests::factories::circuit_factory::circuit_with_single_opcode;
 use crate::circuit_translation::tests::factories::utils;
@@ -319,8 +320,8 @@ fn test_add_module_32_bits(input_values_1: Vec<F>, input_values_2: Vec<F>, outpu
         .map(|_| circuit_builder.add_virtual_bool_target_unsafe())
         .collect();
 
-    let binary_input_1 = BinaryDigitsTarget { bits: bits_1 };
-    let binary_input_2 = BinaryDigitsTarget { bits: bits_2 };
+    let binary_input_1 = BinaryDigitsTarget::from_bits(bits_1);
+    let binary_input_2 = BinaryDigitsTarget::from_bits(bits_2);
     let result_bits = BinaryDigitsTarget::add_module_32_bits(
         &binary_input_1,
         &binary_input_2,
@@ -329,9 +330,18 @@ fn test_add_module_32_bits(input_values_1: Vec<F>, input_values_2: Vec<F>, outpu
 
     let mut partial_witnesses = PartialWitness::<F>::new();
     for i in 0..32 {
-        partial_witnesses.set_target(binary_input_1.bits[i].target, input_values_1[i]);
-        partial_witnesses.set_target(binary_input_2.bits[i].target, input_values_2[i]);
-        partial_witnesses.set_target(result_bits.bits[i].target, output_values[i]);
+        partial_witnesses.set_target(
+            binary_input_1.bits(&mut circuit_builder)[i].target,
+            input_values_1[i],
+        );
+        partial_witnesses.set_target(
+            binary_input_2.bits(&mut circuit_builder)[i].target,
+            input_values_2[i],
+        );
+        partial_witnesses.set_target(
+            result_bits.bits(&mut circuit_builder)[i].target,
+            output_values[i],
+        );
     }
 
     let circuit_data = circuit_builder.build::<C>();
@@ -362,9 +372,9 @@ fn test_majority(
         .map(|_| circuit_builder.add_virtual_bool_target_unsafe())
         .collect();
 
-    let binary_input_0 = BinaryDigitsTarget { bits: bits_0 };
-    let binary_input_1 = BinaryDigitsTarget { bits: bits_1 };
-    let binary_input_2 = BinaryDigitsTarget { bits: bits_2 };
+    let binary_input_0 = BinaryDigitsTarget::from_bits(bits_0);
+    let binary_input_1 = BinaryDigitsTarget::from_bits(bits_1);
+    let binary_input_2 = BinaryDigitsTarget::from_bits(bits_2);
     let chosen_bits = BinaryDigitsTarget::majority(
         &binary_input_0,
         &binary_input_1,
@@ -374,10 +384,22 @@ fn test_majority(
 
     let mut partial_witnesses = PartialWitness::<F>::new();
     for i in 0..size {
-        partial_witnesses.set_target(binary_input_0.bits[i].target, input_values_0[i]);
-        partial_witnesses.set_target(binary_input_1.bits[i].target, input_values_1[i]);
-        partial_witnesses.set_target(binary_input_2.bits[i].target, input_values_2[i]);
-        partial_witnesses.set_target(chosen_bits.bits[i].target, output_values[i]);
+        partial_witnesses.set_target(
+            binary_input_0.bits(&mut circuit_builder)[i].target,
+            input_values_0[i],
+        );
+        partial_witnesses.set_target(
+            binary_input_1.bits(&mut circuit_builder)[i].target,
+            input_values_1[i],
+        );
+        partial_witnesses.set_target(
+            binary_input_2.bits(&mut circuit_builder)[i].target,
+            input_values_2[i],
+        );
+        partial_witnesses.set_target(
+            chosen_bits.bits(&mut circuit_builder)[i].target,
+            output_values[i],
+        );
     }
 
     let circuit_data = circuit_builder.build::<C>();
@@ -408,9 +430,9 @@ fn test_choose(
         .map(|_| circuit_builder.add_virtual_bool_target_unsafe())
         .collect();
 
-    let binary_chooser = BinaryDigitsTarget { bits: bits_chooser };
-    let binary_input_1 = BinaryDigitsTarget { bits: bits_1 };
-    let binary_input_2 = BinaryDigitsTarget { bits: bits_2 };
+    let binary_chooser = BinaryDigitsTarget::from_bits(bits_chooser);
+    let binary_input_1 = BinaryDigitsTarget::from_bits(bits_1);
+    let binary_input_2 = BinaryDigitsTarget::from_bits(bits_2);
     let chosen_bits = BinaryDigitsTarget::choose(
         &binary_chooser,
         &binary_input_1,
@@ -420,10 +442,22 @@ fn test_choose(
 
     let mut partial_witnesses = PartialWitness::<F>::new();
     for i in 0..size {
-        partial_witnesses.set_target(binary_chooser.bits[i].target, chooser_values[i]);
-        partial_witnesses.set_target(binary_input_1.bits[i].target, input_values_1[i]);
-        partial_witnesses.set_target(binary_input_2.bits[i].target, input_values_2[i]);
-        partial_witnesses.set_target(chosen_bits.bits[i].target, output_values[i]);
+        partial_witnesses.set_target(
+            binary_chooser.bits(&mut circuit_builder)[i].target,
+            chooser_values[i],
+        );
+        partial_witnesses.set_target(
+            binary_input_1.bits(&mut circuit_builder)[i].target,
+            input_values_1[i],
+        );
+        partial_witnesses.set_target(
+            binary_input_2.bits(&mut circuit_builder)[i].target,
+            input_values_2[i],
+        );
+        partial_witnesses.set_target(
+            chosen_bits.bits(&mut circuit_builder)[i].target,
+            output_values[i],
+        );
     }
 
     let circuit_data = circuit_builder.build::<C>();
@@ -440,13 +474,19 @@ fn test_rotate_right(n: usize, size: usize, input_values: Vec<F>, output_values:
         .map(|_| circuit_builder.add_virtual_bool_target_unsafe())
         .collect();
 
-    let binary_input = BinaryDigitsTarget { bits };
-    let rotated_bits = BinaryDigitsTarget::rotate_right(&binary_input, n, &mut circuit_builder);
+    let binary_input = BinaryDigitsTarget::from_bits(bits);
+    let rotated_bits = binary_input.rotate_right(n);
 
     let mut partial_witnesses = PartialWitness::<F>::new();
     for i in 0..size {
-        partial_witnesses.set_target(binary_input.bits[i].target, input_values[i]);
-        partial_witnesses.set_target(rotated_bits.bits[i].target, output_values[i]);
+        partial_witnesses.set_target(
+            binary_input.bits(&mut circuit_builder)[i].target,
+            input_values[i],
+        );
+        partial_witnesses.set_target(
+            rotated_bits.bits(&mut circuit_builder)[i].target,
+            output_values[i],
+        );
     }
 
     let circuit_data = circuit_builder.build::<C>();
@@ -463,13 +503,19 @@ fn test_shift_right(n: usize, size: usize, input_values: Vec<F>, output_values:
         .map(|_| circuit_builder.add_virtual_bool_target_unsafe())
         .collect();
 
-    let binary_input = BinaryDigitsTarget { bits };
-    let rotated_bits = BinaryDigitsTarget::shift_right(&binary_input, n, &mut circuit_builder);
+    let binary_input = BinaryDigitsTarget::from_bits(bits);
+    let rotated_bits = binary_input.shift_right(n);
 
     let mut partial_witnesses = PartialWitness::<F>::new();
     for i in 0..size {
-        partial_witnesses.set_target(binary_input.bits[i].target, input_values[i]);
-        partial_witnesses.set_target(rotated_bits.bits[i].target, output_values[i]);
+        partial_witnesses.set_target(
+            binary_input.bits(&mut circuit_builder)[i].target,
+            input_values[i],
+        );
+        partial_witnesses.set_target(
+            rotated_bits.bits(&mut circuit_builder)[i].target,
+            output_values[i],
+        );
     }
 
     let circuit_data = circuit_builder.build::<C>();
@@ -479,65 +525,131 @@ fn test_shift_right(n: usize, size: usize, input_values: Vec<F>, output_values:
 
 #[test]
 fn test_compression_function() {
-    // Given
-    let public_input_witnesses: Vec<Witness> = (0..16).into_iter().map(|v| Witness(v)).collect();
-    let initial_h: Vec<Witness> = (16..24).into_iter().map(|v| Witness(v)).collect();
-    let output_witnesses: Vec<Witness> = (24..32).into_iter().map(|v| Witness(v)).collect();
+    test_compression_function_of_empty_message(Sha256Implementation::Bitwise);
+}
+
+#[test]
+fn test_compression_function_with_u32_lookups() {
+    test_compression_function_of_empty_message(Sha256Implementation::U32Lookups);
+}
+
+#[test]
+fn test_compression_function_of_abc() {
+    test_compression_function_of_abc_message(Sha256Implementation::Bitwise);
+}
 
-    let only_opcode: Opcode = sha256_compression_opcode(
-        public_input_witnesses.clone(),
-        initial_h.clone(),
-        output_witnesses.clone(),
+#[test]
+fn test_compression_function_of_abc_with_u32_lookups() {
+    test_compression_function_of_abc_message(Sha256Implementation::U32Lookups);
+}
+
+#[test]
+#[should_panic]
+fn test_compression_function_with_u32_lookups_failed() {
+    let mut wrong_output = empty_message_digest();
+    wrong_output[7] += F::ONE;
+    test_compression_function_with(
+        Sha256Implementation::U32Lookups,
+        empty_message_block(),
+        wrong_output,
     );
-    let circuit = circuit_with_single_opcode(only_opcode, vec![]);
+}
+
+#[test]
+fn test_compression_function_with_u32_lookups_uses_fewer_gates() {
+    // Given
+    let circuit = sha256_compression_circuit();
 
     // When
-    let (circuit_data, witness_target_map) =
-        utils::generate_plonky2_circuit_from_acir_circuit(&circuit);
+    let bitwise_gates = sha256_compression_gates(&circuit, Sha256Implementation::Bitwise);
+    let u32_lookups_gates = sha256_compression_gates(&circuit, Sha256Implementation::U32Lookups);
 
     // Then
-    let g_zero = F::default();
-    let g_2_to_the_31 = F::from_canonical_u32(1 << 31);
-    let mut assignments: Vec<(Witness, F)> = vec![(Witness(0), g_2_to_the_31)];
-    let mut a_15_zeroes = public_input_witnesses[1..16]
-        .into_iter()
-        .map(|w| (*w, g_zero))
-        .collect::<Vec<_>>();
-    assignments.append(&mut a_15_zeroes);
-    let h_values = vec![
-        F::from_canonical_u32(0x6a09e667),
-        F::from_canonical_u32(0xbb67ae85),
-        F::from_canonical_u32(0x3c6ef372),
-        F::from_canonical_u32(0xa54ff53a),
-        F::from_canonical_u32(0x510e527f),
-        F::from_canonical_u32(0x9b05688c),
-        F::from_canonical_u32(0x1f83d9ab),
-        F::from_canonical_u32(0x5be0cd19),
-    ];
-    let mut initial_h_values = initial_h
-        .into_iter()
-        .zip(h_values.clone().into_iter())
-        .collect::<Vec<_>>();
-
-
-    let output_values = vec![
-        F::from_canonical_u32(0xe3b0c442),
-        F::from_canonical_u32(0x98fc1c14),
-        F::from_canonical_u32(0x9afbf4c8),
-        F::from_canonical_u32(0x996fb924),
-        F::from_canonical_u32(0x27ae41e4),
-        F::from_canonical_u32(0x649b934c),
-        F::from_canonical_u32(0xa495991b),
-        F::from_canonical_u32(0x7852b855),
-    ];
+    assert!(u32_lookups_gates < bitwise_gates);
+}
 
-    assignments.append(&mut initial_h_values);
+#[test]
+fn test_backend_translates_sha256_compressions_with_its_sha256_implementation() {
+    // Given
+    let circuit = sha256_compression_circuit();
+    let bitwise_backend = Plonky2Backend {
+        sha256_implementation: Sha256Implementation::from_bitwise_flag(true),
+        ..Plonky2Backend::default()
+    };
 
-    let mut output_values = output_witnesses
-        .into_iter()
-        .zip(output_values.into_iter())
-        .collect::<Vec<_>>();
-    assignments.append(&mut output_values);
+    // When
+    let mut bitwise_translator = bitwise_backend.translator();
+    bitwise_translator.translate_circuit(&circuit);
+    let mut default_translator = Plonky2Backend::default().translator();
+    default_translator.translate_circuit(&circuit);
+
+    // Then
+    assert_eq!(
+        sha256_compression_gates(&circuit, Sha256Implementation::Bitwise),
+        bitwise_translator.builder.num_gates()
+    );
+    assert_eq!(
+        sha256_compression_gates(&circuit, Sha256Implementation::U32Lookups),
+        default_translator.builder.num_gates()
+    );
+}
+
+fn test_compression_function_of_empty_message(implementation: Sha256Implementation) {
+    test_compression_function_with(implementation, empty_message_block(), empty_message_digest());
+}
+
+fn test_compression_function_of_abc_message(implementation: Sha256Implementation) {
+    let mut block = vec![F::ZERO; 16];
+    block[0] = F::from_canonical_u32(0x61626380);
+    block[15] = F::from_canonical_u32(0x18);
+    let digest = [
+        0xba7816bf, 0x8f01cfea, 0x414140de, 0x5dae2223, 0xb00361a3, 0x96177a9c, 0xb410ff61,
+        0xf20015ad,
+    ]
+    .map(F::from_canonical_u32)
+    .to_vec();
+    test_compression_function_with(implementation, block, digest);
+}
+
+/// The padded block of the empty message: a single one bit followed by zeroes.
+fn empty_message_block() -> Vec<F> {
+    let mut block = vec![F::ZERO; 16];
+    block[0] = F::from_canonical_u32(1 << 31);
+    block
+}
+
+fn empty_message_digest() -> Vec<F> {
+    [
+        0xe3b0c442, 0x98fc1c14, 0x9afbf4c8, 0x996fb924, 0x27ae41e4, 0x649b934c, 0xa495991b,
+        0x7852b855,
+    ]
+    .map(F::from_canonical_u32)
+    .to_vec()
+}
+
+fn test_compression_function_with(
+    implementation: Sha256Implementation,
+    block_values: Vec<F>,
+    output_values: Vec<F>,
+) {
+    // Given
+    let circuit = sha256_compression_circuit();
+
+    // When
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.sha256_implementation = implementation;
+    translator.translate_circuit(&circuit);
+    let (circuit_data, witness_target_map) = translator.unpack();
+
+    // Then
+    let h_values = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ]
+    .map(F::from_canonical_u32);
+    let values = block_values.into_iter().chain(h_values).chain(output_values);
+    let assignments: Vec<(Witness, F)> =
+        (0..32).map(Witness).zip(values).collect();
 
     let proof = utils::generate_plonky2_proof_using_witness_values(
         assignments,
@@ -548,6 +660,25 @@ fn test_compression_function() {
     assert!(circuit_data.verify(proof).is_ok());
 }
 
+/// A circuit with a single compression whose inputs are the witnesses 0 to 15, the initial hash
+/// values 16 to 23 and the outputs 24 to 31.
+fn sha256_compression_circuit() -> Circuit {
+    let public_input_witnesses: Vec<Witness> = (0..16).into_iter().map(|v| Witness(v)).collect();
+    let initial_h: Vec<Witness> = (16..24).into_iter().map(|v| Witness(v)).collect();
+    let output_witnesses: Vec<Witness> = (24..32).into_iter().map(|v| Witness(v)).collect();
+
+    let only_opcode: Opcode =
+        sha256_compression_opcode(public_input_witnesses, initial_h, output_witnesses);
+    circuit_with_single_opcode(only_opcode, vec![])
+}
+
+fn sha256_compression_gates(circuit: &Circuit, implementation: Sha256Implementation) -> usize {
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.sha256_implementation = implementation;
+    translator.translate_circuit(circuit);
+    translator.builder.num_gates()
+}
+
 fn sha256_compression_opcode(
     public_input_witnesses: Vec<Witness>,
     initial_h: Vec<Witness>,
@@ -587,3 +718,50 @@ fn sha256_compression_opcode(
         outputs: o,
     })
 }
+
+#[test]
+fn test_rotations_and_shifts_do_not_add_gates() {
+    // Given
+    let config = CircuitConfig::standard_recursion_config();
+    let mut circuit_builder = CB::new(config);
+    let bits = (0..32)
+        .map(|_| circuit_builder.add_virtual_bool_target_safe())
+        .collect();
+    let binary_input = BinaryDigitsTarget::from_bits(bits);
+    let gates_before = circuit_builder.num_gates();
+
+    // When
+    let rotated = binary_input.rotate_right(7).rotate_right(11).shift_right(3);
+
+    // Then
+    assert_eq!(32, rotated.number_of_digits());
+    assert_eq!(gates_before, circuit_builder.num_gates());
+}
+
+#[test]
+fn test_views_of_a_packed_number_share_its_bit_decomposition() {
+    // Given
+    let config = CircuitConfig::standard_recursion_config();
+    let mut circuit_builder = CB::new(config);
+    let limb = circuit_builder.add_virtual_target();
+    let packed = BinaryDigitsTarget::from_target(limb, 32);
+
+    // When
+    assert_eq!(limb, packed.to_target(&mut circuit_builder));
+    let gates_before_decomposition = circuit_builder.num_gates();
+    let rotated_bits = packed.rotate_right(5).bits(&mut circuit_builder);
+    let gates_after_decomposition = circuit_builder.num_gates();
+    let bits = packed.bits(&mut circuit_builder);
+
+    // Then
+    assert!(gates_after_decomposition > gates_before_decomposition);
+    assert_eq!(gates_after_decomposition, circuit_builder.num_gates());
+    assert_eq!(bits[27..], rotated_bits[..5]);
+    assert_eq!(bits[..27], rotated_bits[5..]);
+
+    let mut partial_witnesses = PartialWitness::<F>::new();
+    partial_witnesses.set_target(limb, F::from_canonical_u32(0x8000_0001));
+    let circuit_data = circuit_builder.build::<C>();
+    let proof = circuit_data.prove(partial_witnesses).unwrap();
+    assert!(circuit_data.verify(proof).is_ok());
+}
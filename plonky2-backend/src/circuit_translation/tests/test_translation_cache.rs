@@ -0,0 +1,196 @@
+use super::factories::circuit_factory::*;
+use super::factories::circuit_parser;
+use super::*;
+use crate::circuit_translation::acir_expression_gate::AcirExpressionGate;
+use crate::circuit_translation::translation_cache::TranslationCache;
+use parameterized::parameterized;
+
+/// Gate-count regression tests for the reuse of targets across opcodes.
+
+#[test]
+fn test_bitwise_operation_reuses_the_decompositions_of_previous_operations() {
+    // Given
+    let mut circuit = bitwise_and_circuit(Witness(0), Witness(1), Witness(2), 32);
+    circuit
+        .opcodes
+        .push(Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::XOR {
+            lhs: FunctionInput {
+                witness: Witness(0),
+                num_bits: 32,
+            },
+            rhs: FunctionInput {
+                witness: Witness(2),
+                num_bits: 32,
+            },
+            output: Witness(3),
+        }));
+
+    // When
+    let cached_gate_counts = _opcode_gate_counts(&circuit, TranslationCache::new());
+    let uncached_gate_counts = _opcode_gate_counts(&circuit, TranslationCache::disabled());
+
+    // Then
+    assert_eq!(cached_gate_counts[2], uncached_gate_counts[2]);
+    assert!(cached_gate_counts[3] < uncached_gate_counts[3]);
+}
+
+#[test]
+fn test_range_checks_of_a_witness_only_enforce_the_tightest_one() {
+    // Given
+    let witness = Witness(0);
+    let mut circuit =
+        circuit_with_single_opcode(black_box_range_opcode(witness, 16), vec![witness]);
+    circuit.opcodes.push(black_box_range_opcode(witness, 32));
+    circuit.opcodes.push(black_box_range_opcode(witness, 8));
+    let tightest_circuit =
+        circuit_with_single_opcode(black_box_range_opcode(witness, 8), vec![witness]);
+
+    // When
+    let gates = _circuit_num_gates(&circuit, TranslationCache::new());
+    let tightest_gates = _circuit_num_gates(&tightest_circuit, TranslationCache::new());
+    let uncached_gates = _circuit_num_gates(&circuit, TranslationCache::disabled());
+
+    // Then
+    assert_eq!(tightest_gates, gates);
+    assert!(gates < uncached_gates);
+}
+
+#[test]
+fn test_range_check_of_a_witness_decomposed_afterwards_adds_no_gates() {
+    // Given
+    let (lhs, rhs, output) = (Witness(0), Witness(1), Witness(2));
+    let circuit = bitwise_and_circuit(lhs, rhs, output, 32);
+    let mut unchecked_circuit = circuit.clone();
+    unchecked_circuit.opcodes.retain(|opcode| {
+        !matches!(
+            opcode,
+            Opcode::BlackBoxFuncCall(opcodes::BlackBoxFuncCall::RANGE { .. })
+        )
+    });
+
+    // When
+    let gates = _circuit_num_gates(&circuit, TranslationCache::new());
+    let unchecked_gates = _circuit_num_gates(&unchecked_circuit, TranslationCache::new());
+
+    // Then
+    assert_eq!(unchecked_gates, gates);
+}
+
+#[test]
+fn test_expression_reuses_a_product_computed_for_a_memory_operation() {
+    // Given
+    let (x, y) = (Witness(0), Witness(1));
+    let product_expression = Expression {
+        mul_terms: vec![(FieldElement::one(), x, y)],
+        linear_combinations: vec![],
+        q_c: FieldElement::zero(),
+    };
+    let mut circuit = circuit_with_single_opcode(
+        Opcode::MemoryInit {
+            block_id: BlockId(0),
+            init: vec![x, y],
+            block_type: BlockType::Memory,
+        },
+        vec![x, y],
+    );
+    circuit.opcodes.push(Opcode::MemoryOp {
+        block_id: BlockId(0),
+        op: MemOp {
+            operation: Expression::one(),
+            index: Expression::zero(),
+            value: product_expression,
+        },
+        predicate: None,
+    });
+    circuit.opcodes.push(Opcode::AssertZero(Expression {
+        mul_terms: vec![(FieldElement::one(), x, y), (FieldElement::one(), y, x)],
+        linear_combinations: vec![(FieldElement::one(), x), (FieldElement::one(), y)],
+        q_c: -FieldElement::from(8u128),
+    }));
+
+    // When
+    let cached_gate_counts = _opcode_gate_counts(&circuit, TranslationCache::new());
+    let uncached_gate_counts = _opcode_gate_counts(&circuit, TranslationCache::disabled());
+
+    // Then
+    assert_eq!(1, cached_gate_counts[2]);
+    assert_eq!(2, uncached_gate_counts[2]);
+}
+
+#[test]
+fn test_expressions_reuse_the_products_of_previous_expressions() {
+    // Given
+    let (x, y, w, v, a) = (Witness(0), Witness(1), Witness(2), Witness(3), Witness(4));
+    let assert_zero = Opcode::AssertZero(Expression {
+        mul_terms: vec![(FieldElement::one(), x, y), (FieldElement::one(), w, v)],
+        linear_combinations: vec![(-FieldElement::one(), a)],
+        q_c: FieldElement::zero(),
+    });
+    let mut circuit = circuit_with_single_opcode(assert_zero.clone(), vec![x, y, w, v, a]);
+    circuit.opcodes.push(assert_zero.clone());
+    circuit.opcodes.push(assert_zero);
+
+    // When
+    let cached_gate_counts = _opcode_gate_counts(&circuit, TranslationCache::new());
+    let uncached_gate_counts = _opcode_gate_counts(&circuit, TranslationCache::disabled());
+
+    // Then
+    // The first expression evaluates both products. The second one computes them, which takes a
+    // row of ArithmeticGates, and adds them as linear terms like the third one does.
+    assert_eq!(vec![2, 2, 1], cached_gate_counts);
+    assert_eq!(vec![2, 2, 2], uncached_gate_counts);
+}
+
+#[parameterized(program_name = {
+    "basic_memory_write",
+    "assert_x_equals_5",
+    "node_guardians_example",
+    "array_dynamic",
+    "1_mul",
+    "3_add",
+    "5_over",
+    "7_function",
+    "sha256_4",
+    "ecdsa_secp256k1",
+})]
+fn test_translation_cache_never_increases_the_gates_of_noir_programs(program_name: &str) {
+    // Given
+    let (program, _) = circuit_parser::precompiled_program_and_witnesses_with_name(program_name);
+
+    // When
+    let cached_gates = _program_num_gates(&program, TranslationCache::new());
+    let uncached_gates = _program_num_gates(&program, TranslationCache::disabled());
+
+    // Then
+    assert!(
+        cached_gates <= uncached_gates,
+        "{} needs {} gates with the cache and {} without it",
+        program_name,
+        cached_gates,
+        uncached_gates
+    );
+}
+
+/// With a single AcirExpressionGate operation per row, the rows of an AssertZero opcode are the
+/// operations it needs.
+fn _opcode_gate_counts(circuit: &Circuit, translation_cache: TranslationCache) -> Vec<usize> {
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.acir_expression_gate = AcirExpressionGate::new(4, 1, 1);
+    translator.translation_cache = translation_cache;
+    translator.translate_circuit(circuit);
+    translator.opcode_gate_counts
+}
+
+fn _circuit_num_gates(circuit: &Circuit, translation_cache: TranslationCache) -> usize {
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translation_cache = translation_cache;
+    translator.translate_circuit(circuit);
+    translator.builder.num_gates()
+}
+
+fn _program_num_gates(program: &Program, translation_cache: TranslationCache) -> usize {
+    let mut translator = CircuitBuilderFromAcirToPlonky2::new();
+    translator.translation_cache = translation_cache;
+    translator.translate_program(program);
+    translator.builder.num_gates()
+}
@@ -0,0 +1,113 @@
+use super::factories::circuit_factory;
+use super::factories::circuit_factory::public_field_parameter_abi;
+use super::*;
+use crate::actions::verify_action::VerifyAction;
+use crate::actions::write_vk_action::WriteVKAction;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_and_plonky2_serialization::{program_hash, VerifyingKey};
+
+fn _x_equals_4_program_json(abi: serde_json::Value) -> serde_json::Value {
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_equals_4_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    circuit_factory::program_json(&circuit_factory::program_with_main(circuit), abi)
+}
+
+fn _x_times_3_equals_12_program_json() -> serde_json::Value {
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_times_3_equals_12_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    let abi = public_field_parameter_abi("x");
+    circuit_factory::program_json(&circuit_factory::program_with_main(circuit), abi)
+}
+
+fn _verifying_key_for(program_json: &serde_json::Value, zero_knowledge: bool) -> VerifyingKey {
+    WriteVKAction {
+        acir_program_json_path: String::new(),
+        vk_path_output: String::new(),
+        field_conversion_mode: FieldConversionMode::Lenient,
+        zero_knowledge,
+        sha256_implementation: Sha256Implementation::default(),
+    }
+    .generate_verifying_key(program_json)
+}
+
+fn _verify_action() -> VerifyAction {
+    VerifyAction {
+        proof_path: String::new(),
+        vk_path: String::new(),
+        acir_program_json_path: None,
+        field_conversion_mode: FieldConversionMode::Lenient,
+        sha256_implementation: Sha256Implementation::default(),
+    }
+}
+
+#[test]
+fn test_verifying_key_matches_the_program_it_was_generated_from() {
+    for zero_knowledge in [true, false] {
+        // Given
+        let program_json = _x_equals_4_program_json(public_field_parameter_abi("x"));
+        let verifying_key = _verifying_key_for(&program_json, zero_knowledge);
+
+        // When
+        let result =
+            _verify_action().check_verifying_key_matches_program(&verifying_key, &program_json);
+
+        // Then
+        assert_eq!(Ok(()), result);
+    }
+}
+
+#[test]
+fn test_verifying_key_of_another_program_is_detected() {
+    // Given
+    let program_json = _x_equals_4_program_json(public_field_parameter_abi("x"));
+    let verifying_key = _verifying_key_for(&_x_times_3_equals_12_program_json(), true);
+
+    // When
+    let result =
+        _verify_action().check_verifying_key_matches_program(&verifying_key, &program_json);
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("generated from the ACIR program with hash"), "{}", error);
+}
+
+#[test]
+fn test_verifying_key_of_the_same_bytecode_with_another_abi_is_detected() {
+    // Given
+    let program_json = _x_equals_4_program_json(public_field_parameter_abi("x"));
+    let renamed_program_json = _x_equals_4_program_json(public_field_parameter_abi("y"));
+    let verifying_key = _verifying_key_for(&renamed_program_json, true);
+
+    // When
+    let result =
+        _verify_action().check_verifying_key_matches_program(&verifying_key, &program_json);
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("generated from the ACIR program with hash"), "{}", error);
+}
+
+#[test]
+fn test_verifying_key_with_the_program_hash_but_another_circuit_is_detected() {
+    // Given
+    let program_json = _x_equals_4_program_json(public_field_parameter_abi("x"));
+    let verifying_key = VerifyingKey {
+        program_hash: program_hash(&program_json),
+        verifier_data: _verifying_key_for(&_x_times_3_equals_12_program_json(), true)
+            .verifier_data,
+    };
+
+    // When
+    let result =
+        _verify_action().check_verifying_key_matches_program(&verifying_key, &program_json);
+
+    // Then
+    let error = result.err().unwrap();
+    assert!(error.contains("translates to a circuit with digest"), "{}", error);
+}
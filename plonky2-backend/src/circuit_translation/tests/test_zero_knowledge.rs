@@ -0,0 +1,126 @@
+use super::factories::{circuit_factory, circuit_parser, utils};
+use super::*;
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_and_plonky2_serialization::{
+    deserialize_verifying_key, serialize_verifying_key, VerifyingKey,
+};
+
+fn _generate_zero_knowledge_plonky2_circuit_from_acir_circuit(
+    circuit: &Circuit,
+) -> (CircuitData<F, C, 2>, HashMap<Witness, Target>) {
+    let mut translator =
+        CircuitBuilderFromAcirToPlonky2::new_with_options(FieldConversionMode::Lenient, true);
+    translator.translate_circuit(circuit);
+    translator.unpack()
+}
+
+#[test]
+fn test_zero_knowledge_proof_of_a_simple_circuit_verifies() {
+    // Given
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_times_3_equals_12_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+
+    // When
+    let (circuit_data, witness_target_map) =
+        _generate_zero_knowledge_plonky2_circuit_from_acir_circuit(&circuit);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        vec![(public_input_witness, F::from_canonical_u64(4))],
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    // Then
+    assert!(circuit_data.common.config.zero_knowledge);
+    assert_eq!(vec![F::from_canonical_u64(4)], proof.public_inputs);
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_zero_knowledge_proof_of_a_precompiled_program_verifies() {
+    // Given
+    let (circuit, mut witnesses) =
+        circuit_parser::precompiled_circuit_and_withesses_with_name("1_mul");
+    let witness_mapping = witnesses.pop().unwrap().witness;
+    let witness_assignment: Vec<(Witness, F)> = witness_mapping
+        .into_iter()
+        .map(|(witness, value)| (witness, F::from_canonical_u64(value.try_to_u64().unwrap())))
+        .collect();
+
+    // When
+    let (circuit_data, witness_target_map) =
+        _generate_zero_knowledge_plonky2_circuit_from_acir_circuit(&circuit);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        witness_assignment.clone(),
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    // Then
+    utils::check_public_inputs_match_witness_values(&circuit, &proof, &witness_assignment);
+    assert!(circuit_data.verify(proof).is_ok());
+}
+
+#[test]
+fn test_two_zero_knowledge_proofs_of_the_same_witnesses_are_different() {
+    // Given
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_times_3_equals_12_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    let (circuit_data, witness_target_map) =
+        _generate_zero_knowledge_plonky2_circuit_from_acir_circuit(&circuit);
+    let witness_assignment = vec![(public_input_witness, F::from_canonical_u64(4))];
+
+    // When
+    let first_proof = utils::generate_plonky2_proof_using_witness_values(
+        witness_assignment.clone(),
+        &witness_target_map,
+        &circuit_data,
+    );
+    let second_proof = utils::generate_plonky2_proof_using_witness_values(
+        witness_assignment,
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    // Then
+    // The witness is blinded with random values, so the openings change between proofs
+    assert_ne!(first_proof.to_bytes(), second_proof.to_bytes());
+    assert!(circuit_data.verify(first_proof).is_ok());
+    assert!(circuit_data.verify(second_proof).is_ok());
+}
+
+#[test]
+fn test_zero_knowledge_setting_is_kept_in_the_serialized_verifying_key() {
+    // Given
+    let public_input_witness = Witness(0);
+    let circuit = circuit_factory::circuit_with_single_opcode(
+        circuit_factory::x_equals_4_opcode(public_input_witness),
+        vec![public_input_witness],
+    );
+    let (circuit_data, witness_target_map) =
+        _generate_zero_knowledge_plonky2_circuit_from_acir_circuit(&circuit);
+    let proof = utils::generate_plonky2_proof_using_witness_values(
+        vec![(public_input_witness, F::from_canonical_u64(4))],
+        &witness_target_map,
+        &circuit_data,
+    );
+
+    // When
+    let verifying_key = VerifyingKey {
+        program_hash: vec![],
+        verifier_data: circuit_data.verifier_data(),
+    };
+    let serialized_verifying_key = serialize_verifying_key(&verifying_key);
+    let verifier_data = deserialize_verifying_key(&serialized_verifying_key)
+        .unwrap()
+        .verifier_data;
+
+    // Then
+    assert!(verifier_data.common.config.zero_knowledge);
+    assert!(verifier_data.verify(proof).is_ok());
+}
@@ -0,0 +1,301 @@
+use super::*;
+use byte_lookups::ByteLookupTable;
+use std::collections::BTreeMap;
+use witness_generators::{TargetExpression, ToLeRadixGenerator};
+
+/// With at least this many range checks (or once the Range table is in the circuit for other
+/// reasons) they're enforced with lookups into the Range table. Each one costs a lookup per byte,
+/// a lot less than a bit decomposition, but the table itself takes a few rows, so a handful of
+/// checks is still cheaper with bit decompositions.
+pub const RANGE_CHECK_LOOKUP_THRESHOLD: usize = 8;
+
+/// Targets computed while translating that later opcodes can reuse instead of adding the same
+/// gates again: products of two targets and bit decompositions. The cache lives as long as the
+/// translation of a circuit, so the reuse spans all of its opcodes. Constants need no cache,
+/// since the CircuitBuilder already gives the same target to every use of a value.
+///
+/// The products of the AssertZero opcodes are evaluated inside the AcirExpressionGate, so they
+/// don't have a target of their own. The first time another expression multiplies the same
+/// targets, the product is computed (see expression_product), and from then on the expressions
+/// add it as a linear term.
+///
+/// A bit decomposition of a target into n digits also proves that its value fits in n bits, so
+/// range checks are skipped for targets that already have a small enough decomposition.
+///
+/// Range checks are only collected while translating, and they're enforced all together once
+/// every opcode has been translated (see add_range_check_constraints). By then it's known how
+/// many there are, which decides whether it pays off to use the Range table, and a target
+/// checked several times only needs the tightest check. The gates of each check are attributed
+/// to the first opcode that requested it (see start_opcode).
+///
+/// The byte lookup tables are added to the circuit the first time they are used, and every lookup
+/// into them is shared afterwards, even with the cache disabled.
+///
+/// The cache can be disabled, which is only useful to measure how many gates it saves.
+pub struct TranslationCache {
+    enabled: bool,
+    products: HashMap<(Target, Target), Target>,
+    /// The products evaluated inside the AcirExpressionGate by some expression.
+    expression_products: HashSet<(Target, Target)>,
+    bit_decompositions: HashMap<(Target, usize), BinaryDigitsTarget>,
+    /// The fewest digits of the decompositions of each target.
+    range_digits: HashMap<Target, usize>,
+    /// The bytes of a target, least significant first, keyed by its number of bits.
+    byte_decompositions: HashMap<(Target, usize), Vec<Target>>,
+    lookup_tables: HashMap<ByteLookupTable, usize>,
+    /// The range checks not enforced yet, as (target, digits, opcode), in the order they were
+    /// requested.
+    pending_range_checks: Vec<(Target, usize, usize)>,
+    /// The index of the opcode being translated, within the gate counts of the translator.
+    current_opcode: usize,
+}
+
+impl Default for TranslationCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranslationCache {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            products: HashMap::new(),
+            expression_products: HashSet::new(),
+            bit_decompositions: HashMap::new(),
+            range_digits: HashMap::new(),
+            byte_decompositions: HashMap::new(),
+            lookup_tables: HashMap::new(),
+            pending_range_checks: Vec::new(),
+            current_opcode: 0,
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::new()
+        }
+    }
+
+    /// The range checks requested from now on belong to the opcode with the given index.
+    pub fn start_opcode(&mut self, opcode_index: usize) {
+        self.current_opcode = opcode_index;
+    }
+
+    /// The product of two targets that was computed already, in any order.
+    pub fn cached_product(&self, target_1: Target, target_2: Target) -> Option<Target> {
+        self.products
+            .get(&(target_1, target_2))
+            .or_else(|| self.products.get(&(target_2, target_1)))
+            .copied()
+    }
+
+    /// The product of two targets for a term of an AssertZero expression: the cached one if it was
+    /// computed already, or a new one if another expression multiplied the same targets before.
+    /// Otherwise the expression evaluates the product itself, which is recorded so the next
+    /// expression with it computes it instead.
+    pub fn expression_product(
+        &mut self,
+        builder: &mut CB,
+        target_1: Target,
+        target_2: Target,
+    ) -> Option<Target> {
+        if let Some(product) = self.cached_product(target_1, target_2) {
+            return Some(product);
+        }
+        if !self.enabled {
+            return None;
+        }
+        if self.expression_products.contains(&(target_1, target_2))
+            || self.expression_products.contains(&(target_2, target_1))
+        {
+            return Some(self.mul(builder, target_1, target_2));
+        }
+        self.expression_products.insert((target_1, target_2));
+        None
+    }
+
+    pub fn mul(&mut self, builder: &mut CB, target_1: Target, target_2: Target) -> Target {
+        if let Some(product) = self.cached_product(target_1, target_2) {
+            return product;
+        }
+        let product = builder.mul(target_1, target_2);
+        if self.enabled {
+            self.products.insert((target_1, target_2), product);
+        }
+        product
+    }
+
+    /// The bits of the target, most significant first.
+    pub fn split_be(
+        &mut self,
+        builder: &mut CB,
+        target: Target,
+        digits: usize,
+    ) -> BinaryDigitsTarget {
+        if let Some(binary_target) = self.bit_decompositions.get(&(target, digits)) {
+            return binary_target.clone();
+        }
+        let bits = builder.split_le(target, digits).into_iter().rev().collect();
+        let binary_target = BinaryDigitsTarget::from_target_and_bits(target, bits);
+        self.record_bit_decomposition(target, binary_target.clone());
+        binary_target
+    }
+
+    /// Registers the bits of a target computed elsewhere, like the result of a bitwise operation
+    /// recomposed from its bits.
+    pub fn record_bit_decomposition(&mut self, target: Target, binary_target: BinaryDigitsTarget) {
+        if self.enabled {
+            let digits = binary_target.number_of_digits();
+            let range_digits = self.range_digits.entry(target).or_insert(digits);
+            *range_digits = (*range_digits).min(digits);
+            self.bit_decompositions.insert((target, digits), binary_target);
+        }
+    }
+
+    /// The check is collected to be enforced by add_range_check_constraints, unless a
+    /// decomposition of the target already proves it.
+    pub fn range_check(&mut self, target: Target, digits: usize) {
+        if !self._is_in_range(target, digits) {
+            self.pending_range_checks
+                .push((target, digits, self.current_opcode));
+        }
+    }
+
+    fn _is_in_range(&self, target: Target, digits: usize) -> bool {
+        self.range_digits
+            .get(&target)
+            .is_some_and(|range_digits| *range_digits <= digits)
+    }
+
+    /// Enforces the range checks collected so far, grouped by their number of bits. Each target
+    /// is only checked against its tightest range, and not at all if it was decomposed after the
+    /// check was requested. Depending on how many checks there are, they're either decomposed
+    /// into bytes looked up in the Range table or into bits. The gates added for each check are
+    /// counted in the opcode_gate_counts entry of the first opcode that requested it. The lookups
+    /// of the bytes only fill rows when the circuit is built, so they aren't counted.
+    pub fn add_range_check_constraints(
+        &mut self,
+        builder: &mut CB,
+        opcode_gate_counts: &mut [usize],
+    ) {
+        let pending_range_checks = std::mem::take(&mut self.pending_range_checks);
+        let mut targets_by_digits: BTreeMap<usize, Vec<(Target, usize)>> = BTreeMap::new();
+        if self.enabled {
+            let mut tightest_digits: HashMap<Target, usize> = HashMap::new();
+            for (target, digits, _) in &pending_range_checks {
+                let tightest = tightest_digits.entry(*target).or_insert(*digits);
+                *tightest = (*tightest).min(*digits);
+            }
+            for (target, _, opcode) in &pending_range_checks {
+                if let Some(digits) = tightest_digits.remove(target) {
+                    if !self._is_in_range(*target, digits) {
+                        targets_by_digits
+                            .entry(digits)
+                            .or_default()
+                            .push((*target, *opcode));
+                    }
+                }
+            }
+        } else {
+            for (target, digits, opcode) in pending_range_checks {
+                targets_by_digits
+                    .entry(digits)
+                    .or_default()
+                    .push((target, opcode));
+            }
+        }
+
+        let num_range_checks: usize = targets_by_digits.values().map(Vec::len).sum();
+        let use_lookups = num_range_checks >= RANGE_CHECK_LOOKUP_THRESHOLD
+            || self.lookup_tables.contains_key(&ByteLookupTable::Range);
+        for (digits, targets) in targets_by_digits {
+            for (target, opcode) in targets {
+                let gates_before_check = builder.num_gates();
+                if use_lookups {
+                    self.split_bytes(builder, target, digits);
+                } else {
+                    self.split_be(builder, target, digits);
+                }
+                opcode_gate_counts[opcode] += builder.num_gates() - gates_before_check;
+            }
+        }
+    }
+
+    pub fn lookup_table(&mut self, builder: &mut CB, table: ByteLookupTable) -> usize {
+        *self
+            .lookup_tables
+            .entry(table)
+            .or_insert_with(|| builder.add_lookup_table_from_pairs(table.entries()))
+    }
+
+    /// The bytes of a target of the given number of bits, least significant first. Every byte is
+    /// looked up in the Range table, and when the number of bits isn't a multiple of 8 the last
+    /// one is also checked to fit in the bits left. A target of at most 8 bits is its own byte.
+    pub fn split_bytes(
+        &mut self,
+        builder: &mut CB,
+        target: Target,
+        num_bits: usize,
+    ) -> Vec<Target> {
+        if let Some(bytes) = self.byte_decompositions.get(&(target, num_bits)) {
+            return bytes.clone();
+        }
+        let num_bytes = num_bits.div_ceil(8);
+        let bytes: Vec<Target> = if num_bytes == 1 {
+            vec![target]
+        } else {
+            let bytes = builder.add_virtual_targets(num_bytes);
+            builder.add_simple_generator(ToLeRadixGenerator {
+                a: TargetExpression::from_target(target),
+                radix: 256,
+                digits: bytes.clone(),
+                // The bytes are recomposed in the Goldilocks field
+                field_conversion_mode: FieldConversionMode::Lenient,
+            });
+            bytes
+        };
+        let range_table = self.lookup_table(builder, ByteLookupTable::Range);
+        for byte in &bytes {
+            builder.add_lookup_from_index(*byte, range_table);
+        }
+        if num_bits % 8 != 0 {
+            // A byte fits in the bits left if shifting it to the top of a byte is still a byte
+            let shift = F::from_canonical_u64(1 << (8 - num_bits % 8));
+            let shifted_byte = builder.mul_const(shift, *bytes.last().unwrap());
+            builder.add_lookup_from_index(shifted_byte, range_table);
+        }
+        if num_bytes != 1 {
+            let recomposed_target = Self::recompose_bytes(builder, &bytes);
+            builder.connect(recomposed_target, target);
+        }
+
+        self.record_byte_decomposition(target, num_bits, bytes.clone());
+        bytes
+    }
+
+    /// Registers the bytes of a target computed elsewhere, like the result of a bitwise operation
+    /// looked up byte by byte.
+    pub fn record_byte_decomposition(
+        &mut self,
+        target: Target,
+        num_bits: usize,
+        bytes: Vec<Target>,
+    ) {
+        if self.enabled {
+            let range_digits = self.range_digits.entry(target).or_insert(num_bits);
+            *range_digits = (*range_digits).min(num_bits);
+            self.byte_decompositions.insert((target, num_bits), bytes);
+        }
+    }
+
+    pub fn recompose_bytes(builder: &mut CB, bytes: &[Target]) -> Target {
+        let base = F::from_canonical_u64(256);
+        let mut recomposed_target = builder.zero();
+        for byte in bytes.iter().rev() {
+            recomposed_target = builder.mul_const_add(base, recomposed_target, *byte);
+        }
+        recomposed_target
+    }
+}
@@ -0,0 +1,397 @@
+use acir::brillig::{ForeignCallResult, Opcode as GenericBrilligOpcode};
+use acir::circuit::brillig::BrilligOutputs;
+use acvm_blackbox_solver::StubbedBlackBoxSolver;
+use brillig_vm::{VMStatus, VM};
+use num_bigint::BigUint;
+use plonky2::iop::generator::{GeneratedValues, SimpleGenerator};
+use plonky2::iop::witness::{PartitionWitness, Witness as _, WitnessWrite};
+use plonky2::plonk::circuit_data::CommonCircuitData;
+use plonky2::util::serialization::{Buffer, IoError, IoResult, Read, Write};
+
+use super::*;
+
+/// The translation only adds constraints, but Plonky2 also needs the value of every target to
+/// generate a proof. Most targets are computed by the generators of the gates that constrain them
+/// (an AND output, a random access, a SHA256 compression), so this module has the generators for
+/// the witnesses that ACIR solves outside the constraints: the unknown of an AssertZero opcode,
+/// the digits of a ToLeRadix directive and the outputs of Brillig calls. With them, the input
+/// witnesses of the program are enough for proving.
+
+pub type BrilligOpcode = GenericBrilligOpcode<FieldElement>;
+
+/// An ACIR expression over Plonky2 targets, which generators evaluate without adding gates.
+#[derive(Clone, Debug)]
+pub struct TargetExpression {
+    constant: F,
+    linear_terms: Vec<(F, Target)>,
+    mul_terms: Vec<(F, Target, Target)>,
+}
+
+impl TargetExpression {
+    /// Every witness of the expression must already have a target.
+    pub fn new_for(
+        expression: &Expression,
+        witness_target_map: &HashMap<Witness, Target>,
+        field_conversion_mode: FieldConversionMode,
+    ) -> Self {
+        let to_goldilocks = |fe: &FieldElement| {
+            field_conversion_mode.field_element_to_goldilocks_field(fe, || {
+                format!("A coefficient of the expression {}", expression)
+            })
+        };
+        Self {
+            constant: to_goldilocks(&expression.q_c),
+            linear_terms: expression
+                .linear_combinations
+                .iter()
+                .map(|(coefficient, witness)| {
+                    (to_goldilocks(coefficient), witness_target_map[witness])
+                })
+                .collect(),
+            mul_terms: expression
+                .mul_terms
+                .iter()
+                .map(|(coefficient, witness_1, witness_2)| {
+                    (
+                        to_goldilocks(coefficient),
+                        witness_target_map[witness_1],
+                        witness_target_map[witness_2],
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_target(target: Target) -> Self {
+        Self {
+            constant: F::ZERO,
+            linear_terms: vec![(F::ONE, target)],
+            mul_terms: vec![],
+        }
+    }
+
+    pub fn targets(&self) -> Vec<Target> {
+        let mut targets: Vec<Target> = self.linear_terms.iter().map(|(_, t)| *t).collect();
+        for (_, target_1, target_2) in &self.mul_terms {
+            targets.push(*target_1);
+            targets.push(*target_2);
+        }
+        let mut seen_targets = HashSet::new();
+        targets.retain(|target| seen_targets.insert(*target));
+        targets
+    }
+
+    pub fn evaluate(&self, witness: &PartitionWitness<F>) -> F {
+        let mut value = self.constant;
+        for (coefficient, target) in &self.linear_terms {
+            value += *coefficient * witness.get_target(*target);
+        }
+        for (coefficient, target_1, target_2) in &self.mul_terms {
+            value += *coefficient * witness.get_target(*target_1) * witness.get_target(*target_2);
+        }
+        value
+    }
+
+    /// Splits the value of the expression into the part that depends linearly on the unknown
+    /// target and the rest: the expression equals coefficient * unknown + rest.
+    fn evaluate_linear_in(&self, unknown: Target, witness: &PartitionWitness<F>) -> (F, F) {
+        let mut coefficient = F::ZERO;
+        let mut rest = self.constant;
+        for (term_coefficient, target) in &self.linear_terms {
+            if *target == unknown {
+                coefficient += *term_coefficient;
+            } else {
+                rest += *term_coefficient * witness.get_target(*target);
+            }
+        }
+        for (term_coefficient, target_1, target_2) in &self.mul_terms {
+            if *target_1 == unknown {
+                coefficient += *term_coefficient * witness.get_target(*target_2);
+            } else if *target_2 == unknown {
+                coefficient += *term_coefficient * witness.get_target(*target_1);
+            } else {
+                rest += *term_coefficient
+                    * witness.get_target(*target_1)
+                    * witness.get_target(*target_2);
+            }
+        }
+        (coefficient, rest)
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>) -> IoResult<()> {
+        dst.write_field(self.constant)?;
+        dst.write_usize(self.linear_terms.len())?;
+        for (coefficient, target) in &self.linear_terms {
+            dst.write_field(*coefficient)?;
+            dst.write_target(*target)?;
+        }
+        dst.write_usize(self.mul_terms.len())?;
+        for (coefficient, target_1, target_2) in &self.mul_terms {
+            dst.write_field(*coefficient)?;
+            dst.write_target(*target_1)?;
+            dst.write_target(*target_2)?;
+        }
+        Ok(())
+    }
+
+    fn deserialize(src: &mut Buffer) -> IoResult<Self> {
+        let constant = src.read_field()?;
+        let mut linear_terms = Vec::new();
+        for _ in 0..src.read_usize()? {
+            linear_terms.push((src.read_field()?, src.read_target()?));
+        }
+        let mut mul_terms = Vec::new();
+        for _ in 0..src.read_usize()? {
+            mul_terms.push((src.read_field()?, src.read_target()?, src.read_target()?));
+        }
+        Ok(Self {
+            constant,
+            linear_terms,
+            mul_terms,
+        })
+    }
+}
+
+/// Solves the only unknown witness of an AssertZero opcode, which must not be multiplied by
+/// itself (the same restriction the ACVM has).
+#[derive(Debug)]
+pub struct AssertZeroSolverGenerator {
+    pub expression: TargetExpression,
+    pub unknown: Target,
+}
+
+impl SimpleGenerator<F, D> for AssertZeroSolverGenerator {
+    fn id(&self) -> String {
+        "AssertZeroSolverGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.expression
+            .targets()
+            .into_iter()
+            .filter(|target| *target != self.unknown)
+            .collect()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let (coefficient, rest) = self.expression.evaluate_linear_in(self.unknown, witness);
+        if coefficient == F::ZERO {
+            panic!("AssertZero opcode can't be solved: the coefficient of its unknown is zero");
+        }
+        out_buffer.set_target(self.unknown, -rest / coefficient);
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        self.expression.serialize(dst)?;
+        dst.write_target(self.unknown)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        Ok(Self {
+            expression: TargetExpression::deserialize(src)?,
+            unknown: src.read_target()?,
+        })
+    }
+}
+
+/// Little endian decomposition of the value of an expression, as the ToLeRadix directive does.
+/// The digits are constrained by the AssertZero opcodes that come after the directive. The value
+/// is decomposed as the FieldElement the field conversion mode reads it as, like the ACVM would.
+#[derive(Debug)]
+pub struct ToLeRadixGenerator {
+    pub a: TargetExpression,
+    pub radix: u32,
+    pub digits: Vec<Target>,
+    pub field_conversion_mode: FieldConversionMode,
+}
+
+impl SimpleGenerator<F, D> for ToLeRadixGenerator {
+    fn id(&self) -> String {
+        "ToLeRadixGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        self.a.targets()
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        let value = self
+            .field_conversion_mode
+            .goldilocks_field_to_field_element(self.a.evaluate(witness));
+        let mut value = BigUint::from_bytes_be(&value.to_be_bytes());
+        let radix = BigUint::from(self.radix);
+        for digit in &self.digits {
+            let digit_value = (&value % &radix)
+                .to_u64_digits()
+                .first()
+                .copied()
+                .unwrap_or(0);
+            out_buffer.set_target(*digit, F::from_canonical_u64(digit_value));
+            value /= &radix;
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        self.a.serialize(dst)?;
+        dst.write_u32(self.radix)?;
+        dst.write_target_vec(&self.digits)?;
+        dst.write_bool(self.field_conversion_mode == FieldConversionMode::Strict)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        Ok(Self {
+            a: TargetExpression::deserialize(src)?,
+            radix: src.read_u32()?,
+            digits: src.read_target_vec()?,
+            field_conversion_mode: FieldConversionMode::from_strict_flag(src.read_bool()?),
+        })
+    }
+}
+
+/// Runs a Brillig function in the Brillig VM, with the values of the inputs as calldata, and sets
+/// its return data as the values of the outputs. Like in the ACVM, when the predicate is zero the
+/// function isn't run and the outputs are zero. Foreign calls other than print can't be answered
+/// here, so a function making them leaves its outputs to the provided witnesses.
+#[derive(Debug)]
+pub struct BrilligCallGenerator {
+    pub bytecode: Vec<BrilligOpcode>,
+    pub calldata: Vec<TargetExpression>,
+    pub predicate: Option<TargetExpression>,
+    pub outputs: Vec<Target>,
+    pub field_conversion_mode: FieldConversionMode,
+}
+
+impl BrilligCallGenerator {
+    fn _run_brillig_function(&self, calldata: Vec<FieldElement>) -> Option<Vec<FieldElement>> {
+        let black_box_solver = StubbedBlackBoxSolver;
+        let mut vm = VM::new(calldata, &self.bytecode, vec![], &black_box_solver);
+        loop {
+            match vm.process_opcodes() {
+                VMStatus::Finished {
+                    return_data_offset,
+                    return_data_size,
+                } => {
+                    let return_data_end = return_data_offset + return_data_size;
+                    return Some(
+                        vm.get_memory()[return_data_offset..return_data_end]
+                            .iter()
+                            .map(|memory_value| memory_value.to_field())
+                            .collect(),
+                    );
+                }
+                // Printing has no effect on the witnesses
+                VMStatus::ForeignCallWait { function, .. } if function == "print" => {
+                    vm.resolve_foreign_call(ForeignCallResult { values: vec![] });
+                }
+                VMStatus::ForeignCallWait { .. } => return None,
+                VMStatus::Failure { reason, .. } => {
+                    panic!("Brillig call failed while generating the witnesses: {:?}", reason)
+                }
+                VMStatus::InProgress => {}
+            }
+        }
+    }
+}
+
+impl SimpleGenerator<F, D> for BrilligCallGenerator {
+    fn id(&self) -> String {
+        "BrilligCallGenerator".to_string()
+    }
+
+    fn dependencies(&self) -> Vec<Target> {
+        let mut dependencies: Vec<Target> = self
+            .calldata
+            .iter()
+            .flat_map(|expression| expression.targets())
+            .collect();
+        if let Some(predicate) = &self.predicate {
+            dependencies.extend(predicate.targets());
+        }
+        dependencies
+    }
+
+    fn run_once(&self, witness: &PartitionWitness<F>, out_buffer: &mut GeneratedValues<F>) {
+        if let Some(predicate) = &self.predicate {
+            if predicate.evaluate(witness) == F::ZERO {
+                for output in &self.outputs {
+                    out_buffer.set_target(*output, F::ZERO);
+                }
+                return;
+            }
+        }
+        let calldata: Vec<FieldElement> = self
+            .calldata
+            .iter()
+            .map(|expression| {
+                self.field_conversion_mode
+                    .goldilocks_field_to_field_element(expression.evaluate(witness))
+            })
+            .collect();
+        let Some(return_data) = self._run_brillig_function(calldata) else {
+            return;
+        };
+        assert_eq!(
+            self.outputs.len(),
+            return_data.len(),
+            "The Brillig call returned {} values but it has {} outputs",
+            return_data.len(),
+            self.outputs.len()
+        );
+        for (position, (output, value)) in self.outputs.iter().zip(return_data).enumerate() {
+            let value = self
+                .field_conversion_mode
+                .field_element_to_goldilocks_field(&value, || {
+                    format!("Output {} of a Brillig call", position)
+                });
+            out_buffer.set_target(*output, value);
+        }
+    }
+
+    fn serialize(&self, dst: &mut Vec<u8>, _common_data: &CommonCircuitData<F, D>) -> IoResult<()> {
+        let bytecode = serde_json::to_vec(&self.bytecode).map_err(|_| IoError)?;
+        dst.write_usize(bytecode.len())?;
+        dst.write_all(&bytecode)?;
+        dst.write_usize(self.calldata.len())?;
+        for expression in &self.calldata {
+            expression.serialize(dst)?;
+        }
+        dst.write_bool(self.predicate.is_some())?;
+        if let Some(predicate) = &self.predicate {
+            predicate.serialize(dst)?;
+        }
+        dst.write_target_vec(&self.outputs)?;
+        dst.write_bool(self.field_conversion_mode == FieldConversionMode::Strict)
+    }
+
+    fn deserialize(src: &mut Buffer, _common_data: &CommonCircuitData<F, D>) -> IoResult<Self> {
+        let mut bytecode = vec![0; src.read_usize()?];
+        src.read_exact(&mut bytecode)?;
+        let bytecode = serde_json::from_slice(&bytecode).map_err(|_| IoError)?;
+        let mut calldata = Vec::new();
+        for _ in 0..src.read_usize()? {
+            calldata.push(TargetExpression::deserialize(src)?);
+        }
+        let predicate = match src.read_bool()? {
+            true => Some(TargetExpression::deserialize(src)?),
+            false => None,
+        };
+        Ok(Self {
+            bytecode,
+            calldata,
+            predicate,
+            outputs: src.read_target_vec()?,
+            field_conversion_mode: FieldConversionMode::from_strict_flag(src.read_bool()?),
+        })
+    }
+}
+
+/// The witnesses written by a Brillig call, in the order of its return data.
+pub fn brillig_output_witnesses(outputs: &Vec<BrilligOutputs>) -> Vec<Witness> {
+    outputs
+        .iter()
+        .flat_map(|output| match output {
+            BrilligOutputs::Simple(witness) => vec![*witness],
+            BrilligOutputs::Array(witnesses) => witnesses.clone(),
+        })
+        .collect()
+}
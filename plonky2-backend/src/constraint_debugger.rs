@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use acir::circuit::opcodes::{BlackBoxFuncCall, BlockId, FunctionInput};
+use acir::native_types::WitnessMap;
+use acvm_blackbox_solver::{ecdsa_secp256k1_verify, sha256compression};
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::iop::generator::generate_partial_witness;
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, Witness as _, WitnessWrite};
+use plonky2::plonk::circuit_data::CircuitData;
+
+use crate::circuit_translation::{
+    expression_witnesses, opcode_kind_name, opcode_witnesses, Circuit, Expression, FieldElement,
+    Opcode, Witness,
+};
+use crate::field_conversion::FieldConversionMode;
+use crate::noir_debug_info::ProgramDebugInfo;
+use crate::{C, D, F};
+
+/// When the witnesses don't satisfy the circuit, Plonky2 fails while proving with an error about a
+/// copy constraint or a generator, which says nothing about the ACIR program. This module finds
+/// the problem in terms of the program instead, in two steps:
+/// 1. Every ACIR opcode is evaluated against the witness values, in the Goldilocks field (as the
+///    circuit does), reporting the first opcode that doesn't hold along with the values involved.
+/// 2. The circuit generates its targets from the values of the parameters, and those are compared
+///    against the witness values, reporting the first witness the circuit computes differently.
+///
+/// Opcodes with witnesses that don't have a value can't be evaluated, so they are skipped.
+
+/// An ACIR opcode that doesn't hold for the witness values.
+#[derive(Debug, PartialEq)]
+pub struct OpcodeViolation {
+    pub opcode_index: usize,
+    pub opcode_kind: String,
+    pub reason: String,
+    pub values: Vec<(Witness, F)>,
+}
+
+/// A witness whose target gets a different value than the one in the witness stack when the
+/// circuit generates it.
+#[derive(Debug, PartialEq)]
+pub struct TargetMismatch {
+    pub witness: Witness,
+    pub expected_value: F,
+    pub generated_value: F,
+}
+
+pub struct ConstraintDebugger<'a> {
+    circuit: &'a Circuit,
+    field_conversion_mode: FieldConversionMode,
+    witness_values: HashMap<Witness, F>,
+    memory_blocks: HashMap<BlockId, Vec<Option<F>>>,
+}
+
+impl<'a> ConstraintDebugger<'a> {
+    pub fn new_for(
+        circuit: &'a Circuit,
+        witness_map: &WitnessMap<FieldElement>,
+        field_conversion_mode: FieldConversionMode,
+    ) -> Self {
+        let witness_values = witness_map
+            .clone()
+            .into_iter()
+            .map(|(witness, value)| {
+                let value = field_conversion_mode.field_element_to_goldilocks_field(&value, || {
+                    format!("Witness {}", witness.0)
+                });
+                (witness, value)
+            })
+            .collect();
+        Self {
+            circuit,
+            field_conversion_mode,
+            witness_values,
+            memory_blocks: HashMap::new(),
+        }
+    }
+
+    pub fn first_violated_opcode(&mut self) -> Option<OpcodeViolation> {
+        for (opcode_index, opcode) in self.circuit.opcodes.iter().enumerate() {
+            if let Some((reason, witnesses)) = self._check_opcode(opcode) {
+                return Some(OpcodeViolation {
+                    opcode_index,
+                    opcode_kind: opcode_kind_name(opcode),
+                    reason,
+                    values: witnesses
+                        .into_iter()
+                        .filter_map(|witness| Some((witness, self._value(witness)?)))
+                        .collect(),
+                });
+            }
+        }
+        None
+    }
+
+    /// Only the parameters are given to the circuit, so every other target is generated by it.
+    /// The witnesses are checked in increasing order, which is roughly the order in which the
+    /// ACVM solves them.
+    pub fn first_mismatched_target(
+        &self,
+        circuit_data: &CircuitData<F, C, D>,
+        witness_target_map: &HashMap<Witness, Target>,
+    ) -> Result<Option<TargetMismatch>, String> {
+        let mut inputs = PartialWitness::<F>::new();
+        for witness in self.circuit.circuit_arguments() {
+            if let (Some(target), Some(value)) =
+                (witness_target_map.get(&witness), self._value(witness))
+            {
+                inputs.set_target(*target, value);
+            }
+        }
+        let generated_witness = catch_unwind(AssertUnwindSafe(|| {
+            generate_partial_witness(inputs, &circuit_data.prover_only, &circuit_data.common)
+        }))
+        .map_err(|error| _panic_message(&*error))?;
+
+        let mut witnesses: Vec<&Witness> = witness_target_map.keys().collect();
+        witnesses.sort();
+        for witness in witnesses {
+            let target = witness_target_map[witness];
+            let (Some(expected_value), Some(generated_value)) =
+                (self._value(*witness), generated_witness.try_get_target(target))
+            else {
+                continue;
+            };
+            if expected_value != generated_value {
+                return Ok(Some(TargetMismatch {
+                    witness: *witness,
+                    expected_value,
+                    generated_value,
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The first opcode that uses the witness, which is where its value is usually computed.
+    pub fn first_opcode_using(&self, witness: Witness) -> Option<usize> {
+        self.circuit
+            .opcodes
+            .iter()
+            .position(|opcode| opcode_witnesses(opcode).contains(&witness))
+    }
+
+    /// Returns why the opcode doesn't hold and the witnesses involved. The opcodes that add no
+    /// constraints (Brillig calls and directives) always hold.
+    fn _check_opcode(&mut self, opcode: &Opcode) -> Option<(String, Vec<Witness>)> {
+        match opcode {
+            Opcode::AssertZero(expression) => {
+                let value = self._evaluate(expression)?;
+                (value != F::ZERO).then(|| {
+                    (
+                        format!("the expression {} evaluates to {}", expression, value),
+                        expression_witnesses(expression),
+                    )
+                })
+            }
+            Opcode::MemoryInit { block_id, init, .. } => {
+                let values = init.iter().map(|witness| self._value(*witness)).collect();
+                self.memory_blocks.insert(*block_id, values);
+                None
+            }
+            Opcode::MemoryOp { block_id, op, .. } => {
+                let witnesses = [
+                    expression_witnesses(&op.index),
+                    expression_witnesses(&op.value),
+                ]
+                .concat();
+                let is_memory_write = op.operation.to_const()?.is_one();
+                let index = self._evaluate(&op.index)?.to_canonical_u64() as usize;
+                let value = self._evaluate(&op.value);
+                let memory_block = self.memory_blocks.get_mut(block_id)?;
+                if index >= memory_block.len() {
+                    return Some((
+                        format!(
+                            "position {} is out of bounds of block {} of length {}",
+                            index,
+                            block_id.0,
+                            memory_block.len()
+                        ),
+                        witnesses,
+                    ));
+                }
+                if is_memory_write {
+                    memory_block[index] = value;
+                    return None;
+                }
+                let (Some(value), Some(value_in_memory)) = (value, memory_block[index]) else {
+                    return None;
+                };
+                (value != value_in_memory).then(|| {
+                    (
+                        format!(
+                            "the value read is {} but position {} of block {} holds {}",
+                            value, index, block_id.0, value_in_memory
+                        ),
+                        witnesses,
+                    )
+                })
+            }
+            Opcode::BlackBoxFuncCall(func_call) => self._check_black_box_func_call(func_call),
+            _ => None,
+        }
+    }
+
+    fn _check_black_box_func_call(
+        &self,
+        func_call: &BlackBoxFuncCall<FieldElement>,
+    ) -> Option<(String, Vec<Witness>)> {
+        let mut witnesses: Vec<Witness> =
+            func_call.get_inputs_vec().iter().map(|input| input.witness).collect();
+        witnesses.extend(func_call.get_outputs_vec());
+        for input in func_call.get_inputs_vec() {
+            let value = self._value(input.witness)?;
+            let num_bits = input.num_bits.min(_operand_bits(func_call));
+            if !_fits_in_bits(value, num_bits) {
+                return Some((
+                    format!(
+                        "witness {} has value {} which doesn't fit in {} bits",
+                        input.witness.0, value, num_bits
+                    ),
+                    witnesses,
+                ));
+            }
+        }
+        let expected_outputs: Vec<F> = match func_call {
+            BlackBoxFuncCall::RANGE { .. } => return None,
+            BlackBoxFuncCall::AND { lhs, rhs, .. } => {
+                let lhs = self._value(lhs.witness)?.to_canonical_u64();
+                let rhs = self._value(rhs.witness)?.to_canonical_u64();
+                vec![F::from_canonical_u64(lhs & rhs)]
+            }
+            BlackBoxFuncCall::XOR { lhs, rhs, .. } => {
+                let lhs = self._value(lhs.witness)?.to_canonical_u64();
+                let rhs = self._value(rhs.witness)?.to_canonical_u64();
+                vec![F::from_canonical_u64(lhs ^ rhs)]
+            }
+            BlackBoxFuncCall::Sha256Compression {
+                inputs,
+                hash_values,
+                ..
+            } => {
+                let mut state: [u32; 8] = self._u32_values(hash_values.as_ref())?.try_into().ok()?;
+                let message: [u32; 16] = self._u32_values(inputs.as_ref())?.try_into().ok()?;
+                sha256compression(&mut state, &message);
+                state.iter().map(|word| F::from_canonical_u32(*word)).collect()
+            }
+            BlackBoxFuncCall::EcdsaSecp256k1 {
+                public_key_x,
+                public_key_y,
+                signature,
+                hashed_message,
+                ..
+            } => {
+                let hashed_message = self._byte_values(hashed_message.as_ref())?;
+                let public_key_x = self._byte_values(public_key_x.as_ref())?;
+                let public_key_y = self._byte_values(public_key_y.as_ref())?;
+                let signature = self._byte_values(signature.as_ref())?;
+                let verifies = ecdsa_secp256k1_verify(
+                    &hashed_message,
+                    &public_key_x.try_into().ok()?,
+                    &public_key_y.try_into().ok()?,
+                    &signature.try_into().ok()?,
+                )
+                .unwrap_or(false);
+                vec![F::from_bool(verifies)]
+            }
+            _ => return None,
+        };
+        for (output, expected_value) in func_call.get_outputs_vec().iter().zip(expected_outputs) {
+            let value = self._value(*output)?;
+            if value != expected_value {
+                return Some((
+                    format!(
+                        "output witness {} is {} but the function returns {}",
+                        output.0, value, expected_value
+                    ),
+                    witnesses,
+                ));
+            }
+        }
+        None
+    }
+
+    fn _value(&self, witness: Witness) -> Option<F> {
+        self.witness_values.get(&witness).copied()
+    }
+
+    fn _evaluate(&self, expression: &Expression) -> Option<F> {
+        let to_goldilocks = |fe: &FieldElement| {
+            self.field_conversion_mode
+                .field_element_to_goldilocks_field(fe, || {
+                    format!("A coefficient of the expression {}", expression)
+                })
+        };
+        let mut value = to_goldilocks(&expression.q_c);
+        for (coefficient, witness) in &expression.linear_combinations {
+            value += to_goldilocks(coefficient) * self._value(*witness)?;
+        }
+        for (coefficient, witness_1, witness_2) in &expression.mul_terms {
+            value +=
+                to_goldilocks(coefficient) * self._value(*witness_1)? * self._value(*witness_2)?;
+        }
+        Some(value)
+    }
+
+    /// The inputs were checked to fit in the operands of the function, so they're converted
+    /// without truncating.
+    fn _u32_values(&self, inputs: &[FunctionInput]) -> Option<Vec<u32>> {
+        inputs
+            .iter()
+            .map(|input| u32::try_from(self._value(input.witness)?.to_canonical_u64()).ok())
+            .collect()
+    }
+
+    fn _byte_values(&self, inputs: &[FunctionInput]) -> Option<Vec<u8>> {
+        inputs
+            .iter()
+            .map(|input| u8::try_from(self._value(input.witness)?.to_canonical_u64()).ok())
+            .collect()
+    }
+}
+
+impl OpcodeViolation {
+    pub fn report(&self, debug_info: &ProgramDebugInfo, function_index: usize) -> String {
+        let mut report = format!(
+            "Opcode {} ({}) is not satisfied: {}",
+            self.opcode_index, self.opcode_kind, self.reason
+        );
+        for (witness, value) in &self.values {
+            report.push_str(&format!("\n    w{} = {}", witness.0, value));
+        }
+        report.push_str(&_source_locations(debug_info, function_index, self.opcode_index));
+        report
+    }
+}
+
+impl TargetMismatch {
+    pub fn report(
+        &self,
+        debug_info: &ProgramDebugInfo,
+        function_index: usize,
+        opcode_index: Option<usize>,
+    ) -> String {
+        let mut report = format!(
+            "Witness {} is {} in the witness stack, but the Plonky2 circuit generates {}",
+            self.witness.0, self.expected_value, self.generated_value
+        );
+        if let Some(opcode_index) = opcode_index {
+            report.push_str(&format!("\n    first used by opcode {}", opcode_index));
+            report.push_str(&_source_locations(debug_info, function_index, opcode_index));
+        }
+        report
+    }
+}
+
+fn _source_locations(
+    debug_info: &ProgramDebugInfo,
+    function_index: usize,
+    opcode_index: usize,
+) -> String {
+    debug_info
+        .opcode_call_stack(function_index, opcode_index)
+        .iter()
+        .map(|location| format!("\n    at {}: {}", location, location.snippet))
+        .collect()
+}
+
+/// The size of the operands of the function, which bounds its inputs even if they're declared
+/// with more bits: SHA256 works on 32 bit words and ECDSA on bytes.
+fn _operand_bits(func_call: &BlackBoxFuncCall<FieldElement>) -> u32 {
+    match func_call {
+        BlackBoxFuncCall::Sha256Compression { .. } => 32,
+        BlackBoxFuncCall::EcdsaSecp256k1 { .. } => 8,
+        _ => u32::MAX,
+    }
+}
+
+fn _fits_in_bits(value: F, num_bits: u32) -> bool {
+    num_bits >= 64 || value.to_canonical_u64() < (1 << num_bits)
+}
+
+fn _panic_message(error: &(dyn std::any::Any + Send)) -> String {
+    match error.downcast_ref::<String>() {
+        Some(message) => message.clone(),
+        None => error
+            .downcast_ref::<&str>()
+            .map_or(String::from("unknown error"), |message| message.to_string()),
+    }
+}
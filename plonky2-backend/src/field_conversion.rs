@@ -0,0 +1,116 @@
+use num_bigint::BigUint;
+use plonky2::field::types::{Field, Field64, PrimeField64};
+
+use crate::circuit_translation::{AcirField, FieldElement};
+use crate::F;
+
+/// The ACIR program and its witnesses talk about Noir's FieldElement, while the Plonky2 circuit
+/// works over the Goldilocks field. Unless the custom nargo fork is used, a FieldElement is a
+/// BN254 element, so a constant like -1 is p_bn254 - 1, which reduced modulo the Goldilocks prime
+/// is a completely different number.
+///
+/// The lenient mode (the default) just reduces the values modulo the Goldilocks prime. The strict
+/// mode only accepts values that are canonical Goldilocks elements or that are close enough to
+/// the FieldElement modulus to be read as negative numbers (like p_bn254 - 1 = -1), and fails
+/// otherwise instead of silently changing the meaning of the program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldConversionMode {
+    #[default]
+    Lenient,
+    Strict,
+}
+
+impl FieldConversionMode {
+    pub fn from_strict_flag(strict: bool) -> Self {
+        if strict {
+            FieldConversionMode::Strict
+        } else {
+            FieldConversionMode::Lenient
+        }
+    }
+
+    /// The description is only used for the error message, and it should name the witness or
+    /// opcode the value belongs to.
+    pub fn field_element_to_goldilocks_field(
+        &self,
+        fe: &FieldElement,
+        description: impl Fn() -> String,
+    ) -> F {
+        self.try_field_element_to_goldilocks_field(fe, description)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like field_element_to_goldilocks_field, but returning the error of the strict mode instead
+    /// of panicking, for values that come from the user (like the witnesses to prove).
+    pub fn try_field_element_to_goldilocks_field(
+        &self,
+        fe: &FieldElement,
+        description: impl Fn() -> String,
+    ) -> Result<F, String> {
+        match self {
+            FieldConversionMode::Lenient => Ok(field_element_to_goldilocks_field(fe)),
+            FieldConversionMode::Strict => strict_field_element_to_goldilocks_field(fe)
+                .ok_or_else(|| {
+                    format!(
+                        "{} has value 0x{} which is not a Goldilocks field element (nor the \
+                         negation of one)",
+                        description(),
+                        fe.to_hex()
+                    )
+                }),
+        }
+    }
+
+    /// The way back, for the values computed by the circuit that ACIR code reads (the calldata
+    /// of Brillig calls, the value decomposed by ToLeRadix and the public inputs of a proof). The
+    /// lenient mode keeps the canonical value. The strict mode undoes the reading of negative
+    /// values: a Goldilocks element above (p - 1) / 2 is taken as the negation of one below it,
+    /// so -1 in the circuit is p_bn254 - 1 for ACIR, like it was before the conversion. When the
+    /// FieldElement is a Goldilocks element too (the custom nargo fork) both modes are the same.
+    pub fn goldilocks_field_to_field_element(&self, value: F) -> FieldElement {
+        match self {
+            FieldConversionMode::Lenient => goldilocks_field_to_field_element(value),
+            FieldConversionMode::Strict => strict_goldilocks_field_to_field_element(value),
+        }
+    }
+}
+
+pub fn goldilocks_field_to_field_element(value: F) -> FieldElement {
+    FieldElement::from(value.to_canonical_u64() as u128)
+}
+
+pub fn strict_goldilocks_field_to_field_element(value: F) -> FieldElement {
+    if FieldElement::modulus() == F::order() || value.to_canonical_u64() <= F::ORDER / 2 {
+        return goldilocks_field_to_field_element(value);
+    }
+    -goldilocks_field_to_field_element(-value)
+}
+
+/// This is just a transformation from the Noir's FieldElement to the Plonky2 GoldilocksField
+pub fn field_element_to_goldilocks_field(fe: &FieldElement) -> F {
+    F::from_noncanonical_biguint(_field_element_to_biguint(fe))
+}
+
+pub fn strict_field_element_to_goldilocks_field(fe: &FieldElement) -> Option<F> {
+    strict_biguint_to_goldilocks_field(&_field_element_to_biguint(fe), &FieldElement::modulus())
+}
+
+/// Values smaller than the Goldilocks prime are kept as they are, while values v such that
+/// source_modulus - v is smaller than the Goldilocks prime are interpreted as -(source_modulus - v).
+pub fn strict_biguint_to_goldilocks_field(value: &BigUint, source_modulus: &BigUint) -> Option<F> {
+    let goldilocks_modulus = F::order();
+    if value < &goldilocks_modulus {
+        return Some(F::from_noncanonical_biguint(value.clone()));
+    }
+    if value < source_modulus {
+        let negated_value = source_modulus - value;
+        if negated_value < goldilocks_modulus {
+            return Some(-F::from_noncanonical_biguint(negated_value));
+        }
+    }
+    None
+}
+
+fn _field_element_to_biguint(fe: &FieldElement) -> BigUint {
+    BigUint::from_bytes_be(&fe.to_be_bytes() as &[u8])
+}
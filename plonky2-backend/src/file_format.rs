@@ -0,0 +1,216 @@
+use plonky2::plonk::circuit_data::{CommonCircuitData, VerifierOnlyCircuitData};
+use plonky2::plonk::config::GenericHashOut;
+
+use crate::{C, D, F, HASH_CONFIG};
+
+/// Proofs and verifying keys are written with a header in front of the Plonky2 bytes, so a file
+/// coming from another backend version, hash configuration or circuit is rejected with a precise
+/// message instead of failing somewhere inside Plonky2's deserialization.
+///
+/// Layout (integers are little endian, strings and hashes are prefixed by their u8 length):
+/// magic number | format version (u16) | kind (u8) | backend version | hash configuration |
+/// circuit digest | program hash | amount of public inputs (u32) | Plonky2 bytes
+pub const MAGIC_NUMBER: [u8; 4] = *b"NP2B";
+pub const FORMAT_VERSION: u16 = 2;
+pub const BACKEND_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileKind {
+    Proof,
+    VerifyingKey,
+}
+
+impl FileKind {
+    fn to_byte(&self) -> u8 {
+        match self {
+            FileKind::Proof => 0,
+            FileKind::VerifyingKey => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, String> {
+        match byte {
+            0 => Ok(FileKind::Proof),
+            1 => Ok(FileKind::VerifyingKey),
+            _ => Err(format!("Unknown file kind {}", byte)),
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            FileKind::Proof => "a proof",
+            FileKind::VerifyingKey => "a verifying key",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileHeader {
+    pub kind: FileKind,
+    pub format_version: u16,
+    pub backend_version: String,
+    pub hash_config: String,
+    pub circuit_digest: Vec<u8>,
+    /// Hash of the ACIR program and its ABI, see program_hash in noir_and_plonky2_serialization
+    pub program_hash: Vec<u8>,
+    pub num_public_inputs: usize,
+}
+
+impl FileHeader {
+    /// The header this backend writes for a proof or a verifying key of the given circuit, which
+    /// was translated from the program with the given hash.
+    pub fn new_for(
+        kind: FileKind,
+        program_hash: &[u8],
+        verifier_only: &VerifierOnlyCircuitData<C, D>,
+        common: &CommonCircuitData<F, D>,
+    ) -> Self {
+        Self {
+            kind,
+            format_version: FORMAT_VERSION,
+            backend_version: BACKEND_VERSION.to_string(),
+            hash_config: HASH_CONFIG.to_string(),
+            circuit_digest: verifier_only.circuit_digest.to_bytes(),
+            program_hash: program_hash.to_vec(),
+            num_public_inputs: common.num_public_inputs,
+        }
+    }
+
+    /// Returns the header followed by the payload.
+    pub fn prepend_to(&self, payload: Vec<u8>) -> Vec<u8> {
+        let mut bytes = MAGIC_NUMBER.to_vec();
+        bytes.extend(self.format_version.to_le_bytes());
+        bytes.push(self.kind.to_byte());
+        _write_with_length(&mut bytes, self.backend_version.as_bytes());
+        _write_with_length(&mut bytes, self.hash_config.as_bytes());
+        _write_with_length(&mut bytes, &self.circuit_digest);
+        _write_with_length(&mut bytes, &self.program_hash);
+        bytes.extend((self.num_public_inputs as u32).to_le_bytes());
+        bytes.extend(payload);
+        bytes
+    }
+
+    /// Reads the header at the beginning of the bytes and returns it along with the payload. The
+    /// format version is checked right away, since the rest of the layout depends on it.
+    pub fn split_from(bytes: &[u8]) -> Result<(Self, &[u8]), String> {
+        let mut reader = HeaderReader { bytes, position: 0 };
+        if reader.read(MAGIC_NUMBER.len())? != MAGIC_NUMBER {
+            return Err(
+                "The file was not written by this backend (wrong magic number). Files written \
+                 before the versioned format must be generated again"
+                    .to_string(),
+            );
+        }
+        let format_version = u16::from_le_bytes(reader.read(2)?.try_into().unwrap());
+        if format_version != FORMAT_VERSION {
+            return Err(format!(
+                "The file has format version {} but this backend reads format version {}",
+                format_version, FORMAT_VERSION
+            ));
+        }
+        let kind = FileKind::from_byte(reader.read(1)?[0])?;
+        let backend_version = reader.read_string()?;
+        let hash_config = reader.read_string()?;
+        let circuit_digest = reader.read_with_length()?.to_vec();
+        let program_hash = reader.read_with_length()?.to_vec();
+        let num_public_inputs = u32::from_le_bytes(reader.read(4)?.try_into().unwrap()) as usize;
+        let header = Self {
+            kind,
+            format_version,
+            backend_version,
+            hash_config,
+            circuit_digest,
+            program_hash,
+            num_public_inputs,
+        };
+        Ok((header, &bytes[reader.position..]))
+    }
+
+    pub fn check_kind(&self, expected_kind: FileKind) -> Result<(), String> {
+        if self.kind != expected_kind {
+            return Err(format!(
+                "Expected {} but the file contains {}",
+                expected_kind.description(),
+                self.kind.description()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Compares the header of a file with the one this backend expects, reporting the first
+    /// field that doesn't match.
+    pub fn check_matches(&self, expected: &FileHeader) -> Result<(), String> {
+        self.check_kind(expected.kind)?;
+        if self.backend_version != expected.backend_version {
+            return Err(format!(
+                "The file was written by backend version {} but this is version {}",
+                self.backend_version, expected.backend_version
+            ));
+        }
+        if self.hash_config != expected.hash_config {
+            return Err(format!(
+                "The file uses the {} hash configuration but this backend uses {}",
+                self.hash_config, expected.hash_config
+            ));
+        }
+        if self.circuit_digest != expected.circuit_digest {
+            return Err(format!(
+                "The file belongs to the circuit with digest 0x{} but the expected circuit has \
+                 digest 0x{}",
+                to_hex(&self.circuit_digest),
+                to_hex(&expected.circuit_digest)
+            ));
+        }
+        if self.program_hash != expected.program_hash {
+            return Err(format!(
+                "The file was generated from the ACIR program with hash 0x{} but the expected \
+                 program has hash 0x{}",
+                to_hex(&self.program_hash),
+                to_hex(&expected.program_hash)
+            ));
+        }
+        if self.num_public_inputs != expected.num_public_inputs {
+            return Err(format!(
+                "The file has {} public inputs but the circuit has {}",
+                self.num_public_inputs, expected.num_public_inputs
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct HeaderReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn read(&mut self, amount_of_bytes: usize) -> Result<&'a [u8], String> {
+        let end = self.position + amount_of_bytes;
+        if end > self.bytes.len() {
+            return Err("The file is truncated: its header is incomplete".to_string());
+        }
+        let read_bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(read_bytes)
+    }
+
+    fn read_with_length(&mut self) -> Result<&'a [u8], String> {
+        let length = self.read(1)?[0] as usize;
+        self.read(length)
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        String::from_utf8(self.read_with_length()?.to_vec())
+            .map_err(|_| "The file header has an invalid string".to_string())
+    }
+}
+
+fn _write_with_length(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.push(data.len() as u8);
+    bytes.extend_from_slice(data);
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
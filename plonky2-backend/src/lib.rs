@@ -8,15 +8,25 @@ use plonky2::plonk::circuit_data::VerifierCircuitData;
 use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
 use plonky2::plonk::proof::CompressedProofWithPublicInputs;
 
+pub use backend::{CompiledCircuit, Plonky2Backend, Proof, PublicInputs};
+pub use noir_and_plonky2_serialization::VerifyingKey;
+
 const D: usize = 2;
 
 pub type C = KeccakGoldilocksConfig;
+/// Fixed name of C written in the header of proofs and verifying keys; update it along with C.
+pub const HASH_CONFIG: &str = "KeccakGoldilocksConfig";
 pub(crate) type F = <C as GenericConfig<D>>::F;
 
 pub mod actions;
 pub mod argument_parsing;
+pub mod backend;
 pub mod circuit_translation;
+pub mod constraint_debugger;
+pub mod field_conversion;
+pub mod file_format;
 pub mod noir_and_plonky2_serialization;
+pub mod noir_debug_info;
 pub mod plonky2_ecdsa;
 pub mod binary_digits_target;
 
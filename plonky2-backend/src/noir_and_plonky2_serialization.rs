@@ -6,55 +6,184 @@ use serde_json;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::vec::Vec;
+use plonky2::plonk::circuit_data::CircuitData;
+use sha2::{Digest, Sha256};
 use crate::actions::write_vk_action::BackendGateSerializer;
+use crate::file_format::{FileHeader, FileKind};
+use crate::noir_debug_info::ProgramDebugInfo;
 
 /// Since Nargo is decoupled from the backend (they don't even have to be written in the same
 /// languaje) the communication between them is done through files, so there's a lot of serializing
 /// and deserializing in very specific formats. The fact that this backend is written in Rust is
 /// accidental, but also very usefull.
 
-pub fn deserialize_verifying_key_within_file_path(
-    verifying_key_path: &String,
-) -> VerifierCircuitData<F, C, D> {
+/// The path standing for the standard input or output.
+pub const STANDARD_STREAM_PATH: &str = "-";
+
+/// A verifying key along with the hash of the ACIR program (and ABI) it was generated from.
+pub struct VerifyingKey {
+    pub program_hash: Vec<u8>,
+    pub verifier_data: VerifierCircuitData<F, C, D>,
+}
+
+impl VerifyingKey {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serialize_verifying_key(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        deserialize_verifying_key(bytes)
+    }
+}
+
+/// Proofs and verifying keys are written with the header of the file_format module. The readers
+/// validate it before handing the rest of the bytes to Plonky2.
+pub fn deserialize_verifying_key_within_file_path(verifying_key_path: &String) -> VerifyingKey {
     let buffer = read_file_to_bytes(verifying_key_path);
-    let gate_serializer = BackendGateSerializer;
-    VerifierCircuitData::from_bytes(buffer, &gate_serializer).unwrap()
+    deserialize_verifying_key(&buffer)
+        .unwrap_or_else(|error| panic!("Invalid verifying key {}: {}", verifying_key_path, error))
 }
 
 pub fn deserialize_proof_within_file_path(
     proof_path: &String,
-    verifier_data: &VerifierCircuitData<F, C, D>,
+    verifying_key: &VerifyingKey,
 ) -> CompressedProofWithPublicInputs<F, C, D> {
     let buffer = read_file_to_bytes(proof_path);
-    let common_circuit_data = &verifier_data.common;
-    let proof: CompressedProofWithPublicInputs<F, C, D> =
-        CompressedProofWithPublicInputs::from_bytes(buffer, common_circuit_data).unwrap();
-    proof
+    deserialize_proof(&buffer, verifying_key)
+        .unwrap_or_else(|error| panic!("Invalid proof {}: {}", proof_path, error))
+}
+
+pub fn serialize_verifying_key(verifying_key: &VerifyingKey) -> Vec<u8> {
+    let gate_serializer = BackendGateSerializer;
+    let verifier_data = &verifying_key.verifier_data;
+    let header = FileHeader::new_for(
+        FileKind::VerifyingKey,
+        &verifying_key.program_hash,
+        &verifier_data.verifier_only,
+        &verifier_data.common,
+    );
+    header.prepend_to(verifier_data.to_bytes(&gate_serializer).unwrap())
 }
 
+pub fn deserialize_verifying_key(bytes: &[u8]) -> Result<VerifyingKey, String> {
+    let (header, payload) = FileHeader::split_from(bytes)?;
+    header.check_kind(FileKind::VerifyingKey)?;
+    let gate_serializer = BackendGateSerializer;
+    let verifier_data = VerifierCircuitData::from_bytes(payload.to_vec(), &gate_serializer)
+        .map_err(|_| "The verifying key data is corrupted".to_string())?;
+    // The digest and the amount of public inputs of the header must describe the key itself
+    let expected_header = FileHeader::new_for(
+        FileKind::VerifyingKey,
+        &header.program_hash,
+        &verifier_data.verifier_only,
+        &verifier_data.common,
+    );
+    header.check_matches(&expected_header)?;
+    Ok(VerifyingKey {
+        program_hash: header.program_hash,
+        verifier_data,
+    })
+}
+
+pub fn serialize_compressed_proof(
+    compressed_proof: &CompressedProofWithPublicInputs<F, C, D>,
+    program_hash: &[u8],
+    circuit_data: &CircuitData<F, C, D>,
+) -> Vec<u8> {
+    let header = FileHeader::new_for(
+        FileKind::Proof,
+        program_hash,
+        &circuit_data.verifier_only,
+        &circuit_data.common,
+    );
+    header.prepend_to(compressed_proof.to_bytes())
+}
+
+pub fn deserialize_proof(
+    bytes: &[u8],
+    verifying_key: &VerifyingKey,
+) -> Result<CompressedProofWithPublicInputs<F, C, D>, String> {
+    let (header, payload) = FileHeader::split_from(bytes)?;
+    let verifier_data = &verifying_key.verifier_data;
+    let expected_header = FileHeader::new_for(
+        FileKind::Proof,
+        &verifying_key.program_hash,
+        &verifier_data.verifier_only,
+        &verifier_data.common,
+    );
+    header.check_matches(&expected_header)?;
+    CompressedProofWithPublicInputs::from_bytes(payload.to_vec(), &verifier_data.common)
+        .map_err(|_| "The proof data is corrupted".to_string())
+}
+
+/// Sha256 of the ACIR bytecode followed by the ABI of the program, as found in the json written
+/// by Nargo. The ABI is included since it gives meaning to the public inputs.
+pub fn program_hash(program_json: &serde_json::Value) -> Vec<u8> {
+    let bytecode = _decode_bytecode(program_json);
+    program_hash_of(&bytecode, &program_json["abi"])
+}
+
+/// The same hash for a program that isn't read from a json: the bytecode is the serialized
+/// program, and the ABI is null when it's unknown.
+pub fn program_hash_of(bytecode: &[u8], abi: &serde_json::Value) -> Vec<u8> {
+    let abi = serde_json::to_vec(abi).unwrap();
+    let mut hasher = Sha256::new();
+    hasher.update((bytecode.len() as u64).to_le_bytes());
+    hasher.update(bytecode);
+    hasher.update(abi);
+    hasher.finalize().to_vec()
+}
+
+/// The path "-" stands for the standard input, as in Barretenberg's command line.
 pub fn read_file_to_bytes(file_path: &String) -> Vec<u8> {
-    let mut file = File::open(file_path).expect("There was a problem reading the file");
     let mut buffer: Vec<u8> = Vec::new();
+    if file_path == STANDARD_STREAM_PATH {
+        std::io::stdin()
+            .read_to_end(&mut buffer)
+            .expect("There was a problem reading the standard input");
+        return buffer;
+    }
+    let mut file = File::open(file_path).expect("There was a problem reading the file");
     let _ = file.read_to_end(&mut buffer);
     return buffer;
 }
 
+/// Besides the json written by Nargo, the program can be given as its serialized bytecode alone
+/// (as Nargo writes it for a backend), in which case its ABI is unknown and taken as null. Any
+/// file that isn't valid json is taken as bytecode.
+pub fn read_program_json_within_file_path(acir_program_path: &String) -> serde_json::Value {
+    let file_content = read_file_to_bytes(acir_program_path);
+    serde_json::from_slice(&file_content).unwrap_or_else(|_| {
+        serde_json::json!({
+            "bytecode": base64::prelude::BASE64_STANDARD.encode(&file_content),
+            "abi": serde_json::Value::Null,
+        })
+    })
+}
+
 pub fn deserialize_program_within_file_path(acir_program_path: &String) -> Program {
-    let mut file = File::open(acir_program_path).expect("There was a problem opening the file");
-    let mut json_string = String::new();
-    file.read_to_string(&mut json_string)
-        .expect("There was a problem reading the file content");
-    let json_str: &str = &json_string;
-    let json: serde_json::Value =
-        serde_json::from_str(json_str).expect("There was a problem parsing the json program");
-    let Some(bytecode_str) = json["bytecode"].as_str() else {
+    let json = read_program_json_within_file_path(acir_program_path);
+    deserialize_program(&json)
+}
+
+pub fn deserialize_program(program_json: &serde_json::Value) -> Program {
+    let bytecode: &[u8] = &_decode_bytecode(program_json);
+    let program = Program::deserialize_program(bytecode);
+    program.unwrap()
+}
+
+fn _decode_bytecode(program_json: &serde_json::Value) -> Vec<u8> {
+    let Some(bytecode_str) = program_json["bytecode"].as_str() else {
         panic!("Expected a different circuit format")
     };
-    let bytecode: &[u8] = &base64::prelude::BASE64_STANDARD
+    base64::prelude::BASE64_STANDARD
         .decode(bytecode_str)
-        .expect("There was a problem decoding the program from base 64");
-    let program = Program::deserialize_program(bytecode);
-    program.unwrap()
+        .expect("There was a problem decoding the program from base 64")
+}
+
+pub fn deserialize_debug_info_within_file_path(acir_program_path: &String) -> ProgramDebugInfo {
+    let json = read_program_json_within_file_path(acir_program_path);
+    ProgramDebugInfo::from_program_json(&json)
 }
 
 pub fn deserialize_witnesses_within_file_path(mut witnesses_path: String) -> WitnessStack {
@@ -63,7 +192,20 @@ pub fn deserialize_witnesses_within_file_path(mut witnesses_path: String) -> Wit
     witness_stack.unwrap()
 }
 
+/// The path "-" stands for the standard output. Otherwise the missing directories of the path
+/// are created, since the default paths of the commands point into them.
 pub fn write_bytes_to_file_path(bytes: Vec<u8>, path: &String) {
+    if path == STANDARD_STREAM_PATH {
+        let mut stdout = std::io::stdout();
+        stdout
+            .write_all(&bytes)
+            .and_then(|_| stdout.flush())
+            .expect("Failed to write to the standard output");
+        return;
+    }
+    if let Some(parent_directory) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(parent_directory).expect("Failed to create the directory");
+    }
     let mut file = File::create(path).expect("Failed to create file");
     file.write_all(&bytes).expect("Failed to write file");
 }
@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::PathBuf;
+
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use serde::Deserialize;
+
+/// Nargo stores, next to the bytecode, the debug symbols of the program: for every opcode of every
+/// ACIR function, the call stack of Noir source locations that generated it. This module reads
+/// them so that whatever we learn about an opcode (its cost, whether it fails, etc.) can be
+/// reported in terms of the Noir program the user wrote.
+///
+/// The debug symbols are a json, compressed with deflate and encoded in base 64, while the
+/// sources are stored as plain text in the file_map.
+pub struct ProgramDebugInfo {
+    functions: Vec<FunctionDebugInfo>,
+    files: BTreeMap<usize, DebugFile>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceLocation {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+#[derive(Deserialize)]
+struct DebugSymbols {
+    debug_infos: Vec<FunctionDebugInfo>,
+}
+
+/// The keys of the locations are the opcode locations as nargo displays them: "i" for the i-th
+/// ACIR opcode and "i.j" for the j-th opcode of the Brillig call in the i-th ACIR opcode.
+#[derive(Deserialize)]
+struct FunctionDebugInfo {
+    locations: BTreeMap<String, Vec<Location>>,
+}
+
+#[derive(Deserialize)]
+struct Location {
+    span: Span,
+    file: usize,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Deserialize)]
+struct DebugFile {
+    source: String,
+    path: PathBuf,
+}
+
+impl ProgramDebugInfo {
+    pub fn from_program_json(program_json: &serde_json::Value) -> Self {
+        let functions = match program_json["debug_symbols"].as_str() {
+            Some(debug_symbols) => Self::_decode_debug_symbols(debug_symbols).debug_infos,
+            None => Vec::new(),
+        };
+        let files = serde_json::from_value(program_json["file_map"].clone()).unwrap_or_default();
+        Self { functions, files }
+    }
+
+    /// Noir source locations that generated the opcode, from the outermost call (usually main) to
+    /// the innermost expression. It's empty when there's no debug information for the opcode.
+    pub fn opcode_call_stack(
+        &self,
+        function_index: usize,
+        opcode_index: usize,
+    ) -> Vec<SourceLocation> {
+        let Some(function_debug_info) = self.functions.get(function_index) else {
+            return Vec::new();
+        };
+        match function_debug_info.locations.get(&opcode_index.to_string()) {
+            Some(locations) => locations
+                .iter()
+                .filter_map(|location| self._source_location(location))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn _source_location(&self, location: &Location) -> Option<SourceLocation> {
+        let file = self.files.get(&location.file)?;
+        let start = location.span.start.min(file.source.len());
+        let end = location.span.end.clamp(start, file.source.len());
+        let preceding_source = &file.source[..start];
+        let line = preceding_source.matches('\n').count() + 1;
+        let column = start - preceding_source.rfind('\n').map_or(0, |i| i + 1) + 1;
+        let snippet = file.source[start..end]
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        Some(SourceLocation {
+            path: file.path.display().to_string(),
+            line,
+            column,
+            snippet,
+        })
+    }
+
+    fn _decode_debug_symbols(debug_symbols: &str) -> DebugSymbols {
+        let compressed_debug_symbols = base64::prelude::BASE64_STANDARD
+            .decode(debug_symbols)
+            .expect("There was a problem decoding the debug symbols from base 64");
+        let mut json_string = String::new();
+        DeflateDecoder::new(compressed_debug_symbols.as_slice())
+            .read_to_string(&mut json_string)
+            .expect("There was a problem decompressing the debug symbols");
+        serde_json::from_str(&json_string).expect("There was a problem parsing the debug symbols")
+    }
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.path, self.line, self.column)
+    }
+}
@@ -259,24 +259,16 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderU32<F, D>
     fn split_into_byte_targets(&mut self, a: U32Target) -> [BinaryDigitsTarget; 4] {
         let bool_targets = self.split_into_bool_targets(a);
         [
-            BinaryDigitsTarget {
-                bits: bool_targets[0..8].to_vec(),
-            },
-            BinaryDigitsTarget {
-                bits: bool_targets[8..16].to_vec(),
-            },
-            BinaryDigitsTarget {
-                bits: bool_targets[16..24].to_vec(),
-            },
-            BinaryDigitsTarget {
-                bits: bool_targets[24..32].to_vec(),
-            },
+            BinaryDigitsTarget::from_bits(bool_targets[0..8].to_vec()),
+            BinaryDigitsTarget::from_bits(bool_targets[8..16].to_vec()),
+            BinaryDigitsTarget::from_bits(bool_targets[16..24].to_vec()),
+            BinaryDigitsTarget::from_bits(bool_targets[24..32].to_vec()),
         ]
     }
 
     fn constant_byte(&mut self, byte: u8) -> BinaryDigitsTarget {
-        BinaryDigitsTarget {
-            bits: (0u8..8u8)
+        BinaryDigitsTarget::from_bits(
+            (0u8..8u8)
                 .rev()
                 .map(|i| {
                     let value = ((1u8 << i) & byte) >> i;
@@ -286,11 +278,13 @@ impl<F: RichField + Extendable<D>, const D: usize> CircuitBuilderU32<F, D>
                     )
                 })
                 .collect(),
-        }
+        )
     }
 
     fn connect_byte(&mut self, x: BinaryDigitsTarget, y: BinaryDigitsTarget) {
-        for (a, b) in x.bits.iter().zip(y.bits.iter()) {
+        let x_bits = x.bits(self);
+        let y_bits = y.bits(self);
+        for (a, b) in x_bits.iter().zip(y_bits.iter()) {
             self.connect_bit(*a, *b);
         }
     }
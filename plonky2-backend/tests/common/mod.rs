@@ -0,0 +1,52 @@
+//! Fixtures shared by the integration tests, which can't use the factories of the unit tests
+//! (src/circuit_translation/tests/factories) since those are only compiled for the library's
+//! own tests.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use acir::circuit::{Circuit, ExpressionWidth, Opcode, Program, PublicInputs};
+use acir::native_types::{Expression, Witness, WitnessMap, WitnessStack};
+use acir_field::{AcirField, FieldElement};
+
+/// (x + 4)^2 = 25 with x public, so x = 1 and the intermediate witness is 5. It's the same
+/// program as circuit_factory::x_plus_4_squared_equals_25_program.
+pub fn x_plus_4_squared_equals_25_program() -> Program<FieldElement> {
+    let x = Witness(0);
+    let x_plus_4 = Witness(1);
+    let circuit = Circuit {
+        current_witness_index: 1,
+        expression_width: ExpressionWidth::Unbounded,
+        opcodes: vec![
+            Opcode::AssertZero(Expression {
+                mul_terms: vec![],
+                linear_combinations: vec![
+                    (FieldElement::one(), x),
+                    (-FieldElement::one(), x_plus_4),
+                ],
+                q_c: FieldElement::from(4_u128),
+            }),
+            Opcode::AssertZero(Expression {
+                mul_terms: vec![(FieldElement::one(), x_plus_4, x_plus_4)],
+                linear_combinations: vec![],
+                q_c: -FieldElement::from(25_u128),
+            }),
+        ],
+        private_parameters: BTreeSet::new(),
+        public_parameters: PublicInputs(BTreeSet::from([x])),
+        return_values: PublicInputs(BTreeSet::new()),
+        assert_messages: Default::default(),
+        recursive: false,
+    };
+    Program {
+        functions: vec![circuit],
+        unconstrained_functions: vec![],
+    }
+}
+
+pub fn serialized_witness_stack(x: u128, x_plus_4: u128) -> Vec<u8> {
+    let witness_map = WitnessMap::from(BTreeMap::from([
+        (Witness(0), FieldElement::from(x)),
+        (Witness(1), FieldElement::from(x_plus_4)),
+    ]));
+    WitnessStack::from(witness_map).try_into().unwrap()
+}
@@ -0,0 +1,245 @@
+//! Drives the binary through its command line the way Nargo drives a backend: Barretenberg's
+//! commands and flags, the CRS path that is ignored, the default paths relative to the Noir
+//! project and "-" for the standard input and output.
+
+mod common;
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use acir::circuit::Program;
+use base64::Engine;
+use common::{serialized_witness_stack, x_plus_4_squared_equals_25_program};
+use serde_json::json;
+
+/// A Noir project with the compiled program and its witnesses where Nargo leaves them. It lives
+/// in a temporary directory, which is removed when the project is dropped.
+struct NoirProject {
+    path: PathBuf,
+}
+
+impl NoirProject {
+    fn new(name: &str, x: u128, x_plus_4: u128) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("plonky2_backend_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("target")).unwrap();
+
+        let bytecode = Program::serialize_program(&x_plus_4_squared_equals_25_program());
+        let program_json = json!({
+            "bytecode": base64::prelude::BASE64_STANDARD.encode(&bytecode),
+            "abi": {
+                "parameters": [{"name": "x", "type": {"kind": "field"}, "visibility": "public"}],
+                "return_type": null,
+                "error_types": {},
+            },
+        });
+        fs::write(path.join("target/program.json"), program_json.to_string()).unwrap();
+        fs::write(path.join("target/program.bytecode"), bytecode).unwrap();
+        fs::write(path.join("target/witness.gz"), serialized_witness_stack(x, x_plus_4)).unwrap();
+        Self { path }
+    }
+}
+
+impl Drop for NoirProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn _run_backend(project_path: &Path, args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_plonky2-backend"))
+        .current_dir(project_path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("The backend binary could not be run");
+    let _ = child.stdin.take().unwrap().write_all(stdin);
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_a_proof_written_to_stdout_verifies_with_the_written_verifying_key() {
+    // Given
+    let project = NoirProject::new("stdout_proof", 1, 5);
+    let write_vk_args = [
+        "write_vk",
+        "-c",
+        "./crs",
+        "-b",
+        "./target/program.bytecode",
+        "-o",
+        "./target/vk",
+    ];
+    assert!(_run_backend(&project.path, &write_vk_args, &[])
+        .status
+        .success());
+
+    // When
+    let prove_args = [
+        "prove",
+        "-c",
+        "./crs",
+        "-b",
+        "./target/program.bytecode",
+        "-w",
+        "./target/witness.gz",
+        "-o",
+        "-",
+    ];
+    let prove_output = _run_backend(&project.path, &prove_args, &[]);
+
+    // Then
+    assert!(prove_output.status.success());
+    fs::write(project.path.join("proof"), &prove_output.stdout).unwrap();
+    let verify_args = [
+        "verify",
+        "-c",
+        "./crs",
+        "-k",
+        "./target/vk",
+        "-p",
+        "./proof",
+    ];
+    assert!(_run_backend(&project.path, &verify_args, &[])
+        .status
+        .success());
+}
+
+#[test]
+fn test_the_commands_use_the_default_paths_of_barretenberg() {
+    // Given
+    let project = NoirProject::new("default_paths", 1, 5);
+
+    // When
+    let prove_output = _run_backend(&project.path, &["prove"], &[]);
+    let write_vk_output = _run_backend(&project.path, &["write_vk"], &[]);
+
+    // Then
+    assert!(prove_output.status.success());
+    assert!(write_vk_output.status.success());
+    assert!(project.path.join("proofs/proof").exists());
+    assert!(project.path.join("target/vk").exists());
+    assert!(_run_backend(&project.path, &["verify", "-v"], &[])
+        .status
+        .success());
+}
+
+#[test]
+fn test_verify_reads_the_proof_from_stdin() {
+    // Given
+    let project = NoirProject::new("stdin_proof", 1, 5);
+    assert!(_run_backend(&project.path, &["write_vk"], &[])
+        .status
+        .success());
+    let proof = _run_backend(&project.path, &["prove", "-o", "-"], &[]).stdout;
+
+    // When
+    let verify_output = _run_backend(&project.path, &["verify", "-p", "-"], &proof);
+
+    // Then
+    assert!(verify_output.status.success());
+}
+
+#[test]
+fn test_verify_fails_with_the_verifying_key_of_another_program() {
+    // Given
+    let project = NoirProject::new("another_program", 1, 5);
+    assert!(_run_backend(&project.path, &["prove"], &[])
+        .status
+        .success());
+    let write_vk_args = ["write_vk", "-b", "./target/program.bytecode"];
+    assert!(_run_backend(&project.path, &write_vk_args, &[])
+        .status
+        .success());
+
+    // When
+    let verify_output = _run_backend(&project.path, &["verify"], &[]);
+
+    // Then
+    assert!(!verify_output.status.success());
+}
+
+#[test]
+fn test_prove_and_verify_succeeds_for_witnesses_that_satisfy_the_program() {
+    let project = NoirProject::new("prove_and_verify", 1, 5);
+    let output = _run_backend(
+        &project.path,
+        &["prove_and_verify", "-c", "./crs", "-v"],
+        &[],
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_prove_and_verify_fails_for_witnesses_that_dont_satisfy_the_program() {
+    let project = NoirProject::new("prove_and_verify_invalid", 2, 5);
+    let output = _run_backend(&project.path, &["prove_and_verify"], &[]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_prove_and_verify_reads_the_witnesses_from_stdin() {
+    let project = NoirProject::new("prove_and_verify_stdin", 1, 5);
+    let witness_stack = serialized_witness_stack(1, 5);
+    let output = _run_backend(
+        &project.path,
+        &["prove_and_verify", "-w", "-"],
+        &witness_stack,
+    );
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_only_one_input_can_be_read_from_stdin() {
+    let project = NoirProject::new("two_stdin_inputs", 1, 5);
+    let output = _run_backend(&project.path, &["prove", "-b", "-", "-w", "-"], &[]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_gates_prints_the_report_nargo_expects() {
+    // Given
+    let project = NoirProject::new("gates", 1, 5);
+
+    // When
+    let output = _run_backend(&project.path, &["gates", "-c", "./crs"], &[]);
+
+    // Then
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let main_function = &report["functions"][0];
+    assert_eq!(2, main_function["acir_opcodes"]);
+    assert_eq!(
+        2,
+        main_function["gates_per_opcode"].as_array().unwrap().len()
+    );
+    assert!(main_function["circuit_size"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_a_program_json_with_leading_whitespace_is_read_as_json() {
+    // Given
+    let project = NoirProject::new("indented_program", 1, 5);
+    let program_json = fs::read_to_string(project.path.join("target/program.json")).unwrap();
+    let indented_program_json = format!("\n  {}", program_json);
+    fs::write(project.path.join("target/indented.json"), indented_program_json).unwrap();
+    let write_vk_args = ["write_vk", "-b", "./target/indented.json"];
+    assert!(_run_backend(&project.path, &write_vk_args, &[])
+        .status
+        .success());
+
+    // When
+    let prove_output = _run_backend(&project.path, &["prove"], &[]);
+
+    // Then
+    // The key only matches the proof of program.json if the ABI was read too.
+    assert!(prove_output.status.success());
+    assert!(_run_backend(&project.path, &["verify"], &[])
+        .status
+        .success());
+}